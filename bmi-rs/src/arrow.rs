@@ -0,0 +1,142 @@
+use crate::bmi::{BmiResult, RefValues, ValueType, Values};
+use crate::errors::BmiInvalidValue;
+use arrow_array::types::{
+    ArrowPrimitiveType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, UInt16Type,
+    UInt32Type, UInt64Type,
+};
+use arrow_array::{Array, ArrayRef, PrimitiveArray};
+use arrow_buffer::ScalarBuffer;
+use arrow_schema::DataType;
+use std::sync::Arc;
+
+impl From<ValueType> for DataType {
+    fn from(value: ValueType) -> Self {
+        match value {
+            ValueType::I16 => DataType::Int16,
+            ValueType::U16 => DataType::UInt16,
+            ValueType::I32 => DataType::Int32,
+            ValueType::U32 => DataType::UInt32,
+            ValueType::I64 => DataType::Int64,
+            ValueType::U64 => DataType::UInt64,
+            ValueType::F32 => DataType::Float32,
+            ValueType::F64 => DataType::Float64,
+        }
+    }
+}
+
+impl TryFrom<&DataType> for ValueType {
+    type Error = BmiInvalidValue;
+
+    fn try_from(value: &DataType) -> Result<Self, Self::Error> {
+        match value {
+            DataType::Int16 => Ok(ValueType::I16),
+            DataType::UInt16 => Ok(ValueType::U16),
+            DataType::Int32 => Ok(ValueType::I32),
+            DataType::UInt32 => Ok(ValueType::U32),
+            DataType::Int64 => Ok(ValueType::I64),
+            DataType::UInt64 => Ok(ValueType::U64),
+            DataType::Float32 => Ok(ValueType::F32),
+            DataType::Float64 => Ok(ValueType::F64),
+            _ => Err(BmiInvalidValue),
+        }
+    }
+}
+
+fn primitive_array<T: ArrowPrimitiveType>(values: Vec<T::Native>) -> ArrayRef {
+    Arc::new(PrimitiveArray::<T>::new(ScalarBuffer::from(values), None))
+}
+
+/// Borrow the values of a null-free primitive `array`.
+fn primitive_values<T: ArrowPrimitiveType>(array: &dyn Array) -> BmiResult<&[T::Native]> {
+    match array.as_any().downcast_ref::<PrimitiveArray<T>>() {
+        Some(array) if array.null_count() == 0 => Ok(array.values()),
+        _ => BmiInvalidValue.into(),
+    }
+}
+
+macro_rules! arrow_conversions {
+    ($($name:ident; $arrow_type:ty),*$(,)?) => {
+        impl Values {
+            /// Convert into an Arrow primitive array without copying.
+            pub fn into_arrow(self) -> ArrayRef {
+                match self {
+                    $(Values::$name(v) => primitive_array::<$arrow_type>(v),)*
+                }
+            }
+
+            /// Copy a null-free Arrow primitive array.
+            /// Returns Err([`BmiInvalidValue`]) if `array` has nulls or a data type without a
+            /// [`ValueType`] equivalent.
+            pub fn try_from_arrow(array: &dyn Array) -> BmiResult<Values> {
+                match ValueType::try_from(array.data_type())? {
+                    $(ValueType::$name => Ok(Values::$name(primitive_values::<$arrow_type>(array)?.to_vec())),)*
+                }
+            }
+        }
+
+        impl<'a> RefValues<'a> {
+            /// Copy into an Arrow primitive array.
+            /// Arrow arrays own their buffers, use [`Values::into_arrow`] to avoid the copy.
+            pub fn to_arrow(&self) -> ArrayRef {
+                match self {
+                    $(RefValues::$name(v) => primitive_array::<$arrow_type>(v.to_vec()),)*
+                }
+            }
+
+            /// Borrow the values of a null-free Arrow primitive array without copying.
+            /// Returns Err([`BmiInvalidValue`]) if `array` has nulls or a data type without a
+            /// [`ValueType`] equivalent.
+            pub fn try_from_arrow(array: &'a dyn Array) -> BmiResult<RefValues<'a>> {
+                match ValueType::try_from(array.data_type())? {
+                    $(ValueType::$name => Ok(RefValues::$name(primitive_values::<$arrow_type>(array)?)),)*
+                }
+            }
+        }
+    };
+}
+
+arrow_conversions!(
+    I16; Int16Type,
+    U16; UInt16Type,
+    I32; Int32Type,
+    U32; UInt32Type,
+    I64; Int64Type,
+    U64; UInt64Type,
+    F32; Float32Type,
+    F64; Float64Type,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Float64Array, Int32Array};
+
+    #[test]
+    fn test_round_trip() {
+        let values = Values::I32(vec![1, 2, 3]);
+        let ptr = match &values {
+            Values::I32(v) => v.as_ptr(),
+            _ => unreachable!(),
+        };
+        let array = values.into_arrow();
+        assert_eq!(array.data_type(), &DataType::Int32);
+        let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(array.values().as_ptr(), ptr);
+
+        let borrowed = RefValues::try_from_arrow(array).unwrap();
+        match &borrowed {
+            RefValues::I32(v) => assert_eq!(v.as_ptr(), ptr),
+            _ => panic!("expected i32 values"),
+        }
+        let copied = Values::try_from_arrow(&borrowed.to_arrow()).unwrap();
+        assert_eq!(copied.to_f64_vec(), [1., 2., 3.]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let nulls = Float64Array::from(vec![Some(1.), None]);
+        assert!(RefValues::try_from_arrow(&nulls).is_err());
+        let strings = arrow_array::StringArray::from(vec!["a"]);
+        assert!(Values::try_from_arrow(&strings).is_err());
+    }
+}
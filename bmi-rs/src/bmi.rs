@@ -1,4 +1,6 @@
-use crate::errors::{BmiIndexOutOfBounds, BmiNotImplementedError};
+use crate::clock::steps_between;
+use crate::errors::{BmiIndexOutOfBounds, BmiInvalidValue, BmiNotImplementedError};
+use crate::grid::Grid;
 use std::error::Error;
 
 pub const MAX_COMPONENT_NAME: u32 = 2048;
@@ -8,7 +10,7 @@ pub const MAX_TYPE_NAME: u32 = 2048;
 
 /// Bmi variable grid
 /// [element location](https://bmi.csdms.io/en/stable/bmi.var_funcs.html#get-var-location).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Location {
     Node,
     Edge,
@@ -27,7 +29,7 @@ impl std::fmt::Display for Location {
 
 /// Bmi
 /// [grid type](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-type).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GridType {
     Scalar,
     Points,
@@ -52,9 +54,39 @@ impl std::fmt::Display for GridType {
     }
 }
 
+impl std::str::FromStr for Location {
+    type Err = BmiInvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node" => Ok(Location::Node),
+            "edge" => Ok(Location::Edge),
+            "face" => Ok(Location::Face),
+            _ => Err(BmiInvalidValue),
+        }
+    }
+}
+
+impl std::str::FromStr for GridType {
+    type Err = BmiInvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scalar" => Ok(GridType::Scalar),
+            "points" => Ok(GridType::Points),
+            "vector" => Ok(GridType::Vector),
+            "unstructured" => Ok(GridType::Unstructured),
+            "structured_quadrilateral" => Ok(GridType::StructuredQuadrilateral),
+            "rectilinear" => Ok(GridType::Rectilinear),
+            "uniform_rectilinear" => Ok(GridType::UniformRectilinear),
+            _ => Err(BmiInvalidValue),
+        }
+    }
+}
+
 // TODO: how to add isize and usize?
 /// Represents the numeric data type of an item in a [`Bmi`] variable's array.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ValueType {
     /// signed 16 bit int
     I16,
@@ -85,6 +117,26 @@ impl ValueType {
     }
 }
 
+/// Parse a bmi-c `get_var_type` type name, e.g. `"double"` or `"int"`.
+/// Numpy style names, e.g. `"float64"` or `"int32"`, are also accepted.
+impl std::str::FromStr for ValueType {
+    type Err = BmiInvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" | "int16" => Ok(ValueType::I16),
+            "unsigned short" | "uint16" => Ok(ValueType::U16),
+            "int" | "int32" => Ok(ValueType::I32),
+            "unsigned int" | "uint32" => Ok(ValueType::U32),
+            "long" | "long long" | "int64" => Ok(ValueType::I64),
+            "unsigned long" | "unsigned long long" | "uint64" => Ok(ValueType::U64),
+            "float" | "float32" => Ok(ValueType::F32),
+            "double" | "float64" => Ok(ValueType::F64),
+            _ => Err(BmiInvalidValue),
+        }
+    }
+}
+
 // NOTE: consider a more generic container type than Vec<T>, maybe Box<[T]>?
 /// An owned `Vec` of a numeric type wrapped with type information.
 #[derive(Debug, Clone)]
@@ -223,6 +275,51 @@ impl_from_ref_t_for_ref_values!(
 impl_len!(RefValues<'_>; I16, U16, I32, U32, I64, U64, F32, F64,);
 impl_value_type!(RefValues<'_>; I16, U16, I32, U32, I64, U64, F32, F64,);
 
+macro_rules! impl_to_f64_vec {
+    ($t:ty; $($name:ident),*$(,)?) => {
+        impl $t {
+            /// Return a copy of the values cast to `f64`.
+            ///
+            /// Note, 64 bit integer values greater than 2^53 lose precision.
+            pub fn to_f64_vec(&self) -> Vec<f64> {
+                match self {
+                    $(Self::$name(v) => v.iter().map(|v| *v as f64).collect(),)*
+                }
+            }
+        }
+    };
+}
+
+impl_to_f64_vec!(Values; I16, U16, I32, U32, I64, U64, F32, F64,);
+impl_to_f64_vec!(RefValues<'_>; I16, U16, I32, U32, I64, U64, F32, F64,);
+
+macro_rules! from_f64_slice {
+    ($value_type:expr, $src:expr; $($name:ident; $t:ty),*$(,)?) => {
+        match $value_type {
+            $(ValueType::$name => Values::$name($src.iter().map(|v| *v as $t).collect()),)*
+        }
+    };
+}
+
+impl Values {
+    /// Create [`Values`] of `value_type` by casting each item in `src`.
+    ///
+    /// Note, casts to integer types truncate toward zero and saturate at the type's bounds.
+    pub fn from_f64_slice(value_type: ValueType, src: &[f64]) -> Values {
+        from_f64_slice!(
+            value_type, src;
+            I16;i16,
+            U16;u16,
+            I32;i32,
+            U32;u32,
+            I64;i64,
+            U64;u64,
+            F32;f32,
+            F64;f64,
+        )
+    }
+}
+
 pub type BmiResult<T> = Result<T, Box<dyn Error>>;
 
 macro_rules! values_at_indices {
@@ -276,8 +373,27 @@ pub trait Bmi {
     /// [csdms bmi `update_until`](https://bmi.csdms.io/en/stable/bmi.control_funcs.html#update-until)
     /// docs for more info.
     ///
+    /// Note, the default implementation calls [`update`] the number of whole [`get_time_step`]
+    /// sized steps between [`get_current_time`] and `then`.
+    /// It returns Err([`BmiTimeInPast`]) if `then` is before the current time and
+    /// Err([`BmiPartialTimeStep`]) if `then` does not fall on a time step boundary.
+    /// In both cases [`update`] is not called.
+    /// Implementations that support partial time steps should override this method.
+    /// See [`steps_between`] for details.
+    ///
     /// [`get_current_time`]: #tymethod.get_current_time
-    fn update_until(&mut self, then: f64) -> BmiResult<()>;
+    /// [`update`]: #tymethod.update
+    /// [`get_time_step`]: #tymethod.get_time_step
+    /// [`BmiTimeInPast`]: crate::errors::BmiTimeInPast
+    /// [`BmiPartialTimeStep`]: crate::errors::BmiPartialTimeStep
+    /// [`steps_between`]: crate::clock::steps_between
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        let steps = steps_between(self.get_current_time(), then, self.get_time_step())?;
+        for _ in 0..steps {
+            self.update()?;
+        }
+        Ok(())
+    }
 
     /// Perform any necessary tasks after exiting the model’s time loop.
     /// Note, the implementing type is not consumed and therefore not dropped.
@@ -522,17 +638,38 @@ pub trait Bmi {
     /// docs for more info.
     fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()>;
 
-    // NOTE: can we implement a default here?
-    /// Return the [`GridType`] for a given grid identifier.
+    /// Return `true` if the model serves ngen's `serialization_*` variables, see
+    /// [`Stateful`](crate::state::Stateful). Only then does the bmi-c wrapper expose
+    /// `serialization_state`'s pointer and read its length from the state blob's header.
+    ///
+    /// Sealed: the [`Sealed`] argument cannot be named outside of this crate, so only
+    /// [`Stateful`](crate::state::Stateful) overrides this method.
+    #[doc(hidden)]
+    fn serializes_state(&self, _: Sealed) -> bool {
+        false
+    }
+
+    /// Return the [`Grid`] descriptor for a given grid identifier.
     ///
+    /// The default implementations of the other grid information methods are derived from
+    /// the returned descriptor, so implementing this method is sufficient to describe a grid.
     /// Default implementation returns Err([`BmiNotImplementedError`]).
+    #[allow(unused_variables)]
+    fn get_grid(&self, grid: i32) -> BmiResult<&Grid> {
+        BmiNotImplementedError.into()
+    }
+
+    /// Return the [`GridType`] for a given grid identifier.
+    ///
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_type`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-type)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_type(&self, grid: i32) -> BmiResult<GridType> {
-        BmiNotImplementedError.into()
+        Ok(self.get_grid(grid)?.grid_type())
     }
 
     /* Grid information */
@@ -542,14 +679,15 @@ pub trait Bmi {
     ///
     /// This function is needed for every
     /// [grid type](https://bmi.csdms.io/en/stable/model_grids.html#model-grids).
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_rank`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-rank)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_rank(&self, grid: i32) -> BmiResult<u32> {
-        BmiNotImplementedError.into()
+        Ok(self.get_grid(grid)?.rank())
     }
 
     /// Return the total number of elements (or
@@ -559,14 +697,16 @@ pub trait Bmi {
     /// This function is needed for every
     /// [grid type](https://bmi.csdms.io/en/stable/model_grids.html#model-grids).
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`], returning
+    /// Err([`BmiInvalidValue`]) if the size does not fit in a `u32`.
     ///
     /// See
     /// [csdms bmi `get_grid_size`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-size)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_size(&self, grid: i32) -> BmiResult<u32> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.size()
     }
 
     /* Uniform rectilinear */
@@ -576,16 +716,16 @@ pub trait Bmi {
     /// This function is used for describing all
     /// [structured grids](https://bmi.csdms.io/en/stable/model_grids.html#structured-grids).
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_shape`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-shape)
     /// docs for more info.
     ///
     /// [`get_grid_rank`]: #tymethod.get_grid_rank
-    #[allow(unused_variables)]
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_shape(&self, grid: i32) -> BmiResult<&[u32]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.shape()
     }
 
     /// Return the distance between the
@@ -596,14 +736,15 @@ pub trait Bmi {
     /// [uniform rectilinear](https://bmi.csdms.io/en/stable/model_grids.html#uniform-rectilinear)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_spacing`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-spacing)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_spacing(&self, grid: i32) -> BmiResult<&[f64]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.spacing()
     }
 
     /// Return the coordinates of the lower-left corner of the model grid.
@@ -612,14 +753,15 @@ pub trait Bmi {
     /// [uniform rectilinear](https://bmi.csdms.io/en/stable/model_grids.html#uniform-rectilinear)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_origin`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-origin)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_origin(&self, grid: i32) -> BmiResult<&[f64]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.origin()
     }
 
     /* Non-uniform rectilinear, curvilinear */
@@ -636,14 +778,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_rank`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-x)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_x(&self, grid: i32) -> BmiResult<&[f64]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.x()
     }
 
     /// Return locations of the grid
@@ -659,14 +802,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_rank`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-y)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_y(&self, grid: i32) -> BmiResult<&[f64]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.y()
     }
 
     /// Return locations of the grid
@@ -682,14 +826,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_rank`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-z)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_z(&self, grid: i32) -> BmiResult<&[f64]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.z()
     }
 
     /* Unstructured */
@@ -701,14 +846,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_node_count`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-node-count)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_node_count(&self, grid: i32) -> BmiResult<u32> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.node_count()
     }
 
     /// Get the number of
@@ -719,14 +865,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_edge_count`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-edge-count)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_edge_count(&self, grid: i32) -> BmiResult<u32> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.edge_count()
     }
 
     /// Get the number of
@@ -737,14 +884,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_face_count`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-face-count)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_face_count(&self, grid: i32) -> BmiResult<u32> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.face_count()
     }
 
     /// Return the edge-node connectivity.
@@ -754,16 +902,16 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_edge_nodes`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-edge-nodes)
     /// docs for more info.
     ///
     /// [`get_grid_edge_count`]: #tymethod.get_grid_edge_count
-    #[allow(unused_variables)]
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_edge_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.edge_nodes()
     }
 
     /// Return the face-edge connectivity.
@@ -773,16 +921,16 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_face_edges`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-face-edges)
     /// docs for more info.
     ///
     /// [`get_grid_nodes_per_face`]: #tymethod.get_grid_nodes_per_face
-    #[allow(unused_variables)]
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_face_edges(&self, grid: i32) -> BmiResult<&[u32]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.face_edges()
     }
 
     /// Return the face-node connectivity.
@@ -791,14 +939,15 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_face_nodes`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-face-nodes)
     /// docs for more info.
-    #[allow(unused_variables)]
+    ///
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_face_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.face_nodes()
     }
 
     /// Return the number of nodes for each face.
@@ -808,19 +957,88 @@ pub trait Bmi {
     /// [unstructured](https://bmi.csdms.io/en/stable/model_grids.html#unstructured-grids)
     /// grids.
     ///
-    /// Default implementation returns Err([`BmiNotImplementedError`]).
+    /// Default implementation is derived from [`get_grid`].
     ///
     /// See
     /// [csdms bmi `get_grid_nodes_per_face`](https://bmi.csdms.io/en/stable/bmi.grid_funcs.html#get-grid-nodes-per-face)
     /// docs for more info.
     ///
     /// [`get_grid_face_count`]: #tymethod.get_grid_face_count
-    #[allow(unused_variables)]
+    /// [`get_grid`]: #method.get_grid
     fn get_grid_nodes_per_face(&self, grid: i32) -> BmiResult<&[u32]> {
-        BmiNotImplementedError.into()
+        self.get_grid(grid)?.nodes_per_face()
     }
 }
 
+/// Implement the [`Bmi`] grid information methods by forwarding each call to `$field`.
+///
+/// Used by [`Bmi`] implementations that wrap another [`Bmi`] and do not alter its grids.
+macro_rules! forward_grid_funcs {
+    ($field:ident) => {
+        fn get_grid(&self, grid: i32) -> BmiResult<&crate::grid::Grid> {
+            self.$field.get_grid(grid)
+        }
+        fn get_grid_type(&self, grid: i32) -> BmiResult<GridType> {
+            self.$field.get_grid_type(grid)
+        }
+        fn get_grid_rank(&self, grid: i32) -> BmiResult<u32> {
+            self.$field.get_grid_rank(grid)
+        }
+        fn get_grid_size(&self, grid: i32) -> BmiResult<u32> {
+            self.$field.get_grid_size(grid)
+        }
+        fn get_grid_shape(&self, grid: i32) -> BmiResult<&[u32]> {
+            self.$field.get_grid_shape(grid)
+        }
+        fn get_grid_spacing(&self, grid: i32) -> BmiResult<&[f64]> {
+            self.$field.get_grid_spacing(grid)
+        }
+        fn get_grid_origin(&self, grid: i32) -> BmiResult<&[f64]> {
+            self.$field.get_grid_origin(grid)
+        }
+        fn get_grid_x(&self, grid: i32) -> BmiResult<&[f64]> {
+            self.$field.get_grid_x(grid)
+        }
+        fn get_grid_y(&self, grid: i32) -> BmiResult<&[f64]> {
+            self.$field.get_grid_y(grid)
+        }
+        fn get_grid_z(&self, grid: i32) -> BmiResult<&[f64]> {
+            self.$field.get_grid_z(grid)
+        }
+        fn get_grid_node_count(&self, grid: i32) -> BmiResult<u32> {
+            self.$field.get_grid_node_count(grid)
+        }
+        fn get_grid_edge_count(&self, grid: i32) -> BmiResult<u32> {
+            self.$field.get_grid_edge_count(grid)
+        }
+        fn get_grid_face_count(&self, grid: i32) -> BmiResult<u32> {
+            self.$field.get_grid_face_count(grid)
+        }
+        fn get_grid_edge_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
+            self.$field.get_grid_edge_nodes(grid)
+        }
+        fn get_grid_face_edges(&self, grid: i32) -> BmiResult<&[u32]> {
+            self.$field.get_grid_face_edges(grid)
+        }
+        fn get_grid_face_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
+            self.$field.get_grid_face_nodes(grid)
+        }
+        fn get_grid_nodes_per_face(&self, grid: i32) -> BmiResult<&[u32]> {
+            self.$field.get_grid_nodes_per_face(grid)
+        }
+    };
+}
+pub(crate) use forward_grid_funcs;
+
+mod sealed {
+    /// Argument of [`Bmi::serializes_state`](super::Bmi::serializes_state), public but not
+    /// nameable outside of this crate.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Sealed;
+}
+
+pub(crate) use sealed::Sealed;
+
 /// Bootstraps the `model` so it can be called through the
 /// [bmi-c](https://github.com/csdms/bmi-c/blob/031c5abf0ff0e75bec7aea48a064611138a0de64/bmi.h)
 /// interface.
@@ -950,4 +1168,22 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn test_default_update_until() {
+        let mut model = crate::testing::Reservoir::new(0.1);
+        model.update_until(0.3).unwrap();
+        assert_eq!(model.updates, 3);
+        assert_eq!(model.get_current_time(), model.clock.time_at(3));
+
+        // no-op
+        model.update_until(model.get_current_time()).unwrap();
+        assert_eq!(model.updates, 3);
+
+        let err = model.update_until(0.45).unwrap_err();
+        assert!(err.is::<crate::errors::BmiPartialTimeStep>());
+        let err = model.update_until(0.1).unwrap_err();
+        assert!(err.is::<crate::errors::BmiTimeInPast>());
+        assert_eq!(model.updates, 3);
+    }
 }
@@ -0,0 +1,319 @@
+use crate::errors::BmiInvalidValue;
+use crate::param::{self, Parameter};
+use crate::rng::Rng;
+use crate::{Bmi, BmiResult};
+
+/// Goodness of fit of a simulated series to an observed series.
+///
+/// Pairs where either value is not finite (e.g. missing observations stored as `NaN`) are
+/// ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Objective {
+    /// Nash-Sutcliffe efficiency, `(-inf, 1]`, 1 is a perfect fit.
+    #[default]
+    Nse,
+    /// Kling-Gupta efficiency (Gupta et al. 2009), `(-inf, 1]`, 1 is a perfect fit.
+    Kge,
+    /// Root mean square error, `[0, inf)`, 0 is a perfect fit.
+    Rmse,
+    /// Mean error, `sim - obs`, 0 is a perfect fit.
+    Bias,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn std_dev(values: &[f64], mean: f64) -> f64 {
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+impl Objective {
+    /// Return the objective of `sim` compared to `obs`, or `NaN` if no pairs are finite.
+    pub fn score(&self, sim: &[f64], obs: &[f64]) -> f64 {
+        let (sim, obs): (Vec<f64>, Vec<f64>) = sim
+            .iter()
+            .zip(obs)
+            .filter(|(s, o)| s.is_finite() && o.is_finite())
+            .unzip();
+        if sim.is_empty() {
+            return f64::NAN;
+        }
+        let (sim_mean, obs_mean) = (mean(&sim), mean(&obs));
+        let sse: f64 = sim.iter().zip(&obs).map(|(s, o)| (s - o).powi(2)).sum();
+        match self {
+            Objective::Nse => 1. - sse / obs.iter().map(|o| (o - obs_mean).powi(2)).sum::<f64>(),
+            Objective::Kge => {
+                let (sim_std, obs_std) = (std_dev(&sim, sim_mean), std_dev(&obs, obs_mean));
+                let cov = sim
+                    .iter()
+                    .zip(&obs)
+                    .map(|(s, o)| (s - sim_mean) * (o - obs_mean))
+                    .sum::<f64>()
+                    / sim.len() as f64;
+                let r = cov / (sim_std * obs_std);
+                let alpha = sim_std / obs_std;
+                let beta = sim_mean / obs_mean;
+                1. - ((r - 1.).powi(2) + (alpha - 1.).powi(2) + (beta - 1.).powi(2)).sqrt()
+            }
+            Objective::Rmse => (sse / sim.len() as f64).sqrt(),
+            Objective::Bias => sim_mean - obs_mean,
+        }
+    }
+
+    /// Return the objective as a loss to minimize, `inf` if it is not finite.
+    pub fn loss(&self, sim: &[f64], obs: &[f64]) -> f64 {
+        let score = self.score(sim, obs);
+        let loss = match self {
+            Objective::Nse | Objective::Kge => 1. - score,
+            Objective::Rmse => score,
+            Objective::Bias => score.abs(),
+        };
+        match loss.is_finite() {
+            true => loss,
+            false => f64::INFINITY,
+        }
+    }
+}
+
+/// An evaluated parameter set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// Parameter values, in [`Calibration`] parameter order.
+    pub params: Vec<f64>,
+    /// [`Objective::score`], `NaN` if the model run failed.
+    pub score: f64,
+    /// [`Objective::loss`], `inf` if the model run failed.
+    pub loss: f64,
+}
+
+/// [`Calibration::run`] result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibrated {
+    /// Best evaluated parameter set.
+    pub best: Evaluation,
+    /// Every evaluation, in order. The first is the parameters' default values.
+    pub history: Vec<Evaluation>,
+}
+
+/// Calibrates a [`Bmi`] model's parameters against an observed series using
+/// Dynamically Dimensioned Search (DDS, Tolson and Shoemaker 2007).
+///
+/// Each evaluation creates an initialized model with the factory, sets each [`Parameter`]
+/// with [`set_value`] (to every element of the variable), then calls [`update`] once per
+/// observation, recording the `output` variable's value at [`index`] after each update.
+/// The search starts from the parameters' default values and is reproducible for a given
+/// [`seed`].
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::calibrate::{Calibration, Objective};
+/// use bmi_rs::param::Parameter;
+/// # fn run<M: Bmi>(factory: impl Fn() -> bmi_rs::BmiResult<M>, observed: Vec<f64>) {
+///
+/// let params = vec![Parameter::new("k", "d-1", 0.01, 1.).default(0.1)];
+/// let calibrated = Calibration::new(factory, params, "discharge", observed)
+///     .objective(Objective::Kge)
+///     .iterations(500)
+///     .run();
+/// println!("{:?} {}", calibrated.best.params, calibrated.best.score);
+/// # }
+/// ```
+///
+/// [`set_value`]: Bmi::set_value
+/// [`update`]: Bmi::update
+/// [`index`]: Calibration::index
+/// [`seed`]: Calibration::seed
+pub struct Calibration<F> {
+    factory: F,
+    params: Vec<Parameter>,
+    output: String,
+    index: usize,
+    observed: Vec<f64>,
+    objective: Objective,
+    iterations: usize,
+    perturbation: f64,
+    seed: u64,
+}
+
+impl<M: Bmi, F: Fn() -> BmiResult<M>> Calibration<F> {
+    /// Calibrate `params` of the models created by `factory`, comparing the `output`
+    /// variable to `observed`, one observation per update.
+    pub fn new(
+        factory: F,
+        params: Vec<Parameter>,
+        output: impl Into<String>,
+        observed: Vec<f64>,
+    ) -> Self {
+        Self {
+            factory,
+            params,
+            output: output.into(),
+            index: 0,
+            observed,
+            objective: Objective::default(),
+            iterations: 1000,
+            perturbation: 0.2,
+            seed: 0,
+        }
+    }
+
+    /// Compare the `output` variable's value at `index` (default 0) to the observations.
+    pub fn index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Set the objective function (default [`Objective::Nse`]).
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Set the number of evaluations, including the initial one (default 1000).
+    /// At least the initial evaluation is run.
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Set the DDS neighborhood perturbation, as a fraction of each parameter's range
+    /// (default 0.2). Values are clamped to `[0, 1]`; `NaN` keeps the current perturbation.
+    pub fn perturbation(mut self, perturbation: f64) -> Self {
+        if !perturbation.is_nan() {
+            self.perturbation = perturbation.clamp(0., 1.);
+        }
+        self
+    }
+
+    /// Seed the random number generator that perturbs parameters (default 0).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run the model with `params`, returning the simulated series.
+    pub fn simulate(&self, params: &[f64]) -> BmiResult<Vec<f64>> {
+        let mut model = (self.factory)()?;
+        param::set_values(&mut model, &self.params, params)?;
+        let mut sim = Vec::with_capacity(self.observed.len());
+        for _ in 0..self.observed.len() {
+            model.update()?;
+            match model
+                .get_value_ptr(&self.output)?
+                .to_f64_vec()
+                .get(self.index)
+            {
+                Some(value) => sim.push(*value),
+                None => return BmiInvalidValue.into(),
+            }
+        }
+        model.finalize()?;
+        Ok(sim)
+    }
+
+    /// Score `params`. A failed model run scores `NaN` with an infinite loss.
+    pub fn evaluate(&self, params: &[f64]) -> Evaluation {
+        let (score, loss) = match self.simulate(params) {
+            Ok(sim) => (
+                self.objective.score(&sim, &self.observed),
+                self.objective.loss(&sim, &self.observed),
+            ),
+            Err(_) => (f64::NAN, f64::INFINITY),
+        };
+        Evaluation {
+            params: params.to_vec(),
+            score,
+            loss,
+        }
+    }
+
+    /// Run the search.
+    pub fn run(&self) -> Calibrated {
+        let mut rng = Rng::new(self.seed);
+        let initial: Vec<f64> = self.params.iter().map(Parameter::default_value).collect();
+        let mut best = self.evaluate(&initial);
+        let mut history = vec![best.clone()];
+        let ln_iterations = (self.iterations as f64).ln();
+        for i in 1..self.iterations {
+            // probability of perturbing each dimension decreases as the search progresses
+            let p = 1. - (i as f64).ln() / ln_iterations;
+            let mut selected: Vec<usize> = (0..self.params.len())
+                .filter(|_| rng.uniform() < p)
+                .collect();
+            if selected.is_empty() && !self.params.is_empty() {
+                selected.push((rng.next_u64() % self.params.len() as u64) as usize);
+            }
+            let mut candidate = best.params.clone();
+            for j in selected {
+                let (min, max) = (self.params[j].min(), self.params[j].max());
+                let mut x = candidate[j] + self.perturbation * (max - min) * rng.normal();
+                // reflect at the bounds, clamping if still outside
+                if x < min {
+                    x = min + (min - x);
+                }
+                if x > max {
+                    x = max - (x - max);
+                }
+                candidate[j] = x.clamp(min, max);
+            }
+            let evaluation = self.evaluate(&candidate);
+            if evaluation.loss <= best.loss {
+                best = evaluation.clone();
+            }
+            history.push(evaluation);
+        }
+        Calibrated { best, history }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+
+    #[test]
+    fn test_objectives() {
+        let obs = [1., 2., 3., f64::NAN];
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(Objective::Nse.score(&[1., 2., 3., 0.], &obs), 1.));
+        assert!(close(Objective::Kge.score(&obs, &obs), 1.));
+        assert!(close(Objective::Nse.score(&[2., 2., 2.], &obs), 0.));
+        assert!(close(Objective::Rmse.score(&[2., 3., 4.], &obs), 1.));
+        assert!(close(Objective::Bias.score(&[0., 1., 2.], &obs), -1.));
+        assert!(close(Objective::Bias.loss(&[0., 1., 2.], &obs), 1.));
+        assert!(Objective::Rmse.score(&[], &obs).is_nan());
+        assert_eq!(Objective::Nse.loss(&[], &obs), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_dds() {
+        let observed: Vec<f64> = (1..=20).map(|t| 1.3 * t as f64).collect();
+        let params = vec![Parameter::new("rate", "m s-1", 0., 2.).default(0.2)];
+        let calibration = Calibration::new(|| Ok(Reservoir::new(1.)), params, "storage", observed)
+            .iterations(200)
+            .seed(7);
+        let calibrated = calibration.run();
+        assert_eq!(calibrated.history.len(), 200);
+        assert_eq!(calibrated.history[0].params, [0.2]);
+        assert!((calibrated.best.params[0] - 1.3).abs() < 0.01);
+        assert!(calibrated.best.score > 0.999);
+        assert_eq!(calibration.run(), calibrated);
+
+        // failed runs are never the best
+        let calibration = Calibration::new(|| Ok(Reservoir::new(1.)), vec![], "missing", vec![1.]);
+        let calibration = calibration.iterations(2);
+        let calibrated = calibration.run();
+        assert!(calibrated.best.score.is_nan());
+        assert_eq!(calibrated.best.loss, f64::INFINITY);
+
+        // out of range settings are clamped
+        let calibration = calibration
+            .iterations(0)
+            .perturbation(f64::INFINITY)
+            .perturbation(f64::NAN);
+        assert_eq!((calibration.iterations, calibration.perturbation), (1, 1.));
+        assert_eq!(calibration.run().history.len(), 1);
+    }
+}
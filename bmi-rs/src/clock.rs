@@ -0,0 +1,245 @@
+use crate::BmiResult;
+use crate::errors::{BmiInvalidTimeStep, BmiInvalidValue, BmiPartialTimeStep, BmiTimeInPast};
+use crate::state::{StateError, StateField};
+
+/// Fraction of a time step a requested time may be off of a step boundary and still be
+/// considered on it.
+/// Absorbs floating point error in host computed times (e.g. `start + n * dt`).
+pub const STEP_TOLERANCE: f64 = 1e-6;
+
+/// Return the number of whole `dt` sized steps needed to advance from `current` to `then`.
+///
+/// Returns `Ok(0)` if `then` is `current`.
+/// Returns Err([`BmiTimeInPast`]) if `then` is before `current`,
+/// Err([`BmiPartialTimeStep`]) if `then` does not fall on a step boundary
+/// (within [`STEP_TOLERANCE`]),
+/// Err([`BmiInvalidTimeStep`]) if `dt` is not positive and finite, and
+/// Err([`BmiInvalidValue`]) if `current` or `then` is not finite or the number of steps does
+/// not fit in a `u64`.
+pub fn steps_between(current: f64, then: f64, dt: f64) -> BmiResult<u64> {
+    if !(current.is_finite() && then.is_finite()) {
+        return BmiInvalidValue.into();
+    }
+    if then == current {
+        return Ok(0);
+    }
+    if !(dt > 0. && dt.is_finite()) {
+        return BmiInvalidTimeStep.into();
+    }
+    let steps = (then - current) / dt;
+    let whole = steps.round();
+    if (steps - whole).abs() > STEP_TOLERANCE {
+        if steps < 0. {
+            return BmiTimeInPast.into();
+        }
+        return BmiPartialTimeStep.into();
+    }
+    if whole < 0. {
+        return BmiTimeInPast.into();
+    }
+    if whole >= u64::MAX as f64 {
+        return BmiInvalidValue.into();
+    }
+    Ok(whole as u64)
+}
+
+/// Fixed time step model clock.
+///
+/// Time is tracked as an integer step count from the start time so repeatedly advancing the clock
+/// does not accumulate floating point drift.
+/// i.e. the current time is always `start + step * dt`.
+///
+/// [`Clock`] is intended to be embedded in [`Bmi`] implementations and used to back
+/// [`get_start_time`], [`get_end_time`], [`get_current_time`], and [`get_time_step`].
+///
+/// Example:
+/// ```
+/// use bmi_rs::Clock;
+///
+/// let mut clock = Clock::new(0., 1., 0.1);
+/// for _ in 0..10 {
+///     clock.advance();
+/// }
+/// assert_eq!(clock.current_time(), 1.);
+/// assert!(clock.is_finished());
+/// ```
+///
+/// [`Bmi`]: crate::Bmi
+/// [`get_start_time`]: crate::Bmi::get_start_time
+/// [`get_end_time`]: crate::Bmi::get_end_time
+/// [`get_current_time`]: crate::Bmi::get_current_time
+/// [`get_time_step`]: crate::Bmi::get_time_step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    start: f64,
+    end: f64,
+    dt: f64,
+    step: u64,
+}
+
+impl Clock {
+    /// Create a [`Clock`] at `start` that advances in `dt` sized steps.
+    ///
+    /// Panics if `dt` is not positive and finite or `end` is before `start`.
+    pub fn new(start: f64, end: f64, dt: f64) -> Self {
+        assert!(dt > 0. && dt.is_finite(), "dt must be positive and finite");
+        assert!(end >= start, "end must not be before start");
+        Self {
+            start,
+            end,
+            dt,
+            step: 0,
+        }
+    }
+
+    /// Return the start time.
+    pub fn start_time(&self) -> f64 {
+        self.start
+    }
+
+    /// Return the end time.
+    pub fn end_time(&self) -> f64 {
+        self.end
+    }
+
+    /// Return the time step size.
+    pub fn time_step(&self) -> f64 {
+        self.dt
+    }
+
+    /// Return the number of steps taken since the start time.
+    pub fn current_step(&self) -> u64 {
+        self.step
+    }
+
+    /// Return the current time, `start + step * dt`.
+    pub fn current_time(&self) -> f64 {
+        self.time_at(self.step)
+    }
+
+    /// Return the time at step `step`.
+    pub fn time_at(&self, step: u64) -> f64 {
+        self.start + step as f64 * self.dt
+    }
+
+    /// Return `true` if the current time is at or past the end time.
+    pub fn is_finished(&self) -> bool {
+        self.current_time() >= self.end - self.dt * STEP_TOLERANCE
+    }
+
+    /// Advance the clock by a single time step.
+    ///
+    /// Note, advancing past the end time is permitted.
+    pub fn advance(&mut self) {
+        self.step += 1;
+    }
+
+    /// Return the number of steps needed to advance from the current time to `then`.
+    ///
+    /// See [`steps_between`] for error conditions.
+    pub fn steps_until(&self, then: f64) -> BmiResult<u64> {
+        steps_between(self.current_time(), then, self.dt)
+    }
+
+    /// Advance the clock to `then`, returning the number of steps taken.
+    ///
+    /// The clock is not modified if an error is returned.
+    /// See [`steps_between`] for error conditions.
+    pub fn advance_until(&mut self, then: f64) -> BmiResult<u64> {
+        let steps = self.steps_until(then)?;
+        self.step += steps;
+        Ok(steps)
+    }
+
+    /// Reset the clock to the start time.
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+}
+
+impl StateField for Clock {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        self.start.write_field(buf);
+        self.end.write_field(buf);
+        self.dt.write_field(buf);
+        self.step.write_field(buf);
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        self.start.read_field(src)?;
+        self.end.read_field(src)?;
+        self.dt.read_field(src)?;
+        self.step.read_field(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_drift() {
+        let mut clock = Clock::new(0., 100., 0.1);
+        let mut summed = 0.;
+        for _ in 0..1000 {
+            clock.advance();
+            summed += 0.1;
+        }
+        assert_ne!(summed, 100.);
+        assert_eq!(clock.current_time(), 100.);
+        assert!(clock.is_finished());
+    }
+
+    #[test]
+    fn test_steps_until() {
+        let clock = Clock::new(10., 20., 0.5);
+        assert_eq!(clock.steps_until(10.).unwrap(), 0);
+        assert_eq!(clock.steps_until(12.).unwrap(), 4);
+        // accumulated float error is tolerated
+        let then = (0..7).fold(10., |t, _| t + 0.5 + 1e-12);
+        assert_eq!(clock.steps_until(then).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_partial_step() {
+        let mut clock = Clock::new(0., 10., 1.);
+        let err = clock.advance_until(2.5).unwrap_err();
+        assert!(err.is::<BmiPartialTimeStep>());
+        assert_eq!(clock.current_step(), 0);
+    }
+
+    #[test]
+    fn test_time_in_past() {
+        let mut clock = Clock::new(0., 10., 1.);
+        clock.advance_until(3.).unwrap();
+        assert!(clock.steps_until(2.).unwrap_err().is::<BmiTimeInPast>());
+        assert!(clock.steps_until(2.5).unwrap_err().is::<BmiTimeInPast>());
+    }
+
+    #[test]
+    fn test_invalid_time_step() {
+        assert!(
+            steps_between(0., 1., 0.)
+                .unwrap_err()
+                .is::<BmiInvalidTimeStep>()
+        );
+        assert!(
+            steps_between(0., 1., f64::NAN)
+                .unwrap_err()
+                .is::<BmiInvalidTimeStep>()
+        );
+        assert_eq!(steps_between(1., 1., 0.).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_invalid_times() {
+        for then in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let err = steps_between(0., then, 1.).unwrap_err();
+            assert!(err.is::<BmiInvalidValue>());
+            let err = steps_between(then, 0., 1.).unwrap_err();
+            assert!(err.is::<BmiInvalidValue>());
+        }
+        let err = steps_between(0., 1e300, 1e-300).unwrap_err();
+        assert!(err.is::<BmiInvalidValue>());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::BmiResult;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// [`Config`] parsing and lookup errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A non-empty, non-comment line is not a `key = value` pair.
+    Syntax { line: usize },
+    /// A required key is missing.
+    Missing(String),
+    /// A key's value could not be parsed.
+    Invalid { key: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax { line } => write!(f, "line {line}: expected `key = value`"),
+            ConfigError::Missing(key) => write!(f, "missing required key `{key}`"),
+            ConfigError::Invalid { key, value } => write!(f, "invalid value for `{key}`: {value}"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Ordered `key = value` configuration, as typically passed to [`Bmi::initialize`].
+///
+/// Lines starting with `#` and blank lines are ignored.
+/// Keys and values are trimmed of surrounding whitespace.
+/// If a key is repeated, the last value wins.
+///
+/// Example:
+/// ```
+/// use bmi_rs::config::Config;
+///
+/// let config = Config::parse("# forcing\nfile = forcing.csv\nunits.precip = mm s-1").unwrap();
+/// assert_eq!(config.get("file"), Some("forcing.csv"));
+/// assert_eq!(config.prefixed("units.").collect::<Vec<_>>(), [("precip", "mm s-1")]);
+/// ```
+///
+/// [`Bmi::initialize`]: crate::Bmi::initialize
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    dir: Option<PathBuf>,
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Parse configuration from a string.
+    pub fn parse(src: &str) -> Result<Config, ConfigError> {
+        let mut entries = Vec::new();
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ConfigError::Syntax { line: i + 1 });
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(ConfigError::Syntax { line: i + 1 });
+            }
+            entries.push((key.to_string(), value.trim().to_string()));
+        }
+        Ok(Config { dir: None, entries })
+    }
+
+    /// Read and parse a configuration file.
+    /// Relative paths returned by [`path`] are resolved against the file's directory.
+    ///
+    /// [`path`]: Config::path
+    pub fn read(path: impl AsRef<Path>) -> BmiResult<Config> {
+        let path = path.as_ref();
+        let mut config = Config::parse(&std::fs::read_to_string(path)?)?;
+        config.dir = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Return the value of `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Return the value of `key` or Err([`ConfigError::Missing`]).
+    pub fn require(&self, key: &str) -> Result<&str, ConfigError> {
+        self.get(key)
+            .ok_or_else(|| ConfigError::Missing(key.to_string()))
+    }
+
+    /// Parse the value of `key`, if present.
+    /// Returns Err([`ConfigError::Invalid`]) if the value cannot be parsed as a `T`.
+    pub fn parse_value<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        let Some(value) = self.get(key) else {
+            return Ok(None);
+        };
+        match value.parse() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(ConfigError::Invalid {
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    /// Return the value of `key` as a path, resolved against the configuration file's directory
+    /// if relative.
+    pub fn path(&self, key: &str) -> Option<PathBuf> {
+        self.get(key).map(|value| self.resolve(value))
+    }
+
+    /// Resolve `value` against the configuration file's directory if relative.
+    pub fn resolve(&self, value: &str) -> PathBuf {
+        match &self.dir {
+            Some(dir) => dir.join(value),
+            None => PathBuf::from(value),
+        }
+    }
+
+    /// Iterate, in order, over keys starting with `prefix` with the prefix removed.
+    pub fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.entries
+            .iter()
+            .filter_map(move |(k, v)| k.strip_prefix(prefix).map(|suffix| (suffix, v.as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse("a = 1\n\n  # comment\nb=two words\na = 3").unwrap();
+        assert_eq!(config.get("a"), Some("3"));
+        assert_eq!(config.get("b"), Some("two words"));
+        assert_eq!(config.parse_value::<u32>("a").unwrap(), Some(3));
+        assert_eq!(config.parse_value::<u32>("c").unwrap(), None);
+        assert_eq!(
+            config.parse_value::<u32>("b").unwrap_err(),
+            ConfigError::Invalid {
+                key: "b".into(),
+                value: "two words".into()
+            }
+        );
+        assert_eq!(
+            config.require("c").unwrap_err(),
+            ConfigError::Missing("c".into())
+        );
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert_eq!(
+            Config::parse("a = 1\nb").unwrap_err(),
+            ConfigError::Syntax { line: 2 }
+        );
+        assert_eq!(
+            Config::parse("= 1").unwrap_err(),
+            ConfigError::Syntax { line: 1 }
+        );
+    }
+}
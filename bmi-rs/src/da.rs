@@ -0,0 +1,340 @@
+use crate::errors::BmiInvalidValue;
+use crate::rng::Rng;
+use crate::{Bmi, BmiResult, Values};
+
+/// Solve `a x = b` for the `cols` columns of the row major `n x cols` matrix `b` in place,
+/// by Gaussian elimination with partial pivoting. Returns `None` if `a` is singular.
+fn solve(mut a: Vec<f64>, n: usize, mut b: Vec<f64>, cols: usize) -> Option<Vec<f64>> {
+    for k in 0..n {
+        let pivot = (k..n).max_by(|i, j| a[i * n + k].abs().total_cmp(&a[j * n + k].abs()))?;
+        if a[pivot * n + k].abs() < f64::EPSILON {
+            return None;
+        }
+        for c in 0..n {
+            a.swap(k * n + c, pivot * n + c);
+        }
+        for c in 0..cols {
+            b.swap(k * cols + c, pivot * cols + c);
+        }
+        for i in k + 1..n {
+            let f = a[i * n + k] / a[k * n + k];
+            for c in k..n {
+                a[i * n + c] -= f * a[k * n + c];
+            }
+            for c in 0..cols {
+                b[i * cols + c] -= f * b[k * cols + c];
+            }
+        }
+    }
+    for k in (0..n).rev() {
+        for c in 0..cols {
+            let sum: f64 = (k + 1..n).map(|j| a[k * n + j] * b[j * cols + c]).sum();
+            b[k * cols + c] = (b[k * cols + c] - sum) / a[k * n + k];
+        }
+    }
+    Some(b)
+}
+
+/// Subtract each row's mean from an `n x cols` row major matrix.
+fn anomalies(x: &[f64], cols: usize) -> Vec<f64> {
+    x.chunks(cols)
+        .flat_map(|row| {
+            let mean = row.iter().sum::<f64>() / cols as f64;
+            row.iter().map(move |v| v - mean)
+        })
+        .collect()
+}
+
+/// Stochastic Ensemble Kalman Filter (Evensen 1994; Burgers et al. 1998) analysis over an
+/// ensemble of [`Bmi`] instances.
+///
+/// A member's state vector is its [`state`] variables, read with [`get_value_ptr`] and
+/// concatenated. After the analysis each variable is written back with [`set_value`], cast to
+/// the variable's type.
+///
+/// Observations are perturbed per member with normally distributed noise of the given
+/// standard deviations. Perturbations are drawn from a stream determined by the [`seed`], the
+/// number of previous analyses, and the member's index, so a run is reproducible.
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::da::EnKF;
+/// # fn run<M: Bmi>(members: &mut [M], observed: &[f64]) -> bmi_rs::BmiResult<()> {
+///
+/// let mut enkf = EnKF::new(&["storage", "discharge"]).seed(42);
+/// for obs in observed {
+///     members.iter_mut().try_for_each(|member| member.update())?;
+///     // the second state variable is observed
+///     enkf.analysis(members.iter_mut(), &[*obs], &[0.5], |state| vec![state[1]])?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`state`]: EnKF::new
+/// [`get_value_ptr`]: Bmi::get_value_ptr
+/// [`set_value`]: Bmi::set_value
+/// [`seed`]: EnKF::seed
+#[derive(Debug, Clone)]
+pub struct EnKF {
+    state: Vec<String>,
+    inflation: f64,
+    seed: u64,
+    cycle: u64,
+}
+
+impl EnKF {
+    /// Create a filter updating the `state` variables.
+    pub fn new(state: &[&str]) -> Self {
+        Self {
+            state: state.iter().map(|name| name.to_string()).collect(),
+            inflation: 1.,
+            seed: 0,
+            cycle: 0,
+        }
+    }
+
+    /// Multiply the forecast state anomalies by `inflation` (default 1) before the analysis.
+    /// Negative values are clamped to 0; non-finite values keep the current inflation.
+    pub fn inflation(mut self, inflation: f64) -> Self {
+        if inflation.is_finite() {
+            self.inflation = inflation.max(0.);
+        }
+        self
+    }
+
+    /// Seed the random number generator that perturbs observations (default 0).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Return a member's state vector.
+    pub fn get_state<M: Bmi>(&self, model: &M) -> BmiResult<Vec<f64>> {
+        let mut state = Vec::new();
+        for name in self.state.iter() {
+            state.extend(model.get_value_ptr(name)?.to_f64_vec());
+        }
+        Ok(state)
+    }
+
+    /// Split a member's state vector into values of its state variables' types.
+    fn to_values<M: Bmi>(&self, model: &M, state: &[f64]) -> BmiResult<Vec<Values>> {
+        let mut values = Vec::with_capacity(self.state.len());
+        let mut offset = 0;
+        for name in self.state.iter() {
+            let len = model.get_value_ptr(name)?.len();
+            let Some(src) = state.get(offset..offset + len) else {
+                return BmiInvalidValue.into();
+            };
+            values.push(Values::from_f64_slice(model.get_var_type(name)?, src));
+            offset += len;
+        }
+        Ok(values)
+    }
+
+    /// Write values of the state variables, see [`to_values`](EnKF::to_values).
+    fn write<M: Bmi>(&self, model: &mut M, values: &[Values]) -> BmiResult<()> {
+        for (name, values) in self.state.iter().zip(values) {
+            model.set_value(name, values.into())?;
+        }
+        Ok(())
+    }
+
+    /// Write a member's state vector back to its state variables.
+    pub fn set_state<M: Bmi>(&self, model: &mut M, state: &[f64]) -> BmiResult<()> {
+        let values = self.to_values(model, state)?;
+        self.write(model, &values)
+    }
+
+    /// Assimilate `observed`, with error standard deviations `obs_std`, into `members`.
+    ///
+    /// `operator` maps a member's state vector to the observation space and must return
+    /// `observed.len()` values. Returns Err([`BmiInvalidValue`]) if there are fewer than two
+    /// members, lengths are inconsistent, or the innovation covariance is singular. Members
+    /// are only written once every check has passed, so they are unchanged on these errors.
+    /// If a member's [`set_value`](Bmi::set_value) fails, earlier members keep their analysis
+    /// states and the error is returned.
+    pub fn analysis<'a, M: Bmi + 'a>(
+        &mut self,
+        members: impl IntoIterator<Item = &'a mut M>,
+        observed: &[f64],
+        obs_std: &[f64],
+        operator: impl Fn(&[f64]) -> Vec<f64>,
+    ) -> BmiResult<()> {
+        let mut members: Vec<&mut M> = members.into_iter().collect();
+        let (n, m) = (members.len(), observed.len());
+        if n < 2 || obs_std.len() != m {
+            return BmiInvalidValue.into();
+        }
+        let mut states = members
+            .iter()
+            .map(|member| self.get_state(*member))
+            .collect::<BmiResult<Vec<_>>>()?;
+        let size = states[0].len();
+        if states.iter().any(|state| state.len() != size) {
+            return BmiInvalidValue.into();
+        }
+        if self.inflation != 1. {
+            for i in 0..size {
+                let mean = states.iter().map(|state| state[i]).sum::<f64>() / n as f64;
+                for state in states.iter_mut() {
+                    state[i] = mean + self.inflation * (state[i] - mean);
+                }
+            }
+        }
+        // state (size x n) and predicted observation (m x n) matrices, a column per member
+        let mut x: Vec<f64> = (0..size)
+            .flat_map(|i| states.iter().map(move |state| state[i]))
+            .collect();
+        let a = anomalies(&x, n);
+        let predicted = states
+            .iter()
+            .map(|state| operator(state))
+            .collect::<Vec<_>>();
+        if predicted.iter().any(|hx| hx.len() != m) {
+            return BmiInvalidValue.into();
+        }
+        let hx: Vec<f64> = (0..m)
+            .flat_map(|i| predicted.iter().map(move |hx| hx[i]))
+            .collect();
+        let ha = anomalies(&hx, n);
+
+        let cov = |u: &[f64], rows: usize, v: &[f64], cols: usize| -> Vec<f64> {
+            (0..rows)
+                .flat_map(|i| {
+                    (0..cols).map(move |j| {
+                        (0..n).map(|k| u[i * n + k] * v[j * n + k]).sum::<f64>() / (n - 1) as f64
+                    })
+                })
+                .collect()
+        };
+        // innovation covariance, H P H^T + R
+        let mut s = cov(&ha, m, &ha, m);
+        for (i, std) in obs_std.iter().enumerate() {
+            s[i * m + i] += std * std;
+        }
+        // innovations of perturbed observations (m x n)
+        let mut d = vec![0.; m * n];
+        for j in 0..n {
+            let mut rng = Rng::keyed(self.seed, &[self.cycle, j as u64]);
+            for i in 0..m {
+                d[i * n + j] = observed[i] + obs_std[i] * rng.normal() - hx[i * n + j];
+            }
+        }
+        let Some(z) = solve(s, m, d, n) else {
+            return BmiInvalidValue.into();
+        };
+        // x += P H^T S^-1 d
+        let pht = cov(&a, size, &ha, m);
+        for i in 0..size {
+            for j in 0..n {
+                x[i * n + j] += (0..m).map(|k| pht[i * m + k] * z[k * n + j]).sum::<f64>();
+            }
+        }
+        let values = members
+            .iter()
+            .enumerate()
+            .map(|(j, member)| {
+                let state: Vec<f64> = (0..size).map(|i| x[i * n + j]).collect();
+                self.to_values(*member, &state)
+            })
+            .collect::<BmiResult<Vec<_>>>()?;
+        for (member, values) in members.iter_mut().zip(values.iter()) {
+            self.write(*member, values)?;
+        }
+        self.cycle += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RefValues;
+    use crate::testing::Reservoir;
+
+    fn ensemble(n: usize) -> Vec<Reservoir> {
+        let mut rng = Rng::new(1);
+        (0..n)
+            .map(|_| {
+                let mut model = Reservoir::new(1.);
+                let storage = 10. + 2. * rng.normal();
+                model
+                    .set_value("storage", RefValues::F64(&[storage]))
+                    .unwrap();
+                model
+            })
+            .collect()
+    }
+
+    fn moments(members: &[Reservoir]) -> (f64, f64) {
+        let n = members.len() as f64;
+        let mean = members.iter().map(|m| m.storage[0]).sum::<f64>() / n;
+        let var = members
+            .iter()
+            .map(|m| (m.storage[0] - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.);
+        (mean, var)
+    }
+
+    #[test]
+    fn test_solve() {
+        let a = vec![2., 1., 1., 3.];
+        let x = solve(a, 2, vec![3., 5., 4., 10.], 2).unwrap();
+        assert!(
+            x.iter()
+                .zip([1., 1., 1., 3.])
+                .all(|(a, b)| (a - b).abs() < 1e-12)
+        );
+        assert!(solve(vec![1., 2., 2., 4.], 2, vec![1., 1.], 1).is_none());
+    }
+
+    #[test]
+    fn test_inflation() {
+        let enkf = EnKF::new(&["storage"]);
+        assert_eq!(enkf.clone().inflation(-1.).inflation, 0.);
+        assert_eq!(
+            enkf.clone().inflation(1.5).inflation(f64::NAN).inflation,
+            1.5
+        );
+        assert_eq!(enkf.inflation(f64::INFINITY).inflation, 1.);
+    }
+
+    #[test]
+    fn test_analysis() {
+        let mut members = ensemble(200);
+        let (prior_mean, prior_var) = moments(&members);
+        let mut enkf = EnKF::new(&["storage"]).seed(3);
+        enkf.analysis(members.iter_mut(), &[12.], &[1.], |state| state.to_vec())
+            .unwrap();
+        let (mean, var) = moments(&members);
+        let gain = prior_var / (prior_var + 1.);
+        assert!((mean - (prior_mean + gain * (12. - prior_mean))).abs() < 0.2);
+        assert!((var - (1. - gain) * prior_var).abs() < 0.2);
+
+        // seeded perturbations are reproducible, failed analyses do not count
+        let mut other = ensemble(200);
+        let mut enkf = EnKF::new(&["storage"]).seed(3);
+        assert!(
+            enkf.analysis(other.iter_mut(), &[12.], &[1.], |_| vec![])
+                .is_err()
+        );
+        enkf.analysis(other.iter_mut(), &[12.], &[1.], |state| state.to_vec())
+            .unwrap();
+        assert!(
+            members
+                .iter()
+                .zip(&other)
+                .all(|(a, b)| a.storage == b.storage)
+        );
+
+        let mut one = ensemble(1);
+        assert!(
+            enkf.analysis(one.iter_mut(), &[12.], &[1.], |s| s.to_vec())
+                .is_err()
+        );
+    }
+}
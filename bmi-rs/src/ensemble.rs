@@ -0,0 +1,345 @@
+use crate::clock::STEP_TOLERANCE;
+use crate::{Bmi, BmiResult};
+use std::error::Error;
+use std::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Mutex;
+
+/// Why an ensemble [`Member`] stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemberError {
+    /// A [`Bmi`] call or the factory returned an error.
+    Failed(String),
+    /// A [`Bmi`] call or the factory panicked.
+    Panicked(String),
+}
+
+impl fmt::Display for MemberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemberError::Failed(msg) => write!(f, "member failed: {msg}"),
+            MemberError::Panicked(msg) => write!(f, "member panicked: {msg}"),
+        }
+    }
+}
+
+impl Error for MemberError {}
+
+/// A model instance of an [`Ensemble`] and its recorded outputs.
+#[derive(Debug)]
+pub struct Member<M> {
+    model: Option<M>,
+    error: Option<MemberError>,
+    times: Vec<f64>,
+    series: Vec<Vec<f64>>,
+}
+
+impl<M> Member<M> {
+    /// Return the model, if the factory created it.
+    pub fn model(&self) -> Option<&M> {
+        self.model.as_ref()
+    }
+
+    /// Return the model, if the factory created it.
+    pub fn model_mut(&mut self) -> Option<&mut M> {
+        self.model.as_mut()
+    }
+
+    /// Return why the member stopped, if it did.
+    /// Stopped members are skipped by later [`Ensemble`] calls.
+    pub fn error(&self) -> Option<&MemberError> {
+        self.error.as_ref()
+    }
+
+    /// Return the model time after each recorded update.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// Return the recorded values of the `i`th [`Ensemble::outputs`] variable as `f64`,
+    /// flattened in time major order: one variable length record per [`times`] entry.
+    ///
+    /// [`times`]: Member::times
+    pub fn series(&self, i: usize) -> &[f64] {
+        &self.series[i]
+    }
+
+    fn is_active(&self) -> bool {
+        self.model.is_some() && self.error.is_none()
+    }
+}
+
+/// Run `f` catching errors and panics as a [`MemberError`].
+fn isolate<T>(f: impl FnOnce() -> BmiResult<T>) -> Result<T, MemberError> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => Err(MemberError::Failed(err.to_string())),
+        Err(payload) => Err(MemberError::Panicked(
+            match (
+                payload.downcast_ref::<&str>(),
+                payload.downcast_ref::<String>(),
+            ) {
+                (Some(msg), _) => msg.to_string(),
+                (_, Some(msg)) => msg.clone(),
+                _ => "unknown panic".to_string(),
+            },
+        )),
+    }
+}
+
+/// Call `f` with each item of `items` and its index on a pool of `threads` scoped threads.
+pub(crate) fn for_each_par<T: Send>(
+    items: &mut [T],
+    threads: usize,
+    f: impl Fn(usize, &mut T) + Sync,
+) {
+    let queue = Mutex::new(items.iter_mut().enumerate());
+    std::thread::scope(|s| {
+        for _ in 0..threads.min(queue.lock().unwrap().len()) {
+            s.spawn(|| {
+                loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some((i, item)) = next else {
+                        break;
+                    };
+                    f(i, item);
+                }
+            });
+        }
+    });
+}
+
+/// Many instances of a [`Bmi`] model advanced concurrently on a thread pool.
+///
+/// Members are created by a factory, typically perturbing each member's parameters or
+/// forcings by its index. An error or panic in one member stops only that member, see
+/// [`Member::error`]. After each update the values of the selected [`outputs`] are recorded
+/// in each member's time series.
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::ensemble::Ensemble;
+/// # fn run<M: Bmi + Send>(factory: impl Fn(usize) -> bmi_rs::BmiResult<M> + Sync) {
+///
+/// let mut ensemble = Ensemble::new(100, 8, factory).outputs(&["discharge"]);
+/// ensemble.initialize(|i| format!("member_{i}.cfg"));
+/// ensemble.update_until(86400.);
+/// for member in ensemble.members() {
+///     match member.error() {
+///         None => println!("{:?}", member.series(0)),
+///         Some(err) => eprintln!("{err}"),
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`outputs`]: Ensemble::outputs
+pub struct Ensemble<M> {
+    members: Vec<Member<M>>,
+    outputs: Vec<String>,
+    threads: usize,
+}
+
+impl<M: Bmi + Send> Ensemble<M> {
+    /// Create `size` members, calling `factory` with each member's index. Members are created
+    /// and advanced on `threads` threads, e.g. [`available_parallelism`].
+    ///
+    /// Panics if `threads` is 0.
+    ///
+    /// [`available_parallelism`]: std::thread::available_parallelism
+    pub fn new(
+        size: usize,
+        threads: usize,
+        factory: impl Fn(usize) -> BmiResult<M> + Sync,
+    ) -> Self {
+        assert!(threads > 0, "threads must be positive");
+        let mut ensemble = Self {
+            members: (0..size)
+                .map(|_| Member {
+                    model: None,
+                    error: None,
+                    times: Vec::new(),
+                    series: Vec::new(),
+                })
+                .collect(),
+            outputs: Vec::new(),
+            threads,
+        };
+        for_each_par(
+            &mut ensemble.members,
+            ensemble.threads,
+            |i, member| match isolate(|| factory(i)) {
+                Ok(model) => member.model = Some(model),
+                Err(err) => member.error = Some(err),
+            },
+        );
+        ensemble
+    }
+
+    /// Record `names` after each update.
+    pub fn outputs(mut self, names: &[&str]) -> Self {
+        self.outputs = names.iter().map(|name| name.to_string()).collect();
+        self.members
+            .iter_mut()
+            .for_each(|member| member.series = vec![Vec::new(); names.len()]);
+        self
+    }
+
+    /// Call `f` with each active member's index and model, stopping members it fails for.
+    fn for_each_model(&mut self, f: impl Fn(usize, &mut M) -> BmiResult<()> + Sync) {
+        for_each_par(&mut self.members, self.threads, |i, member| {
+            if !member.is_active() {
+                return;
+            }
+            let model = member.model.as_mut().unwrap();
+            if let Err(err) = isolate(|| f(i, model)) {
+                member.error = Some(err);
+            }
+        });
+    }
+
+    /// [`initialize`] each member with the configuration file for its index.
+    ///
+    /// [`initialize`]: Bmi::initialize
+    pub fn initialize(&mut self, config_file: impl Fn(usize) -> String + Sync) {
+        self.for_each_model(|i, model| model.initialize(&config_file(i)));
+    }
+
+    /// [`update`] each member until its current time is at least `then` (within
+    /// [`STEP_TOLERANCE`] of its time step), recording the selected outputs after every update.
+    /// A member whose time does not advance on an update fails.
+    ///
+    /// [`update`]: Bmi::update
+    /// [`STEP_TOLERANCE`]: crate::clock::STEP_TOLERANCE
+    pub fn update_until(&mut self, then: f64) {
+        let outputs = &self.outputs;
+        for_each_par(&mut self.members, self.threads, |_, member| {
+            if !member.is_active() {
+                return;
+            }
+            let Member {
+                model,
+                error,
+                times,
+                series,
+            } = member;
+            let model = model.as_mut().unwrap();
+            let result = isolate(|| {
+                let tolerance = model.get_time_step().abs() * STEP_TOLERANCE;
+                while model.get_current_time() < then - tolerance {
+                    let current = model.get_current_time();
+                    model.update()?;
+                    if model.get_current_time() <= current {
+                        return Err(format!("model time did not advance past {current}").into());
+                    }
+                    for (name, series) in outputs.iter().zip(series.iter_mut()) {
+                        series.extend(model.get_value_ptr(name)?.to_f64_vec());
+                    }
+                    times.push(model.get_current_time());
+                }
+                Ok(())
+            });
+            if let Err(err) = result {
+                *error = Some(err);
+            }
+        });
+    }
+
+    /// [`finalize`] each active member.
+    ///
+    /// [`finalize`]: Bmi::finalize
+    pub fn finalize(&mut self) {
+        self.for_each_model(|_, model| model.finalize());
+    }
+
+    /// Return the models of members that have not stopped, e.g. for a [`da::EnKF`] analysis.
+    ///
+    /// [`da::EnKF`]: crate::da::EnKF
+    pub fn models_mut(&mut self) -> impl Iterator<Item = &mut M> {
+        self.members
+            .iter_mut()
+            .filter(|member| member.is_active())
+            .filter_map(|member| member.model.as_mut())
+    }
+
+    /// Return every member, including stopped members, in index order.
+    pub fn members(&self) -> &[Member<M>] {
+        &self.members
+    }
+
+    /// Return every member mutably, e.g. to access a member's model with
+    /// [`Member::model_mut`].
+    pub fn members_mut(&mut self) -> &mut [Member<M>] {
+        &mut self.members
+    }
+
+    /// Consume the ensemble, returning its members in index order.
+    pub fn into_members(self) -> Vec<Member<M>> {
+        self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RefValues;
+    use crate::errors::BmiInvalidValue;
+    use crate::testing::Reservoir;
+
+    #[test]
+    fn test_ensemble() {
+        let factory = |i: usize| -> BmiResult<Reservoir> {
+            match i {
+                1 => BmiInvalidValue.into(),
+                2 => panic!("member {i}"),
+                _ => {
+                    let mut model = Reservoir::new(1.);
+                    model.set_value("rate", RefValues::F64(&[i as f64]))?;
+                    Ok(model)
+                }
+            }
+        };
+        let mut ensemble = Ensemble::new(5, 2, factory).outputs(&["storage", "flux"]);
+        ensemble.initialize(|_| String::new());
+        ensemble.update_until(3.);
+        // a member that panics after initialization is stopped, the rest continue
+        ensemble.for_each_model(|i, _| match i {
+            3 => panic!("member {i}"),
+            _ => Ok(()),
+        });
+        ensemble.update_until(5.);
+
+        let members = ensemble.members();
+        assert_eq!(
+            members[1].error(),
+            Some(&MemberError::Failed("invalid value".to_string()))
+        );
+        assert_eq!(
+            members[2].error(),
+            Some(&MemberError::Panicked("member 2".to_string()))
+        );
+        assert!(members[2].model().is_none());
+        assert!(matches!(members[3].error(), Some(MemberError::Panicked(_))));
+        assert_eq!(members[3].times(), [1., 2., 3.]);
+        assert!(members[0].error().is_none() && members[4].error().is_none());
+        assert_eq!(members[4].times(), [1., 2., 3., 4., 5.]);
+        assert_eq!(members[4].series(0), [4., 8., 12., 16., 20.]);
+        assert_eq!(members[4].series(1), [4.; 5]);
+        assert_eq!(members[0].series(0), [0.; 5]);
+    }
+
+    #[test]
+    fn test_stalled() {
+        // a time step below the start time's precision does not advance the time
+        let factory = |_| {
+            let mut model = Reservoir::new(1.);
+            model.clock = crate::Clock::new(1e20, 2e20, 1.);
+            Ok(model)
+        };
+        let mut ensemble = Ensemble::new(1, 1, factory);
+        ensemble.update_until(1.5e20);
+        let error = ensemble.members()[0].error();
+        assert!(matches!(error, Some(MemberError::Failed(msg)) if msg.contains("did not advance")));
+    }
+}
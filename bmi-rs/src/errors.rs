@@ -1,4 +1,4 @@
-use crate::BmiResult;
+use crate::{BmiResult, Location};
 use std::error::Error;
 use std::fmt;
 
@@ -27,3 +27,60 @@ macro_rules! err {
 
 err!(BmiNotImplementedError, "not implemented");
 err!(BmiIndexOutOfBounds, "index out of bounds");
+err!(BmiTimeInPast, "time in past");
+err!(BmiPartialTimeStep, "partial time step");
+err!(BmiInvalidTimeStep, "invalid time step");
+err!(BmiUnknownVariable, "unknown variable");
+err!(BmiInvalidValue, "invalid value");
+
+/// Errors of the [`geojson`](crate::geojson), [`netcdf`](crate::netcdf), and
+/// [`vtk`](crate::vtk) writers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputError {
+    /// A variable is not on the written grid.
+    Grid { name: String, grid: i32 },
+    /// A variable's location is not supported on the written grid.
+    Location { name: String, location: Location },
+    /// A variable's length differs from the number of elements at its location, or changed
+    /// since the writer was created.
+    Length {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A variable or offset exceeds the format's size limits.
+    TooLarge(String),
+    /// A name is used by more than one dimension or variable, e.g. a model variable named
+    /// after a generated coordinate.
+    Conflict(String),
+    /// A face has a non-finite node coordinate the format cannot represent.
+    Coordinate { face: usize },
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::Grid { name, grid } => write!(f, "`{name}` is not on grid {grid}"),
+            OutputError::Location { name, location } => {
+                write!(
+                    f,
+                    "`{name}`: {location} values are not supported on the grid"
+                )
+            }
+            OutputError::Length {
+                name,
+                expected,
+                found,
+            } => write!(f, "`{name}`: expected {expected} values, found {found}"),
+            OutputError::TooLarge(name) => write!(f, "`{name}` exceeds the format's size limits"),
+            OutputError::Conflict(name) => {
+                write!(f, "`{name}` names more than one dimension or variable")
+            }
+            OutputError::Coordinate { face } => {
+                write!(f, "face {face} has a non-finite coordinate")
+            }
+        }
+    }
+}
+
+impl Error for OutputError {}
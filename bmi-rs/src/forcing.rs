@@ -0,0 +1,9 @@
+/// [`Bmi`] forcing provider backed by ngen/AORC style CSV files.
+///
+/// [`Bmi`]: crate::Bmi
+pub mod csv;
+
+/// [`Bmi`] forcing provider backed by analytic functions of time, for tests.
+///
+/// [`Bmi`]: crate::Bmi
+pub mod synthetic;
@@ -0,0 +1,692 @@
+use crate::clock::{Clock, steps_between};
+use crate::config::Config;
+use crate::errors::{BmiIndexOutOfBounds, BmiUnknownVariable};
+use crate::names::Names;
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+/// Units of the columns found in ngen AORC forcing files.
+/// Used when units are not given by the header or configuration.
+const AORC_UNITS: [(&str, &str); 9] = [
+    ("APCP_surface", "kg m-2"),
+    ("DLWRF_surface", "W m-2"),
+    ("DSWRF_surface", "W m-2"),
+    ("PRES_surface", "Pa"),
+    ("SPFH_2maboveground", "kg kg-1"),
+    ("TMP_2maboveground", "K"),
+    ("UGRD_10maboveground", "m s-1"),
+    ("VGRD_10maboveground", "m s-1"),
+    ("precip_rate", "mm s-1"),
+];
+
+/// CSV forcing file errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvError {
+    /// The file has no header or fewer than two time steps.
+    TooShort,
+    /// A required column is not in the header.
+    MissingColumn(String),
+    /// A row does not have the same number of columns as the header.
+    Width { line: usize },
+    /// A time or value could not be parsed.
+    Parse { line: usize, value: String },
+    /// A time (and id) appears more than once.
+    Duplicate { line: usize },
+    /// A value is missing for a time and id.
+    Missing { time: f64, id: String },
+    /// Times are not evenly spaced and increasing.
+    TimeStep { time: f64 },
+    /// Files do not have the same columns and times.
+    Mismatch(String),
+    /// [`Bmi::initialize`] has not been called.
+    NotInitialized,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::TooShort => write!(f, "expected a header and at least two time steps"),
+            CsvError::MissingColumn(name) => write!(f, "missing column `{name}`"),
+            CsvError::Width { line } => write!(f, "line {line}: unexpected number of columns"),
+            CsvError::Parse { line, value } => write!(f, "line {line}: cannot parse `{value}`"),
+            CsvError::Duplicate { line } => write!(f, "line {line}: duplicate time"),
+            CsvError::Missing { time, id } => write!(f, "missing value for `{id}` at {time}"),
+            CsvError::TimeStep { time } => write!(f, "uneven time step at {time}"),
+            CsvError::Mismatch(file) => write!(f, "`{file}` columns or times do not match"),
+            CsvError::NotInitialized => write!(f, "forcing is not initialized"),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+/// Return the seconds since the unix epoch of a `YYYY-MM-DD[( |T)HH:MM[:SS]][Z]` datetime.
+fn parse_datetime(s: &str) -> Option<f64> {
+    let s = s.strip_suffix('Z').unwrap_or(s);
+    let (date, time) = match s.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !(1..=12).contains(&month) || !(1..=days_in_month).contains(&day) {
+        return None;
+    }
+    let mut seconds = 0.;
+    if let Some(time) = time {
+        let mut time = time.splitn(3, ':');
+        let h: f64 = time.next()?.parse().ok()?;
+        let m: f64 = time.next()?.parse().ok()?;
+        let s: f64 = time.next().map_or(Some(0.), |s| s.parse().ok())?;
+        seconds = h * 3600. + m * 60. + s;
+    }
+
+    // days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    Some(days as f64 * 86400. + seconds)
+}
+
+fn parse_time(s: &str) -> Option<(f64, bool)> {
+    match s.parse::<f64>() {
+        Ok(t) => Some((t, false)),
+        Err(_) => parse_datetime(s).map(|t| (t, true)),
+    }
+}
+
+fn split_row(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').map(|cell| cell.trim().trim_matches('"'))
+}
+
+/// Column `name [units]` header to name and optional units.
+fn split_header(cell: &str) -> (String, Option<String>) {
+    match cell.split_once('[') {
+        Some((name, units)) => (
+            name.trim().to_string(),
+            Some(units.trim_end_matches(']').trim().to_string()),
+        ),
+        None => (cell.to_string(), None),
+    }
+}
+
+/// A parsed forcing file.
+/// `data` holds a `times.len() * ids.len()` time major vector per variable.
+#[derive(Debug)]
+struct Table {
+    ids: Vec<String>,
+    names: Vec<String>,
+    units: Vec<Option<String>>,
+    times: Vec<f64>,
+    datetime: bool,
+    data: Vec<Vec<f64>>,
+}
+
+impl Table {
+    fn parse(src: &str, time_column: &str, id_column: Option<&str>) -> Result<Table, CsvError> {
+        let mut lines = src
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or(CsvError::TooShort)?;
+        let header: Vec<&str> = split_row(header).collect();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|cell| cell.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CsvError::MissingColumn(name.to_string()))
+        };
+        let time_idx = column(time_column)?;
+        let id_idx = id_column.map(column).transpose()?;
+        let var_idxs: Vec<usize> = (0..header.len())
+            .filter(|i| *i != time_idx && Some(*i) != id_idx)
+            .collect();
+        let (names, units) = var_idxs.iter().map(|i| split_header(header[*i])).unzip();
+
+        let mut times: Vec<f64> = Vec::new();
+        let mut time_lookup: HashMap<u64, usize> = HashMap::new();
+        let mut ids: Vec<String> = Vec::new();
+        let mut id_lookup: HashMap<String, usize> = HashMap::new();
+        let mut datetime = false;
+        let mut rows: Vec<(usize, usize, usize, Vec<f64>)> = Vec::new();
+        for (i, line) in lines {
+            let line_no = i + 1;
+            let cells: Vec<&str> = split_row(line).collect();
+            if cells.len() != header.len() {
+                return Err(CsvError::Width { line: line_no });
+            }
+            let parse_err = |value: &str| CsvError::Parse {
+                line: line_no,
+                value: value.to_string(),
+            };
+            let (time, is_datetime) =
+                parse_time(cells[time_idx]).ok_or_else(|| parse_err(cells[time_idx]))?;
+            datetime |= is_datetime;
+            let t = *time_lookup.entry(time.to_bits()).or_insert_with(|| {
+                times.push(time);
+                times.len() - 1
+            });
+            let id = id_idx.map_or("", |idx| cells[idx]);
+            let n = *id_lookup.entry(id.to_string()).or_insert_with(|| {
+                ids.push(id.to_string());
+                ids.len() - 1
+            });
+            let values = var_idxs
+                .iter()
+                .map(|idx| cells[*idx].parse().map_err(|_| parse_err(cells[*idx])))
+                .collect::<Result<Vec<f64>, _>>()?;
+            rows.push((line_no, t, n, values));
+        }
+        if times.len() < 2 {
+            return Err(CsvError::TooShort);
+        }
+
+        let size = times.len() * ids.len();
+        let mut filled = vec![false; size];
+        let mut data = vec![vec![f64::NAN; size]; var_idxs.len()];
+        for (line, t, n, values) in rows {
+            let idx = t * ids.len() + n;
+            if filled[idx] {
+                return Err(CsvError::Duplicate { line });
+            }
+            filled[idx] = true;
+            for (var, value) in std::iter::zip(&mut data, values) {
+                var[idx] = value;
+            }
+        }
+        if let Some(idx) = filled.iter().position(|filled| !filled) {
+            return Err(CsvError::Missing {
+                time: times[idx / ids.len()],
+                id: ids[idx % ids.len()].clone(),
+            });
+        }
+
+        Ok(Table {
+            ids,
+            names,
+            units,
+            times,
+            datetime,
+            data,
+        })
+    }
+
+    /// Append `other`'s catchments, which must have the same columns and times.
+    fn extend(&mut self, other: Table, file: &str) -> Result<(), CsvError> {
+        if other.names != self.names || other.times != self.times {
+            return Err(CsvError::Mismatch(file.to_string()));
+        }
+        let (n, m) = (self.ids.len(), other.ids.len());
+        for (var, other_var) in std::iter::zip(&mut self.data, other.data) {
+            let mut merged = Vec::with_capacity(var.len() + other_var.len());
+            for t in 0..self.times.len() {
+                merged.extend_from_slice(&var[t * n..(t + 1) * n]);
+                merged.extend_from_slice(&other_var[t * m..(t + 1) * m]);
+            }
+            *var = merged;
+        }
+        self.ids.extend(other.ids);
+        Ok(())
+    }
+}
+
+/// [`Bmi`] forcing provider that reads ngen/AORC style CSV files.
+///
+/// A forcing file has a header, a time column, and a column per variable.
+/// Times are either numbers or `YYYY-MM-DD HH:MM:SS` datetimes (converted to seconds since the
+/// unix epoch) and must be evenly spaced.
+/// Each variable column is exposed as an `f64` output variable holding the current row's value
+/// for each catchment.
+/// [`update`] advances one row.
+///
+/// [`initialize`] accepts either the path to a `.csv` file or a [`Config`] file with keys:
+///
+/// - `file`: forcing file path, or `files`: comma separated forcing file paths. Without an
+///   `id_column`, each file holds one catchment whose id is the file's stem (e.g. `cat-1`
+///   for `cat-1.csv`).
+/// - `id_column` (optional): column holding catchment ids if a single file holds many
+///   catchments.
+/// - `time_column` (optional): time column name, `time` (case-insensitive) by default.
+/// - `time_units` (optional): units of numeric times, `s` by default.
+/// - `units.<variable>` (optional): units of `<variable>`.
+///
+/// Units may also be given in the header as `name [units]`.
+/// Standard AORC column units are used otherwise, and `1` if the column is not known.
+///
+/// Before [`initialize`], time methods return `NaN` and other methods return errors.
+///
+/// All variables share grid `0`, a [`GridType::Scalar`] grid when there is a single catchment
+/// and a [`GridType::Vector`] grid of [`ids`] length otherwise.
+///
+/// Example config:
+/// ```text
+/// files = cat-1.csv, cat-2.csv
+/// units.precip_rate = mm s-1
+/// ```
+///
+/// [`update`]: Bmi::update
+/// [`initialize`]: Bmi::initialize
+/// [`ids`]: CsvForcing::ids
+#[derive(Debug, Default)]
+pub struct CsvForcing {
+    ids: Vec<String>,
+    names: Names,
+    units: Vec<String>,
+    times: Vec<f64>,
+    data: Vec<Vec<f64>>,
+    time_units: String,
+    shape: [u32; 1],
+    clock: Option<Clock>,
+}
+
+impl CsvForcing {
+    /// Create an uninitialized forcing, see [`initialize`](Bmi::initialize).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the catchment ids in the order values are stored.
+    /// The id of a single catchment file without an id column is empty.
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// Return the time of each row.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    fn clock(&self) -> BmiResult<&Clock> {
+        match self.clock.as_ref() {
+            Some(clock) => Ok(clock),
+            None => Err(Box::new(CsvError::NotInitialized)),
+        }
+    }
+
+    /// Return `time` of the clock, `NaN` before [`initialize`].
+    ///
+    /// [`initialize`]: Bmi::initialize
+    fn time(&self, time: fn(&Clock) -> f64) -> f64 {
+        self.clock.as_ref().map_or(f64::NAN, time)
+    }
+
+    fn var_index(&self, name: &str) -> BmiResult<usize> {
+        match self.names.position(name) {
+            Some(idx) => Ok(idx),
+            None => BmiUnknownVariable.into(),
+        }
+    }
+}
+
+impl Bmi for CsvForcing {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        let config = if config_file.ends_with(".csv") {
+            Config::parse(&format!("file = {config_file}"))?
+        } else {
+            Config::read(config_file)?
+        };
+        let time_column = config.get("time_column").unwrap_or("time");
+        let id_column = config.get("id_column");
+
+        let files: Vec<&str> = match config.get("files") {
+            Some(files) => files.split(',').map(str::trim).collect(),
+            None => vec![config.require("file")?],
+        };
+        let mut table: Option<Table> = None;
+        for file in &files {
+            let path = config.resolve(file);
+            let mut t = Table::parse(&std::fs::read_to_string(&path)?, time_column, id_column)?;
+            if config.get("files").is_some() && id_column.is_none() {
+                let stem = Path::new(file).file_stem().and_then(|s| s.to_str());
+                t.ids = vec![stem.unwrap_or(file).to_string()];
+            }
+            match table.as_mut() {
+                Some(table) => table.extend(t, file)?,
+                None => table = Some(t),
+            }
+        }
+        let table = table.ok_or(CsvError::TooShort)?;
+
+        let dt = table.times[1] - table.times[0];
+        for (i, time) in table.times.iter().enumerate() {
+            match steps_between(table.times[0], *time, dt) {
+                Ok(step) if step == i as u64 => {}
+                _ => return Err(Box::new(CsvError::TimeStep { time: *time })),
+            }
+        }
+
+        self.units = std::iter::zip(&table.names, table.units)
+            .map(|(name, units)| {
+                config
+                    .get(&format!("units.{name}"))
+                    .map(str::to_string)
+                    .or(units)
+                    .or_else(|| {
+                        AORC_UNITS
+                            .iter()
+                            .find(|(n, _)| n == name)
+                            .map(|(_, u)| u.to_string())
+                    })
+                    .unwrap_or_else(|| "1".to_string())
+            })
+            .collect();
+        self.time_units = match table.datetime {
+            true => "s".to_string(),
+            false => config.get("time_units").unwrap_or("s").to_string(),
+        };
+        self.names = Names::new(table.names);
+        self.shape = [table.ids.len() as u32];
+        self.clock = Some(Clock::new(
+            table.times[0],
+            table.times[table.times.len() - 1],
+            dt,
+        ));
+        self.ids = table.ids;
+        self.times = table.times;
+        self.data = table.data;
+        Ok(())
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        let clock = self.clock()?;
+        self.update_until(clock.time_at(clock.current_step() + 1))
+    }
+
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        let clock = self.clock()?;
+        let steps = clock.steps_until(then)?;
+        if clock.current_step() + steps >= self.times.len() as u64 {
+            return BmiIndexOutOfBounds.into();
+        }
+        self.clock.as_mut().unwrap().advance_until(then)?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn get_component_name(&self) -> &str {
+        "csv forcing"
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        &[]
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.names.as_slice()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        self.var_index(name)?;
+        Ok(0)
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        self.var_index(name)?;
+        Ok(ValueType::F64)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        Ok(&self.units[self.var_index(name)?])
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        self.var_index(name)?;
+        Ok((self.ids.len() * size_of::<f64>()) as u32)
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        self.var_index(name)?;
+        Ok(Location::Node)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.time(Clock::current_time)
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.time(Clock::start_time)
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.time(Clock::end_time)
+    }
+
+    fn get_time_units(&self) -> &str {
+        &self.time_units
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.time(Clock::time_step)
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        let var = &self.data[self.var_index(name)?];
+        let n = self.ids.len();
+        let row = self.clock()?.current_step() as usize;
+        Ok(RefValues::F64(&var[row * n..(row + 1) * n]))
+    }
+
+    fn set_value(&mut self, _name: &str, _src: RefValues) -> BmiResult<()> {
+        BmiUnknownVariable.into()
+    }
+
+    fn set_value_at_indices(
+        &mut self,
+        _name: &str,
+        _inds: &[u32],
+        _src: RefValues,
+    ) -> BmiResult<()> {
+        BmiUnknownVariable.into()
+    }
+
+    fn get_grid_type(&self, grid: i32) -> BmiResult<GridType> {
+        match (grid, self.ids.len()) {
+            (0, 1) => Ok(GridType::Scalar),
+            (0, _) => Ok(GridType::Vector),
+            _ => BmiIndexOutOfBounds.into(),
+        }
+    }
+
+    fn get_grid_rank(&self, grid: i32) -> BmiResult<u32> {
+        match self.get_grid_type(grid)? {
+            GridType::Scalar => Ok(0),
+            _ => Ok(1),
+        }
+    }
+
+    fn get_grid_size(&self, grid: i32) -> BmiResult<u32> {
+        self.get_grid_type(grid)?;
+        Ok(self.ids.len() as u32)
+    }
+
+    fn get_grid_shape(&self, grid: i32) -> BmiResult<&[u32]> {
+        match self.get_grid_type(grid)? {
+            GridType::Scalar => Ok(&[]),
+            _ => Ok(&self.shape),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::tempdir;
+
+    fn write(dir: &Path, name: &str, src: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, src).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        assert_eq!(parse_datetime("1970-01-01"), Some(0.));
+        assert_eq!(parse_datetime("2015-12-01 00:00:00"), Some(1448928000.));
+        assert_eq!(
+            parse_datetime("2015-12-01T01:30Z"),
+            Some(1448928000. + 5400.)
+        );
+        assert_eq!(parse_datetime("1969-12-31 23:00:00"), Some(-3600.));
+        assert_eq!(parse_datetime("2015-13-01"), None);
+        assert_eq!(parse_datetime("2021-02-31"), None);
+        assert_eq!(parse_datetime("2021-02-29"), None);
+        assert_eq!(parse_datetime("2100-02-29"), None);
+        assert_eq!(parse_datetime("2000-02-29"), Some(951782400.));
+        assert_eq!(parse_datetime("2021-04-31"), None);
+        assert_eq!(parse_datetime("foo"), None);
+    }
+
+    #[test]
+    fn test_single_file() {
+        let dir = tempdir("csv-single");
+        let src = "time,APCP_surface,T2D [degC]\n\
+                   2015-12-01 00:00:00,0.5,1\n\
+                   2015-12-01 01:00:00,1.5,2\n\
+                   2015-12-01 02:00:00,2.5,3\n";
+        let file = write(&dir, "cat-1.csv", src);
+        let mut forcing = CsvForcing::new();
+        forcing.initialize(&file).unwrap();
+
+        assert_eq!(forcing.get_output_var_names(), ["APCP_surface", "T2D"]);
+        assert_eq!(forcing.get_var_units("APCP_surface").unwrap(), "kg m-2");
+        assert_eq!(forcing.get_var_units("T2D").unwrap(), "degC");
+        assert_eq!(forcing.get_time_step(), 3600.);
+        assert_eq!(forcing.get_start_time(), 1448928000.);
+        assert!(matches!(
+            forcing.get_grid_type(0).unwrap(),
+            GridType::Scalar
+        ));
+
+        forcing.update().unwrap();
+        assert_eq!(forcing.get_value_ptr("T2D").unwrap().to_f64_vec(), [2.]);
+        forcing.update_until(forcing.get_end_time()).unwrap();
+        assert_eq!(
+            forcing.get_value_ptr("APCP_surface").unwrap().to_f64_vec(),
+            [2.5]
+        );
+        assert!(forcing.update().unwrap_err().is::<BmiIndexOutOfBounds>());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_many_catchments() {
+        let dir = tempdir("csv-many");
+        let long = "id,time,q\na,0,1\nb,0,2\nb,60,4\na,60,3\n";
+        write(&dir, "long.csv", long);
+        let config = write(
+            &dir,
+            "long.cfg",
+            "file = long.csv\nid_column = id\ntime_units = min\nunits.q = m3 s-1",
+        );
+        let mut forcing = CsvForcing::new();
+        forcing.initialize(&config).unwrap();
+        assert_eq!(forcing.ids(), ["a", "b"]);
+        assert_eq!(forcing.get_time_units(), "min");
+        assert_eq!(forcing.get_var_units("q").unwrap(), "m3 s-1");
+        assert_eq!(forcing.get_grid_shape(0).unwrap(), [2]);
+        assert_eq!(forcing.get_value_ptr("q").unwrap().to_f64_vec(), [1., 2.]);
+        forcing.update().unwrap();
+        assert_eq!(forcing.get_value_ptr("q").unwrap().to_f64_vec(), [3., 4.]);
+
+        write(&dir, "cat-2.csv", "time,q\n0,5\n60,6\n");
+        write(&dir, "cat-3.csv", "time,q\n0,7\n60,8\n");
+        let config = write(&dir, "files.cfg", "files = cat-2.csv, cat-3.csv");
+        let mut forcing = CsvForcing::new();
+        forcing.initialize(&config).unwrap();
+        assert_eq!(forcing.ids(), ["cat-2", "cat-3"]);
+        forcing.update().unwrap();
+        assert_eq!(forcing.get_value_ptr("q").unwrap().to_f64_vec(), [6., 8.]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_files_with_id_column() {
+        let dir = tempdir("csv-files-ids");
+        write(
+            &dir,
+            "part-1.csv",
+            "id,time,q\na,0,1\nb,0,2\na,60,3\nb,60,4\n",
+        );
+        write(&dir, "part-2.csv", "id,time,q\nc,0,5\nc,60,6\n");
+        let config = write(
+            &dir,
+            "parts.cfg",
+            "files = part-1.csv, part-2.csv\nid_column = id",
+        );
+        let mut forcing = CsvForcing::new();
+        forcing.initialize(&config).unwrap();
+        assert_eq!(forcing.ids(), ["a", "b", "c"]);
+        forcing.update().unwrap();
+        assert_eq!(
+            forcing.get_value_ptr("q").unwrap().to_f64_vec(),
+            [3., 4., 6.]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut forcing = CsvForcing::new();
+        assert!(forcing.get_current_time().is_nan());
+        let err = forcing.update().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CsvError>(),
+            Some(&CsvError::NotInitialized)
+        );
+
+        let parse = |src| Table::parse(src, "time", None).unwrap_err();
+        assert_eq!(parse("time,q\n0,1\n"), CsvError::TooShort);
+        assert_eq!(
+            parse("t,q\n0,1\n1,2"),
+            CsvError::MissingColumn("time".into())
+        );
+        assert_eq!(parse("time,q\n0,1\n1"), CsvError::Width { line: 3 });
+        assert_eq!(
+            parse("time,q\n0,1\n1,x"),
+            CsvError::Parse {
+                line: 3,
+                value: "x".into()
+            }
+        );
+        assert_eq!(
+            parse("time,q\n0,1\n1,2\n0,3"),
+            CsvError::Duplicate { line: 4 }
+        );
+        let err = Table::parse("id,time,q\na,0,1\nb,0,2\na,1,3\n", "time", Some("id"));
+        assert_eq!(
+            err.unwrap_err(),
+            CsvError::Missing {
+                time: 1.,
+                id: "b".into()
+            }
+        );
+
+        let dir = tempdir("csv-errors");
+        let file = write(&dir, "uneven.csv", "time,q\n0,1\n1,2\n3,3\n");
+        let err = CsvForcing::new().initialize(&file).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CsvError>(),
+            Some(&CsvError::TimeStep { time: 3. })
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+use crate::clock::Clock;
+use crate::errors::{BmiIndexOutOfBounds, BmiInvalidValue, BmiUnknownVariable};
+use crate::grid::Grid;
+use crate::names::Names;
+use crate::rng::Rng;
+use crate::{Bmi, BmiResult, Location, RefValues, ValueType};
+
+/// A function of time (and node index) used to produce a [`SyntheticForcing`] variable.
+pub enum Signal {
+    /// A constant value.
+    Constant(f64),
+    /// `mean + amplitude * sin(2π * t / period + phase)`.
+    Sine {
+        mean: f64,
+        amplitude: f64,
+        period: f64,
+        phase: f64,
+    },
+    /// `before` until time `at`, then `after`.
+    Step { before: f64, after: f64, at: f64 },
+    /// `value` at time `start`, changing by `slope` per time unit.
+    Ramp { value: f64, start: f64, slope: f64 },
+    /// Normally distributed noise, independent per time step and node.
+    /// Values are reproducible for a given `seed`.
+    Noise { mean: f64, std_dev: f64, seed: u64 },
+    /// An arbitrary function of time and node index.
+    Function(Box<dyn Fn(f64, usize) -> f64 + Send>),
+}
+
+impl Signal {
+    fn evaluate(&self, time: f64, step: u64, node: usize) -> f64 {
+        match self {
+            Signal::Constant(value) => *value,
+            Signal::Sine {
+                mean,
+                amplitude,
+                period,
+                phase,
+            } => mean + amplitude * (2. * std::f64::consts::PI * time / period + phase).sin(),
+            Signal::Step { before, after, at } => match time < *at {
+                true => *before,
+                false => *after,
+            },
+            Signal::Ramp {
+                value,
+                start,
+                slope,
+            } => value + slope * (time - start),
+            Signal::Noise {
+                mean,
+                std_dev,
+                seed,
+            } => mean + std_dev * Rng::keyed(*seed, &[step, node as u64]).normal(),
+            Signal::Function(f) => f(time, node),
+        }
+    }
+}
+
+impl std::fmt::Debug for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signal::Constant(value) => f.debug_tuple("Constant").field(value).finish(),
+            Signal::Sine { .. } => write!(f, "Sine"),
+            Signal::Step { .. } => write!(f, "Step"),
+            Signal::Ramp { .. } => write!(f, "Ramp"),
+            Signal::Noise { seed, .. } => f.debug_struct("Noise").field("seed", seed).finish(),
+            Signal::Function(_) => write!(f, "Function"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Variable {
+    units: String,
+    signal: Signal,
+    values: Vec<f64>,
+}
+
+/// Configurable [`Bmi`] that produces output variables from analytic functions of time.
+/// Intended for exercising downstream models in tests without data files.
+///
+/// All variables are `f64`, located at the nodes of grid `0`, and evaluated at the current time
+/// on [`initialize`] and after each [`update`].
+/// The configuration file passed to [`initialize`] is ignored.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Bmi;
+/// use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+/// use bmi_rs::grid::Grid;
+///
+/// let mut forcing = SyntheticForcing::new(0., 86400., 3600.)
+///     .grid(Grid::Points { x: vec![0., 1.], y: vec![0., 0.], z: vec![] })
+///     .variable("precip_rate", "mm s-1", Signal::Constant(0.1))
+///     .variable("node", "1", Signal::Function(Box::new(|_, node| node as f64)));
+/// forcing.initialize("").unwrap();
+/// forcing.update().unwrap();
+/// assert_eq!(forcing.get_value_ptr("node").unwrap().to_f64_vec(), [0., 1.]);
+/// ```
+///
+/// [`initialize`]: Bmi::initialize
+/// [`update`]: Bmi::update
+#[derive(Debug)]
+pub struct SyntheticForcing {
+    clock: Clock,
+    time_units: String,
+    grid: Grid,
+    names: Names,
+    variables: Vec<Variable>,
+}
+
+impl SyntheticForcing {
+    /// Create a forcing without variables on a [`Grid::Scalar`] grid running from `start` to
+    /// `end` in `dt` sized steps of seconds.
+    pub fn new(start: f64, end: f64, dt: f64) -> Self {
+        Self {
+            clock: Clock::new(start, end, dt),
+            time_units: "s".to_string(),
+            grid: Grid::Scalar,
+            names: Names::default(),
+            variables: Vec::new(),
+        }
+    }
+
+    /// Set the time units.
+    pub fn time_units(mut self, units: &str) -> Self {
+        self.time_units = units.to_string();
+        self
+    }
+
+    /// Set the grid all variables are defined on.
+    pub fn grid(mut self, grid: Grid) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Add output variable `name` in `units` produced by `signal`, replacing the variable
+    /// if one named `name` was already added.
+    pub fn variable(mut self, name: &str, units: &str, signal: Signal) -> Self {
+        let variable = Variable {
+            units: units.to_string(),
+            signal,
+            values: Vec::new(),
+        };
+        if let Some(i) = self.names.position(name) {
+            self.variables[i] = variable;
+            return self;
+        }
+        let mut names: Vec<String> = self
+            .names
+            .as_slice()
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        names.push(name.to_string());
+        self.names = Names::new(names);
+        self.variables.push(variable);
+        self
+    }
+
+    fn evaluate(&mut self) -> BmiResult<()> {
+        let time = self.clock.current_time();
+        let step = self.clock.current_step();
+        let size = self.grid.size()? as usize;
+        for var in self.variables.iter_mut() {
+            var.values = (0..size)
+                .map(|node| var.signal.evaluate(time, step, node))
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn variable_ref(&self, name: &str) -> BmiResult<&Variable> {
+        match self.names.position(name) {
+            Some(idx) => Ok(&self.variables[idx]),
+            None => BmiUnknownVariable.into(),
+        }
+    }
+
+    fn check_grid(&self, grid: i32) -> BmiResult<()> {
+        match grid {
+            0 => Ok(()),
+            _ => BmiIndexOutOfBounds.into(),
+        }
+    }
+}
+
+impl Bmi for SyntheticForcing {
+    fn initialize(&mut self, _config_file: &str) -> BmiResult<()> {
+        self.clock.reset();
+        self.evaluate()
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        self.clock.advance();
+        self.evaluate()
+    }
+
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        self.clock.advance_until(then)?;
+        self.evaluate()
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn get_component_name(&self) -> &str {
+        "synthetic forcing"
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        &[]
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.names.as_slice()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        self.variable_ref(name)?;
+        Ok(0)
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        self.variable_ref(name)?;
+        Ok(ValueType::F64)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        Ok(&self.variable_ref(name)?.units)
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        self.variable_ref(name)?;
+        match self.grid.size()?.checked_mul(size_of::<f64>() as u32) {
+            Some(nbytes) => Ok(nbytes),
+            None => BmiInvalidValue.into(),
+        }
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        self.variable_ref(name)?;
+        Ok(Location::Node)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.clock.current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.clock.start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.clock.end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        &self.time_units
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.clock.time_step()
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        Ok((&self.variable_ref(name)?.values).into())
+    }
+
+    fn set_value(&mut self, _name: &str, _src: RefValues) -> BmiResult<()> {
+        BmiUnknownVariable.into()
+    }
+
+    fn set_value_at_indices(
+        &mut self,
+        _name: &str,
+        _inds: &[u32],
+        _src: RefValues,
+    ) -> BmiResult<()> {
+        BmiUnknownVariable.into()
+    }
+
+    fn get_grid(&self, grid: i32) -> BmiResult<&Grid> {
+        self.check_grid(grid)?;
+        Ok(&self.grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(forcing: &SyntheticForcing, name: &str) -> Vec<f64> {
+        forcing.get_value_ptr(name).unwrap().to_f64_vec()
+    }
+
+    #[test]
+    fn test_signals() {
+        let mut forcing = SyntheticForcing::new(0., 8., 1.)
+            .variable("constant", "1", Signal::Constant(2.))
+            .variable(
+                "sine",
+                "1",
+                Signal::Sine {
+                    mean: 1.,
+                    amplitude: 2.,
+                    period: 4.,
+                    phase: 0.,
+                },
+            )
+            .variable(
+                "step",
+                "1",
+                Signal::Step {
+                    before: 0.,
+                    after: 1.,
+                    at: 2.,
+                },
+            )
+            .variable(
+                "ramp",
+                "1",
+                Signal::Ramp {
+                    value: 1.,
+                    start: 0.,
+                    slope: 0.5,
+                },
+            );
+        forcing.initialize("").unwrap();
+        assert_eq!(value(&forcing, "step"), [0.]);
+        forcing.update().unwrap();
+        assert_eq!(value(&forcing, "constant"), [2.]);
+        assert_eq!(value(&forcing, "sine"), [3.]);
+        assert_eq!(value(&forcing, "step"), [0.]);
+        forcing.update_until(2.).unwrap();
+        assert_eq!(value(&forcing, "step"), [1.]);
+        assert_eq!(value(&forcing, "ramp"), [2.]);
+    }
+
+    #[test]
+    fn test_noise_reproducible() {
+        let build = |seed| {
+            let grid = Grid::UniformRectilinear {
+                shape: vec![2, 3],
+                spacing: vec![1., 1.],
+                origin: vec![0., 0.],
+            };
+            let noise = Signal::Noise {
+                mean: 0.,
+                std_dev: 1.,
+                seed,
+            };
+            let mut forcing = SyntheticForcing::new(0., 10., 1.)
+                .grid(grid)
+                .variable("noise", "1", noise);
+            forcing.initialize("").unwrap();
+            forcing
+        };
+        let mut a = build(1);
+        let mut b = build(1);
+        assert_eq!(value(&a, "noise").len(), 6);
+        assert_eq!(a.get_grid_rank(0).unwrap(), 2);
+        a.update().unwrap();
+        b.update_until(1.).unwrap();
+        assert_eq!(value(&a, "noise"), value(&b, "noise"));
+
+        let c = build(2);
+        assert_ne!(value(&build(1), "noise"), value(&c, "noise"));
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        let forcing = SyntheticForcing::new(0., 1., 1.);
+        let err = forcing.get_value_ptr("foo").unwrap_err();
+        assert!(err.is::<BmiUnknownVariable>());
+        assert!(forcing.get_grid_type(1).is_err());
+    }
+
+    #[test]
+    fn test_replace_variable() {
+        let mut forcing = SyntheticForcing::new(0., 1., 1.)
+            .variable("a", "1", Signal::Constant(1.))
+            .variable("b", "1", Signal::Constant(2.))
+            .variable("a", "m", Signal::Constant(3.));
+        forcing.initialize("").unwrap();
+        assert_eq!(forcing.get_output_var_names(), ["a", "b"]);
+        assert_eq!(forcing.get_var_units("a").unwrap(), "m");
+        assert_eq!(value(&forcing, "a"), [3.]);
+    }
+}
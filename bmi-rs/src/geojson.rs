@@ -0,0 +1,224 @@
+use crate::errors::OutputError;
+use crate::grid::Grid;
+use crate::json::json_string;
+use crate::polygon;
+use crate::{Bmi, BmiResult, Location, RefValues};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Append value `i` of `values` as a JSON number, `null` if not finite.
+fn put_value(buf: &mut String, values: &RefValues, i: usize) {
+    let _ = match values {
+        RefValues::I16(v) => write!(buf, "{}", v[i]),
+        RefValues::U16(v) => write!(buf, "{}", v[i]),
+        RefValues::I32(v) => write!(buf, "{}", v[i]),
+        RefValues::U32(v) => write!(buf, "{}", v[i]),
+        RefValues::I64(v) => write!(buf, "{}", v[i]),
+        RefValues::U64(v) => write!(buf, "{}", v[i]),
+        RefValues::F32(v) if v[i].is_finite() => write!(buf, "{}", v[i]),
+        RefValues::F64(v) if v[i].is_finite() => write!(buf, "{}", v[i]),
+        RefValues::F32(_) | RefValues::F64(_) => write!(buf, "null"),
+    };
+}
+
+/// Write the faces of grid `grid` of an initialized `model` as a
+/// [GeoJSON](https://datatracker.ietf.org/doc/html/rfc7946) `FeatureCollection` of
+/// `Polygon` features, with each face's index as the `face` property and its values of the
+/// face variables `names` as properties named after the variables.
+///
+/// Unstructured grids are written as is and rank 2 uniform rectilinear, rectilinear, and
+/// structured quadrilateral grids as their cells, see [`Grid::to_unstructured`]. Polygon
+/// rings are counterclockwise and closed. Coordinates are written unprojected: GeoJSON
+/// readers assume longitude and latitude (WGS 84). Non-finite values are written as `null`.
+///
+/// Returns Err([`OutputError`]) if a variable is selected twice or named `face`, is not a
+/// face variable of `grid` with a value per face, or a face has a non-finite coordinate, and
+/// Err([`BmiNotImplementedError`](crate::errors::BmiNotImplementedError)) for grids without
+/// faces.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Bmi;
+/// use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+/// use bmi_rs::grid::Grid;
+///
+/// let grid = Grid::Unstructured {
+///     x: vec![0., 1., 0.],
+///     y: vec![0., 0., 1.],
+///     z: vec![],
+///     edge_nodes: vec![],
+///     face_edges: vec![],
+///     face_nodes: vec![0, 1, 2],
+///     nodes_per_face: vec![3],
+/// };
+/// let mut model = SyntheticForcing::new(0., 1., 1.)
+///     .grid(grid)
+///     .variable("depth", "m", Signal::Constant(2.));
+/// model.initialize("").unwrap();
+///
+/// let mut out = Vec::new();
+/// bmi_rs::geojson::write(&mut out, &model, 0, &[]).unwrap();
+/// let json = String::from_utf8(out).unwrap();
+/// assert!(json.contains(r#""coordinates":[[[0,0],[1,0],[0,1],[0,0]]]"#));
+/// ```
+pub fn write<W: Write, M: Bmi>(mut out: W, model: &M, grid: i32, names: &[&str]) -> BmiResult<()> {
+    let faces = Grid::from_model(model, grid)?
+        .to_unstructured()?
+        .face_polygons()?;
+    if let Some(face) = faces
+        .iter()
+        .position(|face| face.iter().flatten().any(|v| !v.is_finite()))
+    {
+        return Err(Box::new(OutputError::Coordinate { face }));
+    }
+    let mut vars = Vec::with_capacity(names.len());
+    for (i, name) in names.iter().enumerate() {
+        if *name == "face" || names[..i].contains(name) {
+            return Err(Box::new(OutputError::Conflict(name.to_string())));
+        }
+        if model.get_var_grid(name)? != grid {
+            return Err(Box::new(OutputError::Grid {
+                name: name.to_string(),
+                grid,
+            }));
+        }
+        let location = model.get_var_location(name)?;
+        if location != Location::Face {
+            return Err(Box::new(OutputError::Location {
+                name: name.to_string(),
+                location,
+            }));
+        }
+        let values = model.get_value_ptr(name)?;
+        if values.len() != faces.len() {
+            return Err(Box::new(OutputError::Length {
+                name: name.to_string(),
+                expected: faces.len(),
+                found: values.len(),
+            }));
+        }
+        vars.push((name, values));
+    }
+
+    out.write_all(br#"{"type":"FeatureCollection","features":["#)?;
+    let mut buf = String::new();
+    for (i, face) in faces.iter().enumerate() {
+        buf.clear();
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push_str(r#"{"type":"Feature","geometry":{"type":"Polygon","coordinates":[["#);
+        let ring = polygon::ccw(face);
+        for [x, y] in ring.iter().chain(ring.first()) {
+            let _ = write!(buf, "[{x},{y}],");
+        }
+        buf.pop();
+        let _ = write!(buf, r#"]]}},"properties":{{"face":{i}"#);
+        for (name, values) in vars.iter() {
+            let _ = write!(buf, ",{}:", json_string(name));
+            put_value(&mut buf, values, i);
+        }
+        buf.push_str("}}");
+        out.write_all(buf.as_bytes())?;
+    }
+    out.write_all(b"]}\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Write a GeoJSON file at `path`, see [`write()`].
+pub fn create<M: Bmi>(
+    path: impl AsRef<Path>,
+    model: &M,
+    grid: i32,
+    names: &[&str],
+) -> BmiResult<()> {
+    write(BufWriter::new(File::create(path)?), model, grid, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::testing::Mesh;
+
+    #[test]
+    fn test_write() {
+        let mut buf = String::new();
+        let values = RefValues::F64(&[f64::NAN, 2.5]);
+        put_value(&mut buf, &values, 0);
+        put_value(&mut buf, &values, 1);
+        put_value(&mut buf, &RefValues::U16(&[7]), 0);
+        assert_eq!(buf, "null2.57");
+
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![1., 1.],
+            origin: vec![0., 0.],
+        };
+        let mut model =
+            SyntheticForcing::new(0., 1., 1.)
+                .grid(grid)
+                .variable("a", "1", Signal::Constant(1.5));
+        model.initialize("").unwrap();
+        let mut out = Vec::new();
+        // node variable
+        let err = write(&mut out, &model, 0, &["a"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<OutputError>(),
+            Some(OutputError::Location { .. })
+        ));
+
+        out.clear();
+        write(&mut out, &model, 0, &[]).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert_eq!(json.matches(r#""type":"Feature""#).count(), 2);
+        assert!(json.contains(
+            r#""coordinates":[[[1,0],[2,0],[2,1],[1,1],[1,0]]]},"properties":{"face":1}"#
+        ));
+        assert!(json.ends_with("]}\n"));
+
+        let grid = Grid::Unstructured {
+            x: vec![0., 1., f64::NAN],
+            y: vec![0., 0., 1.],
+            z: vec![],
+            edge_nodes: vec![],
+            face_edges: vec![],
+            face_nodes: vec![0, 1, 2],
+            nodes_per_face: vec![3],
+        };
+        let mut model = SyntheticForcing::new(0., 1., 1.).grid(grid);
+        model.initialize("").unwrap();
+        let err = write(Vec::new(), &model, 0, &[]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OutputError>(),
+            Some(&OutputError::Coordinate { face: 0 })
+        );
+    }
+
+    #[test]
+    fn test_face_variables() {
+        let model = Mesh::new();
+        let mut out = Vec::new();
+        write(&mut out, &model, 0, &["depth"]).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert!(json.contains(r#""properties":{"face":0,"depth":1.5}"#));
+        assert!(json.contains(r#""properties":{"face":1,"depth":null}"#));
+
+        // edge variable, duplicate and reserved names
+        let err = write(Vec::new(), &model, 0, &["flow"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<OutputError>(),
+            Some(OutputError::Location { .. })
+        ));
+        for names in [&["depth", "depth"][..], &["face"]] {
+            let err = write(Vec::new(), &model, 0, names).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<OutputError>(),
+                Some(OutputError::Conflict(_))
+            ));
+        }
+    }
+}
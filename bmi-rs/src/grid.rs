@@ -0,0 +1,605 @@
+use crate::errors::{BmiInvalidValue, BmiNotImplementedError};
+use crate::polygon::Point;
+use crate::{Bmi, BmiResult, GridType};
+
+/// Cell areas, centroids, and adjacency of grid nodes, edges, and faces.
+pub mod geometry;
+
+/// k-d tree spatial index of grid nodes, edges, and faces.
+pub mod index;
+
+/// Consistency checks of a [`Bmi`] model's grid information methods.
+///
+/// [`Bmi`]: crate::Bmi
+pub mod validate;
+
+/// Descriptor of a [`Bmi`] model grid, returned by [`get_grid`].
+///
+/// The [`Bmi`] grid information methods' default implementations are derived from a model's
+/// grid descriptors, so a model describes each grid once instead of implementing a method per
+/// grid attribute. Methods that do not apply to a grid's type return
+/// Err([`BmiNotImplementedError`]).
+///
+/// Shapes, spacings, and origins are in BMI `ij` order, e.g. `[rows, columns]` or
+/// `[z, y, x]`, the last dimension varying fastest.
+///
+/// Example:
+/// ```
+/// use bmi_rs::GridType;
+/// use bmi_rs::grid::Grid;
+///
+/// let grid = Grid::rectilinear(vec![0., 1., 3.], vec![0., 2.], vec![]);
+/// assert_eq!(grid.grid_type(), GridType::Rectilinear);
+/// assert_eq!(grid.shape().unwrap(), [2, 3]);
+/// assert_eq!(grid.size().unwrap(), 6);
+/// ```
+///
+/// [`Bmi`]: crate::Bmi
+/// [`get_grid`]: crate::Bmi::get_grid
+#[derive(Debug, Clone, PartialEq)]
+pub enum Grid {
+    /// A single value.
+    Scalar,
+    /// `size` values without coordinates.
+    Vector { size: u32 },
+    /// Unconnected points at `x`, `y`, and, if not empty, `z`.
+    Points {
+        x: Vec<f64>,
+        y: Vec<f64>,
+        z: Vec<f64>,
+    },
+    /// Equally spaced nodes.
+    UniformRectilinear {
+        shape: Vec<u32>,
+        spacing: Vec<f64>,
+        origin: Vec<f64>,
+    },
+    /// Nodes at the product of per axis coordinates, see [`Grid::rectilinear`].
+    /// Unused axes are empty.
+    Rectilinear {
+        shape: Vec<u32>,
+        x: Vec<f64>,
+        y: Vec<f64>,
+        z: Vec<f64>,
+    },
+    /// Logically rectangular nodes with coordinates `x`, `y`, and, if not empty, `z` per node.
+    StructuredQuadrilateral {
+        shape: Vec<u32>,
+        x: Vec<f64>,
+        y: Vec<f64>,
+        z: Vec<f64>,
+    },
+    /// Nodes at `x`, `y`, and, if not empty, `z`, connected by edges and faces.
+    /// `face_nodes` and `face_edges` are flattened, `nodes_per_face` long per face.
+    Unstructured {
+        x: Vec<f64>,
+        y: Vec<f64>,
+        z: Vec<f64>,
+        edge_nodes: Vec<u32>,
+        face_edges: Vec<u32>,
+        face_nodes: Vec<u32>,
+        nodes_per_face: Vec<u32>,
+    },
+}
+
+impl Grid {
+    /// Create a [`Grid::Rectilinear`] grid, deriving its shape from the non-empty axes.
+    pub fn rectilinear(x: Vec<f64>, y: Vec<f64>, z: Vec<f64>) -> Self {
+        let shape = [&z, &y, &x]
+            .into_iter()
+            .filter(|axis| !axis.is_empty())
+            .map(|axis| axis.len() as u32)
+            .collect();
+        Grid::Rectilinear { shape, x, y, z }
+    }
+
+    /// Read grid `grid` of `model` through its grid information methods.
+    ///
+    /// `z` is read for rank 3 grids. Unstructured grids' `edge_nodes` and `face_edges` are
+    /// empty if the model does not implement them.
+    pub fn from_model<M: Bmi>(model: &M, grid: i32) -> BmiResult<Self> {
+        let rank = model.get_grid_rank(grid)?;
+        let z = || match rank {
+            3 => Ok(model.get_grid_z(grid)?.to_vec()),
+            _ => Ok::<_, Box<dyn std::error::Error>>(Vec::new()),
+        };
+        Ok(match model.get_grid_type(grid)? {
+            GridType::Scalar => Grid::Scalar,
+            GridType::Vector => Grid::Vector {
+                size: model.get_grid_size(grid)?,
+            },
+            GridType::Points => Grid::Points {
+                x: model.get_grid_x(grid)?.to_vec(),
+                y: model.get_grid_y(grid)?.to_vec(),
+                z: z()?,
+            },
+            GridType::UniformRectilinear => Grid::UniformRectilinear {
+                shape: model.get_grid_shape(grid)?.to_vec(),
+                spacing: model.get_grid_spacing(grid)?.to_vec(),
+                origin: model.get_grid_origin(grid)?.to_vec(),
+            },
+            GridType::Rectilinear => Grid::Rectilinear {
+                shape: model.get_grid_shape(grid)?.to_vec(),
+                x: model.get_grid_x(grid)?.to_vec(),
+                y: match rank {
+                    1 => Vec::new(),
+                    _ => model.get_grid_y(grid)?.to_vec(),
+                },
+                z: z()?,
+            },
+            GridType::StructuredQuadrilateral => Grid::StructuredQuadrilateral {
+                shape: model.get_grid_shape(grid)?.to_vec(),
+                x: model.get_grid_x(grid)?.to_vec(),
+                y: model.get_grid_y(grid)?.to_vec(),
+                z: z()?,
+            },
+            GridType::Unstructured => Grid::Unstructured {
+                x: model.get_grid_x(grid)?.to_vec(),
+                y: model.get_grid_y(grid)?.to_vec(),
+                z: z()?,
+                edge_nodes: model
+                    .get_grid_edge_nodes(grid)
+                    .map(<[u32]>::to_vec)
+                    .unwrap_or_default(),
+                face_edges: model
+                    .get_grid_face_edges(grid)
+                    .map(<[u32]>::to_vec)
+                    .unwrap_or_default(),
+                face_nodes: model.get_grid_face_nodes(grid)?.to_vec(),
+                nodes_per_face: model.get_grid_nodes_per_face(grid)?.to_vec(),
+            },
+        })
+    }
+
+    pub fn grid_type(&self) -> GridType {
+        match self {
+            Grid::Scalar => GridType::Scalar,
+            Grid::Vector { .. } => GridType::Vector,
+            Grid::Points { .. } => GridType::Points,
+            Grid::UniformRectilinear { .. } => GridType::UniformRectilinear,
+            Grid::Rectilinear { .. } => GridType::Rectilinear,
+            Grid::StructuredQuadrilateral { .. } => GridType::StructuredQuadrilateral,
+            Grid::Unstructured { .. } => GridType::Unstructured,
+        }
+    }
+
+    pub fn rank(&self) -> u32 {
+        match self {
+            Grid::Scalar => 0,
+            Grid::Vector { .. } => 1,
+            Grid::Points { z, .. } | Grid::Unstructured { z, .. } => 2 + !z.is_empty() as u32,
+            Grid::UniformRectilinear { shape, .. }
+            | Grid::Rectilinear { shape, .. }
+            | Grid::StructuredQuadrilateral { shape, .. } => shape.len() as u32,
+        }
+    }
+
+    /// Return the number of nodes, or Err([`BmiInvalidValue`]) if it does not fit in a `u32`.
+    pub fn size(&self) -> BmiResult<u32> {
+        let size = match self {
+            Grid::Scalar => Some(1),
+            Grid::Vector { size } => Some(*size),
+            Grid::Points { x, .. } | Grid::Unstructured { x, .. } => u32::try_from(x.len()).ok(),
+            Grid::UniformRectilinear { shape, .. }
+            | Grid::Rectilinear { shape, .. }
+            | Grid::StructuredQuadrilateral { shape, .. } => {
+                shape.iter().try_fold(1u32, |size, n| size.checked_mul(*n))
+            }
+        };
+        match size {
+            Some(size) => Ok(size),
+            None => BmiInvalidValue.into(),
+        }
+    }
+
+    pub fn shape(&self) -> BmiResult<&[u32]> {
+        match self {
+            Grid::UniformRectilinear { shape, .. }
+            | Grid::Rectilinear { shape, .. }
+            | Grid::StructuredQuadrilateral { shape, .. } => Ok(shape),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn spacing(&self) -> BmiResult<&[f64]> {
+        match self {
+            Grid::UniformRectilinear { spacing, .. } => Ok(spacing),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn origin(&self) -> BmiResult<&[f64]> {
+        match self {
+            Grid::UniformRectilinear { origin, .. } => Ok(origin),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn x(&self) -> BmiResult<&[f64]> {
+        match self {
+            Grid::Points { x, .. }
+            | Grid::Rectilinear { x, .. }
+            | Grid::StructuredQuadrilateral { x, .. }
+            | Grid::Unstructured { x, .. } => Ok(x),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn y(&self) -> BmiResult<&[f64]> {
+        match self {
+            Grid::Points { y, .. }
+            | Grid::Rectilinear { y, .. }
+            | Grid::StructuredQuadrilateral { y, .. }
+            | Grid::Unstructured { y, .. } => Ok(y),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    /// Return the third coordinate, Err([`BmiInvalidValue`]) if the grid has none.
+    pub fn z(&self) -> BmiResult<&[f64]> {
+        match self {
+            Grid::Points { z, .. }
+            | Grid::Rectilinear { z, .. }
+            | Grid::StructuredQuadrilateral { z, .. }
+            | Grid::Unstructured { z, .. } => match z.is_empty() {
+                true => BmiInvalidValue.into(),
+                false => Ok(z),
+            },
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn node_count(&self) -> BmiResult<u32> {
+        match self {
+            Grid::Scalar | Grid::Vector { .. } => BmiNotImplementedError.into(),
+            _ => self.size(),
+        }
+    }
+
+    pub fn edge_count(&self) -> BmiResult<u32> {
+        Ok(self.edge_nodes()?.len() as u32 / 2)
+    }
+
+    pub fn face_count(&self) -> BmiResult<u32> {
+        Ok(self.nodes_per_face()?.len() as u32)
+    }
+
+    pub fn edge_nodes(&self) -> BmiResult<&[u32]> {
+        match self {
+            Grid::Unstructured { edge_nodes, .. } => Ok(edge_nodes),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn face_edges(&self) -> BmiResult<&[u32]> {
+        match self {
+            Grid::Unstructured { face_edges, .. } => Ok(face_edges),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn face_nodes(&self) -> BmiResult<&[u32]> {
+        match self {
+            Grid::Unstructured { face_nodes, .. } => Ok(face_nodes),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    pub fn nodes_per_face(&self) -> BmiResult<&[u32]> {
+        match self {
+            Grid::Unstructured { nodes_per_face, .. } => Ok(nodes_per_face),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    /// Return a uniform rectilinear grid as the equivalent [`Grid::Rectilinear`] grid, and a
+    /// rectilinear grid unchanged.
+    ///
+    /// Returns Err([`BmiNotImplementedError`]) for other grids.
+    pub fn to_rectilinear(&self) -> BmiResult<Grid> {
+        let Grid::UniformRectilinear {
+            shape,
+            spacing,
+            origin,
+        } = self
+        else {
+            let Grid::Rectilinear { shape, x, y, z } = self else {
+                return BmiNotImplementedError.into();
+            };
+            // axis lengths in [x, y, z] order, empty past the rank
+            let lens = shape.iter().rev().map(|n| *n as usize).chain([0; 3]);
+            if shape.len() <= 3
+                && [x, y, z]
+                    .iter()
+                    .zip(lens)
+                    .any(|(axis, len)| axis.len() != len)
+            {
+                return BmiInvalidValue.into();
+            }
+            return Ok(self.clone());
+        };
+        if spacing.len() != shape.len() || origin.len() != shape.len() {
+            return BmiInvalidValue.into();
+        }
+        if shape.len() > 3 {
+            return BmiNotImplementedError.into();
+        }
+        // axes in [z, y, x] order
+        let mut axes: Vec<Vec<f64>> = shape
+            .iter()
+            .zip(spacing.iter().zip(origin))
+            .map(|(n, (spacing, origin))| (0..*n).map(|i| origin + i as f64 * spacing).collect())
+            .collect();
+        let mut next = || axes.pop().unwrap_or_default();
+        let (x, y, z) = (next(), next(), next());
+        Ok(Grid::Rectilinear {
+            shape: shape.clone(),
+            x,
+            y,
+            z,
+        })
+    }
+
+    /// Return a rank 2 or 3 uniform rectilinear or rectilinear grid as the equivalent
+    /// [`Grid::StructuredQuadrilateral`] grid with coordinates per node, and a structured
+    /// quadrilateral grid unchanged.
+    ///
+    /// Returns Err([`BmiNotImplementedError`]) for other grids.
+    pub fn to_structured_quadrilateral(&self) -> BmiResult<Grid> {
+        match self {
+            Grid::StructuredQuadrilateral { .. } => return Ok(self.clone()),
+            Grid::UniformRectilinear { .. } | Grid::Rectilinear { .. }
+                if (2..=3).contains(&self.rank()) => {}
+            _ => return BmiNotImplementedError.into(),
+        }
+        let Grid::Rectilinear { shape, x, y, z } = self.to_rectilinear()? else {
+            return BmiNotImplementedError.into();
+        };
+        let size = z.len().max(1) * y.len() * x.len();
+        let (mut xs, mut ys) = (Vec::with_capacity(size), Vec::with_capacity(size));
+        let mut zs = Vec::with_capacity(match z.is_empty() {
+            true => 0,
+            false => size,
+        });
+        for k in 0..z.len().max(1) {
+            for y in y.iter() {
+                for x in x.iter() {
+                    xs.push(*x);
+                    ys.push(*y);
+                    if let Some(z) = z.get(k) {
+                        zs.push(*z);
+                    }
+                }
+            }
+        }
+        Ok(Grid::StructuredQuadrilateral {
+            shape,
+            x: xs,
+            y: ys,
+            z: zs,
+        })
+    }
+
+    /// Return a rank 2 uniform rectilinear, rectilinear, or structured quadrilateral grid as
+    /// the equivalent [`Grid::Unstructured`] grid, and an unstructured grid unchanged.
+    ///
+    /// Faces are the quadrilaterals between neighboring nodes, with edges and face edges as
+    /// derived by [`Geometry`].
+    ///
+    /// Returns Err([`BmiNotImplementedError`]) for other grids.
+    ///
+    /// [`Geometry`]: geometry::Geometry
+    pub fn to_unstructured(&self) -> BmiResult<Grid> {
+        match self {
+            Grid::Unstructured { .. } => return Ok(self.clone()),
+            Grid::UniformRectilinear { .. }
+            | Grid::Rectilinear { .. }
+            | Grid::StructuredQuadrilateral { .. }
+                if self.rank() == 2 => {}
+            _ => return BmiNotImplementedError.into(),
+        }
+        let Grid::StructuredQuadrilateral { x, y, z, .. } = self.to_structured_quadrilateral()?
+        else {
+            return BmiNotImplementedError.into();
+        };
+        let geometry = geometry::Geometry::new(self)?;
+        Ok(Grid::Unstructured {
+            x,
+            y,
+            z,
+            edge_nodes: geometry.edge_nodes().concat(),
+            face_edges: geometry.face_edges().indices.clone(),
+            face_nodes: geometry.face_nodes().indices.clone(),
+            nodes_per_face: vec![4; geometry.face_count()],
+        })
+    }
+
+    /// Return the x and y axis coordinates of a rank 2 uniform rectilinear or rectilinear
+    /// grid, `None` for other grids.
+    ///
+    /// Returns Err([`BmiInvalidValue`]) if the spacing, origin, or axis lengths disagree with
+    /// the shape, see [`Grid::to_rectilinear`].
+    pub(crate) fn axes(&self) -> BmiResult<Option<(Vec<f64>, Vec<f64>)>> {
+        match self {
+            Grid::UniformRectilinear { .. } | Grid::Rectilinear { .. } if self.rank() == 2 => {
+                match self.to_rectilinear()? {
+                    Grid::Rectilinear { x, y, .. } => Ok(Some((x, y))),
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the x and y node coordinates of a rank 2 uniform rectilinear or rectilinear
+    /// grid, or of a points, structured quadrilateral, or unstructured grid, `None` for other
+    /// grids.
+    ///
+    /// Returns Err([`BmiInvalidValue`]) if the coordinates' lengths disagree with each other
+    /// or with the shape.
+    pub(crate) fn node_xy(&self) -> BmiResult<Option<(Vec<f64>, Vec<f64>)>> {
+        if let Some((x, y)) = self.axes()? {
+            return Ok(Some(
+                y.iter()
+                    .flat_map(|y| x.iter().map(move |x| (*x, *y)))
+                    .unzip(),
+            ));
+        }
+        match self {
+            Grid::StructuredQuadrilateral { shape, x, .. }
+                if shape.iter().map(|n| *n as usize).product::<usize>() != x.len() =>
+            {
+                BmiInvalidValue.into()
+            }
+            Grid::Points { x, y, .. }
+            | Grid::StructuredQuadrilateral { x, y, .. }
+            | Grid::Unstructured { x, y, .. } => match x.len() == y.len() {
+                true => Ok(Some((x.clone(), y.clone()))),
+                false => BmiInvalidValue.into(),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the polygon of each face of an unstructured grid.
+    ///
+    /// Returns Err([`BmiInvalidValue`]) if a face has fewer than three nodes or a node index
+    /// is out of bounds.
+    pub(crate) fn face_polygons(&self) -> BmiResult<Vec<Vec<Point>>> {
+        let (x, y, face_nodes, nodes_per_face) = (
+            self.x()?,
+            self.y()?,
+            self.face_nodes()?,
+            self.nodes_per_face()?,
+        );
+        let mut nodes = face_nodes.iter();
+        let mut faces = Vec::with_capacity(nodes_per_face.len());
+        for n in nodes_per_face.iter().map(|n| *n as usize) {
+            let face: Option<Vec<Point>> = nodes
+                .by_ref()
+                .take(n)
+                .map(|i| Some([*x.get(*i as usize)?, *y.get(*i as usize)?]))
+                .collect();
+            match face {
+                Some(face) if n >= 3 && face.len() == n => faces.push(face),
+                _ => return BmiInvalidValue.into(),
+            }
+        }
+        Ok(faces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bmi;
+    use crate::forcing::synthetic::SyntheticForcing;
+    use crate::testing::triangles;
+
+    #[test]
+    fn test_bmi_defaults() {
+        let grid = triangles();
+        let model = SyntheticForcing::new(0., 1., 1.).grid(grid);
+        assert_eq!(model.get_grid_type(0).unwrap(), GridType::Unstructured);
+        assert_eq!(model.get_grid_rank(0).unwrap(), 2);
+        assert_eq!(model.get_grid_size(0).unwrap(), 4);
+        assert_eq!(model.get_grid_node_count(0).unwrap(), 4);
+        assert_eq!(model.get_grid_edge_count(0).unwrap(), 5);
+        assert_eq!(model.get_grid_face_count(0).unwrap(), 2);
+        assert_eq!(model.get_grid_face_nodes(0).unwrap(), [0, 1, 2, 1, 3, 2]);
+        assert_eq!(model.get_grid_y(0).unwrap(), [0., 0., 1., 1.]);
+        assert!(model.get_grid_z(0).unwrap_err().is::<BmiInvalidValue>());
+        assert!(
+            model
+                .get_grid_shape(0)
+                .unwrap_err()
+                .is::<BmiNotImplementedError>()
+        );
+        assert!(model.get_grid_type(1).is_err());
+    }
+
+    #[test]
+    fn test_structured() {
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3, 4],
+            spacing: vec![1., 2., 3.],
+            origin: vec![0., 0., 0.],
+        };
+        assert_eq!((grid.rank(), grid.size().unwrap()), (3, 24));
+        assert_eq!(grid.spacing().unwrap(), [1., 2., 3.]);
+        assert!(grid.x().is_err());
+        assert!(grid.edge_count().is_err());
+
+        let grid = Grid::rectilinear(vec![0., 1.], vec![], vec![]);
+        assert_eq!((grid.rank(), grid.size().unwrap()), (1, 2));
+        assert_eq!(Grid::Vector { size: 5 }.rank(), 1);
+        assert_eq!((Grid::Scalar.rank(), Grid::Scalar.size().unwrap()), (0, 1));
+
+        let grid = Grid::UniformRectilinear {
+            shape: vec![100000, 100000],
+            spacing: vec![1., 1.],
+            origin: vec![0., 0.],
+        };
+        assert!(grid.size().unwrap_err().is::<BmiInvalidValue>());
+    }
+
+    #[test]
+    fn test_convert() {
+        let uniform = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![2., 1.],
+            origin: vec![10., 0.],
+        };
+        let rectilinear = uniform.to_rectilinear().unwrap();
+        assert_eq!(
+            rectilinear,
+            Grid::rectilinear(vec![0., 1., 2.], vec![10., 12.], vec![])
+        );
+        let quad = rectilinear.to_structured_quadrilateral().unwrap();
+        assert_eq!(quad.x().unwrap(), [0., 1., 2., 0., 1., 2.]);
+        assert_eq!(quad.y().unwrap(), [10., 10., 10., 12., 12., 12.]);
+        assert_eq!(uniform.to_structured_quadrilateral().unwrap(), quad);
+
+        let mesh = quad.to_unstructured().unwrap();
+        assert_eq!(uniform.to_unstructured().unwrap(), mesh);
+        assert_eq!(mesh.face_nodes().unwrap(), [0, 1, 4, 3, 1, 2, 5, 4]);
+        assert_eq!(mesh.nodes_per_face().unwrap(), [4, 4]);
+        assert_eq!(mesh.edge_count().unwrap(), 7);
+        assert_eq!(mesh.face_edges().unwrap()[4..], [4, 5, 6, 1]);
+        let model = SyntheticForcing::new(0., 1., 1.).grid(mesh.clone());
+        assert!(validate::validate(&model, 0).is_valid());
+        assert_eq!(mesh.to_unstructured().unwrap(), mesh);
+
+        let volume = Grid::UniformRectilinear {
+            shape: vec![2, 1, 2],
+            spacing: vec![1., 1., 1.],
+            origin: vec![0., 0., 0.],
+        };
+        let quad = volume.to_structured_quadrilateral().unwrap();
+        assert_eq!(quad.z().unwrap(), [0., 0., 1., 1.]);
+        assert!(volume.to_unstructured().is_err());
+        assert!(mesh.to_rectilinear().is_err());
+        assert!(Grid::Scalar.to_structured_quadrilateral().is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_axes() {
+        let short = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![1.],
+            origin: vec![0., 0.],
+        };
+        assert!(short.axes().is_err() && short.node_xy().is_err());
+        let short = Grid::Rectilinear {
+            shape: vec![2, 3],
+            x: vec![0., 1.],
+            y: vec![0., 1.],
+            z: vec![],
+        };
+        assert!(short.to_rectilinear().is_err() && short.axes().is_err());
+        assert!(crate::regrid::Weights::between(&short, &short, Default::default()).is_err());
+        assert!(index::KdTree::from_grid(&short, crate::Location::Node).is_err());
+        assert!(geometry::Geometry::new(&short).is_err());
+        assert_eq!(Grid::Scalar.axes().unwrap(), None);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::errors::{BmiInvalidValue, BmiNotImplementedError};
+use crate::grid::Grid;
+use crate::polygon::{self, Point};
+use crate::{Bmi, BmiResult, GridType};
+use std::collections::HashMap;
+
+/// Mean radius of the Earth in meters (IUGG).
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// How face areas are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Metric {
+    /// Planar areas in the squared units of the node coordinates.
+    #[default]
+    Planar,
+    /// Areas on a sphere of `radius`, in its squared units, from node longitudes `x` and
+    /// latitudes `y` in degrees. Edges follow lines of constant latitude between their nodes
+    /// (Chamberlain and Duquette 2007), exact for latitude/longitude cells.
+    Spherical { radius: f64 },
+}
+
+/// Compressed sparse row (CSR) adjacency: the elements adjacent to element `i` are
+/// `indices[offsets[i]..offsets[i + 1]]`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Csr {
+    pub offsets: Vec<u32>,
+    pub indices: Vec<u32>,
+}
+
+impl Csr {
+    fn from_lists(lists: impl IntoIterator<Item = Vec<u32>>) -> Self {
+        let mut csr = Csr {
+            offsets: vec![0],
+            indices: Vec::new(),
+        };
+        for list in lists {
+            csr.indices.extend(list);
+            csr.offsets.push(csr.indices.len() as u32);
+        }
+        csr
+    }
+
+    /// Return the number of elements.
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the elements adjacent to element `i`.
+    pub fn neighbors(&self, i: usize) -> &[u32] {
+        &self.indices[self.offsets[i] as usize..self.offsets[i + 1] as usize]
+    }
+}
+
+/// Geometry and connectivity of a grid's nodes, edges, and faces.
+///
+/// Faces of rank 2 uniform rectilinear, rectilinear, and structured quadrilateral grids are
+/// the quadrilaterals between neighboring nodes, in row major order, their nodes
+/// counterclockwise for increasing coordinates. Edges are the grid's [`get_grid_edge_nodes`]
+/// if it has any, else derived from the faces in order of first appearance.
+///
+/// Example:
+/// ```
+/// use bmi_rs::grid::Grid;
+/// use bmi_rs::grid::geometry::{Geometry, Metric};
+///
+/// let grid = Grid::rectilinear(vec![0., 1., 3.], vec![0., 2.], vec![]);
+/// let geometry = Geometry::new(&grid).unwrap();
+/// assert_eq!(geometry.face_areas(Metric::Planar), [2., 4.]);
+/// assert_eq!(geometry.face_centroids(), [[0.5, 1.], [2., 1.]]);
+/// assert_eq!(geometry.face_neighbors().neighbors(0), [1]);
+/// ```
+///
+/// [`get_grid_edge_nodes`]: Bmi::get_grid_edge_nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    nodes: Vec<Point>,
+    edges: Vec<[u32; 2]>,
+    faces: Csr,
+    /// Edges of each face, in the order of the face's nodes.
+    face_edges: Csr,
+}
+
+impl Geometry {
+    /// Compute the geometry of grid `grid` of `model`.
+    pub fn from_model<M: Bmi>(model: &M, grid: i32) -> BmiResult<Self> {
+        Self::new(&Grid::from_model(model, grid)?)
+    }
+
+    /// Compute the geometry of `grid`.
+    ///
+    /// Returns Err([`BmiNotImplementedError`]) for grids other than rank 2 uniform
+    /// rectilinear, rectilinear, structured quadrilateral, and unstructured grids, and
+    /// Err([`BmiInvalidValue`]) for inconsistent connectivity.
+    pub fn new(grid: &Grid) -> BmiResult<Self> {
+        let Some((x, y)) = grid.node_xy()? else {
+            return BmiNotImplementedError.into();
+        };
+        let nodes: Vec<Point> = x.iter().zip(&y).map(|(x, y)| [*x, *y]).collect();
+        let (faces, edges) = match grid.grid_type() {
+            GridType::Unstructured => {
+                let (face_nodes, nodes_per_face) = (grid.face_nodes()?, grid.nodes_per_face()?);
+                if nodes_per_face.iter().map(|n| *n as usize).sum::<usize>() != face_nodes.len() {
+                    return BmiInvalidValue.into();
+                }
+                let mut face_nodes = face_nodes.iter();
+                let faces = Csr::from_lists(
+                    nodes_per_face
+                        .iter()
+                        .map(|n| face_nodes.by_ref().take(*n as usize).copied().collect()),
+                );
+                let edges: Vec<[u32; 2]> = grid
+                    .edge_nodes()?
+                    .chunks_exact(2)
+                    .map(|edge| [edge[0], edge[1]])
+                    .collect();
+                (faces, edges)
+            }
+            GridType::UniformRectilinear
+            | GridType::Rectilinear
+            | GridType::StructuredQuadrilateral
+                if grid.rank() == 2 =>
+            {
+                let shape = grid.shape()?;
+                let (ny, nx) = (shape[0], shape[1]);
+                let faces = Csr::from_lists((0..ny.saturating_sub(1)).flat_map(|j| {
+                    (0..nx.saturating_sub(1)).map(move |i| {
+                        let node = j * nx + i;
+                        vec![node, node + 1, node + nx + 1, node + nx]
+                    })
+                }));
+                (faces, Vec::new())
+            }
+            _ => return BmiNotImplementedError.into(),
+        };
+        let (n, face_count) = (nodes.len() as u32, faces.len());
+        let valid = (0..face_count).all(|f| faces.neighbors(f).len() >= 3)
+            && faces
+                .indices
+                .iter()
+                .chain(edges.iter().flatten())
+                .all(|i| *i < n);
+        if !valid {
+            return BmiInvalidValue.into();
+        }
+        Ok(Self::connect(nodes, edges, faces))
+    }
+
+    /// Derive face edges, adding edges missing from `edges`.
+    fn connect(nodes: Vec<Point>, mut edges: Vec<[u32; 2]>, faces: Csr) -> Self {
+        let key = |[a, b]: [u32; 2]| (a.min(b), a.max(b));
+        let mut lookup: HashMap<(u32, u32), u32> = edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| (key(*edge), i as u32))
+            .collect();
+        let face_edges = Csr::from_lists((0..faces.len()).map(|f| {
+            let face = faces.neighbors(f);
+            (0..face.len())
+                .map(|k| {
+                    let edge = [face[k], face[(k + 1) % face.len()]];
+                    *lookup.entry(key(edge)).or_insert_with(|| {
+                        edges.push(edge);
+                        edges.len() as u32 - 1
+                    })
+                })
+                .collect()
+        }));
+        Self {
+            nodes,
+            edges,
+            faces,
+            face_edges,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Return the nodes of each edge.
+    pub fn edge_nodes(&self) -> &[[u32; 2]] {
+        &self.edges
+    }
+
+    /// Return the nodes of each face.
+    pub fn face_nodes(&self) -> &Csr {
+        &self.faces
+    }
+
+    /// Return the edges of each face, the `k`th edge joining the face's `k`th and `k + 1`th
+    /// nodes.
+    pub fn face_edges(&self) -> &Csr {
+        &self.face_edges
+    }
+
+    fn polygon(&self, face: usize) -> Vec<Point> {
+        self.faces
+            .neighbors(face)
+            .iter()
+            .map(|i| self.nodes[*i as usize])
+            .collect()
+    }
+
+    /// Return the area of each face.
+    pub fn face_areas(&self, metric: Metric) -> Vec<f64> {
+        (0..self.face_count())
+            .map(|f| {
+                let poly = self.polygon(f);
+                match metric {
+                    Metric::Planar => polygon::area(&poly),
+                    Metric::Spherical { radius } => spherical_area(&poly, radius),
+                }
+            })
+            .collect()
+    }
+
+    /// Return the planar area centroid of each face, in node coordinates.
+    pub fn face_centroids(&self) -> Vec<Point> {
+        (0..self.face_count())
+            .map(|f| polygon::centroid(&self.polygon(f)))
+            .collect()
+    }
+
+    /// Return the nodes sharing an edge with each node, in ascending order.
+    pub fn node_neighbors(&self) -> Csr {
+        let mut lists = vec![Vec::new(); self.node_count()];
+        for [a, b] in self.edges.iter() {
+            lists[*a as usize].push(*b);
+            lists[*b as usize].push(*a);
+        }
+        sorted(lists)
+    }
+
+    /// Return the faces of each node, in ascending order.
+    pub fn node_faces(&self) -> Csr {
+        let mut lists = vec![Vec::new(); self.node_count()];
+        for f in 0..self.face_count() {
+            for node in self.faces.neighbors(f) {
+                lists[*node as usize].push(f as u32);
+            }
+        }
+        sorted(lists)
+    }
+
+    /// Return the one or two faces of each edge, in ascending order. Edges not on a face have
+    /// none.
+    pub fn edge_faces(&self) -> Csr {
+        let mut lists = vec![Vec::new(); self.edge_count()];
+        for f in 0..self.face_count() {
+            for edge in self.face_edges.neighbors(f) {
+                lists[*edge as usize].push(f as u32);
+            }
+        }
+        sorted(lists)
+    }
+
+    /// Return the faces sharing an edge with each face, in ascending order.
+    pub fn face_neighbors(&self) -> Csr {
+        let edge_faces = self.edge_faces();
+        sorted((0..self.face_count()).map(|f| {
+            self.face_edges
+                .neighbors(f)
+                .iter()
+                .flat_map(|edge| edge_faces.neighbors(*edge as usize))
+                .copied()
+                .filter(|other| *other != f as u32)
+                .collect()
+        }))
+    }
+}
+
+fn sorted(lists: impl IntoIterator<Item = Vec<u32>>) -> Csr {
+    Csr::from_lists(lists.into_iter().map(|mut list| {
+        list.sort_unstable();
+        list.dedup();
+        list
+    }))
+}
+
+/// Return the area of a longitude/latitude polygon on a sphere of `radius`, its edges
+/// following lines of constant latitude.
+fn spherical_area(poly: &[Point], radius: f64) -> f64 {
+    let n = poly.len();
+    let sum: f64 = (0..n)
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % n]);
+            // shortest longitude difference, crossing the antimeridian if needed
+            let dlon = ((b[0] - a[0] + 540.) % 360. - 180.).to_radians();
+            dlon * (a[1].to_radians().sin() + b[1].to_radians().sin())
+        })
+        .sum();
+    (sum / 2.).abs() * radius * radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::SyntheticForcing;
+    use crate::testing::triangles;
+
+    #[test]
+    fn test_structured() {
+        // 3 x 3 nodes, 2 x 2 faces
+        let grid = Grid::UniformRectilinear {
+            shape: vec![3, 3],
+            spacing: vec![1., 2.],
+            origin: vec![0., 0.],
+        };
+        let model = SyntheticForcing::new(0., 1., 1.).grid(grid);
+        let geometry = Geometry::from_model(&model, 0).unwrap();
+        assert_eq!(geometry.node_count(), 9);
+        assert_eq!(geometry.edge_count(), 12);
+        assert_eq!(geometry.face_count(), 4);
+        assert_eq!(geometry.face_nodes().neighbors(3), [4, 5, 8, 7]);
+        assert_eq!(geometry.face_areas(Metric::Planar), [2.; 4]);
+        assert_eq!(geometry.face_centroids()[3], [3., 1.5]);
+        assert_eq!(geometry.node_neighbors().neighbors(4), [1, 3, 5, 7]);
+        assert_eq!(geometry.node_faces().neighbors(4), [0, 1, 2, 3]);
+        assert_eq!(geometry.face_neighbors().neighbors(0), [1, 2]);
+        let edge_faces = geometry.edge_faces();
+        let shared = (0..12).filter(|e| edge_faces.neighbors(*e).len() == 2);
+        assert_eq!(shared.count(), 4);
+
+        let line = Grid::rectilinear(vec![0., 1.], vec![], vec![]);
+        assert!(Geometry::new(&line).is_err());
+    }
+
+    #[test]
+    fn test_unstructured() {
+        // edge (0, 2) not listed
+        let mut grid = triangles();
+        if let Grid::Unstructured {
+            edge_nodes,
+            face_edges,
+            ..
+        } = &mut grid
+        {
+            *edge_nodes = vec![0, 1, 1, 2, 1, 3, 3, 2];
+            face_edges.clear();
+        }
+        let geometry = Geometry::new(&grid).unwrap();
+        assert_eq!(geometry.edge_nodes()[4], [2, 0]);
+        assert_eq!(geometry.face_edges().neighbors(0), [0, 1, 4]);
+        assert_eq!(geometry.face_edges().neighbors(1), [2, 3, 1]);
+        assert_eq!(geometry.edge_faces().neighbors(1), [0, 1]);
+        assert_eq!(geometry.face_neighbors().neighbors(1), [0]);
+        assert_eq!(geometry.face_areas(Metric::Planar), [0.5, 0.5]);
+
+        if let Grid::Unstructured { face_nodes, .. } = &mut grid {
+            face_nodes[5] = 4;
+        }
+        assert!(Geometry::new(&grid).is_err());
+    }
+
+    #[test]
+    fn test_spherical() {
+        // globe of 90 degree cells, nodes at cell corners
+        let grid = Grid::UniformRectilinear {
+            shape: vec![3, 5],
+            spacing: vec![90., 90.],
+            origin: vec![-90., -180.],
+        };
+        let areas = Geometry::new(&grid)
+            .unwrap()
+            .face_areas(Metric::Spherical { radius: 1. });
+        let sphere = 4. * std::f64::consts::PI;
+        assert!((areas.iter().sum::<f64>() - sphere).abs() < 1e-12);
+        assert!(areas.iter().all(|a| (a - sphere / 8.).abs() < 1e-12));
+
+        // one degree cell at the equator, crossing the antimeridian
+        let cell = [[179.5, 0.], [-179.5, 0.], [-179.5, 1.], [179.5, 1.]];
+        let expected = 1f64.to_radians() * 1f64.to_radians().sin() * EARTH_RADIUS.powi(2);
+        assert!((spherical_area(&cell, EARTH_RADIUS) / expected - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::errors::{BmiInvalidValue, BmiNotImplementedError};
+use crate::grid::Grid;
+use crate::polygon::{self, Point};
+use crate::{Bmi, BmiResult, GridType, Location};
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// Static 2-d tree (Bentley 1975) of the nodes, edges, or faces of a grid, for nearest
+/// neighbor, radius, and polygon queries.
+///
+/// Queries return element indices, usable with [`get_value_at_indices`] for variables at the
+/// indexed location. Coordinates are planar; `z` is ignored.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Location;
+/// use bmi_rs::grid::Grid;
+/// use bmi_rs::grid::index::KdTree;
+///
+/// let grid = Grid::rectilinear(vec![0., 1., 2.], vec![0., 1.], vec![]);
+/// let index = KdTree::from_grid(&grid, Location::Node).unwrap();
+/// assert_eq!(index.nearest(1.9, 0.8, 2), [5, 2]);
+/// assert_eq!(index.within_radius(0., 0., 1.), [0, 1, 3]);
+/// ```
+///
+/// [`get_value_at_indices`]: Bmi::get_value_at_indices
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdTree {
+    points: Vec<Point>,
+    /// Indexed points, each range's median splitting it on alternating axes.
+    order: Vec<u32>,
+}
+
+fn build(order: &mut [u32], points: &[Point], axis: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |a, b| {
+        points[*a as usize][axis].total_cmp(&points[*b as usize][axis])
+    });
+    let (left, right) = order.split_at_mut(mid);
+    build(left, points, 1 - axis);
+    build(&mut right[1..], points, 1 - axis);
+}
+
+fn distance2(a: Point, b: Point) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+impl KdTree {
+    /// Index the points `(x[i], y[i])`. Points with `NaN` coordinates are not indexed.
+    pub fn new(x: &[f64], y: &[f64]) -> Self {
+        let points: Vec<Point> = x.iter().zip(y).map(|(x, y)| [*x, *y]).collect();
+        Self::from_points(points)
+    }
+
+    fn from_points(points: Vec<Point>) -> Self {
+        let mut order: Vec<u32> = (0..points.len() as u32)
+            .filter(|i| !points[*i as usize].iter().any(|v| v.is_nan()))
+            .collect();
+        build(&mut order, &points, 0);
+        Self { points, order }
+    }
+
+    /// Index the `location` elements of grid `grid` of `model`, see [`KdTree::from_grid`].
+    pub fn from_model<M: Bmi>(model: &M, grid: i32, location: Location) -> BmiResult<Self> {
+        Self::from_grid(&Grid::from_model(model, grid)?, location)
+    }
+
+    /// Index the `location` elements of `grid`: the nodes of points, unstructured, structured
+    /// quadrilateral, and rank 2 uniform rectilinear and rectilinear grids, the edge midpoints
+    /// of unstructured grids, and the face centroids of unstructured grids and of the other
+    /// grids' cells, see [`Grid::to_unstructured`].
+    ///
+    /// Returns Err([`BmiNotImplementedError`]) for other grids and locations.
+    pub fn from_grid(grid: &Grid, location: Location) -> BmiResult<Self> {
+        let unstructured = grid.grid_type() == GridType::Unstructured;
+        let points = match location {
+            Location::Node => {
+                let Some((x, y)) = grid.node_xy()? else {
+                    return BmiNotImplementedError.into();
+                };
+                return Ok(Self::new(&x, &y));
+            }
+            Location::Edge if unstructured => {
+                let (x, y) = (grid.x()?, grid.y()?);
+                let node = |i: u32| Some([*x.get(i as usize)?, *y.get(i as usize)?]);
+                let edges: Option<Vec<Point>> = grid
+                    .edge_nodes()?
+                    .chunks_exact(2)
+                    .map(|edge| {
+                        let (a, b) = (node(edge[0])?, node(edge[1])?);
+                        Some([(a[0] + b[0]) / 2., (a[1] + b[1]) / 2.])
+                    })
+                    .collect();
+                let Some(edges) = edges else {
+                    return BmiInvalidValue.into();
+                };
+                edges
+            }
+            Location::Face => grid
+                .to_unstructured()?
+                .face_polygons()?
+                .iter()
+                .map(|face| polygon::centroid(face))
+                .collect(),
+            _ => return BmiNotImplementedError.into(),
+        };
+        Ok(Self::from_points(points))
+    }
+
+    /// Return the number of indexed points.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    fn point(&self, i: usize) -> Point {
+        self.points[self.order[i] as usize]
+    }
+
+    /// Return the indices of the `k` elements nearest to `(x, y)`, nearest first.
+    pub fn nearest(&self, x: f64, y: f64, k: usize) -> Vec<u32> {
+        // squared distances are non-negative, so their bits order as the distances do
+        let mut heap: BinaryHeap<(u64, u32)> = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_in(0..self.len(), 0, [x, y], k, &mut heap);
+        }
+        heap.into_sorted_vec().into_iter().map(|(_, i)| i).collect()
+    }
+
+    fn nearest_in(
+        &self,
+        range: Range<usize>,
+        axis: usize,
+        p: Point,
+        k: usize,
+        heap: &mut BinaryHeap<(u64, u32)>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let mid = (range.start + range.end) / 2;
+        let median = self.point(mid);
+        heap.push((distance2(p, median).to_bits(), self.order[mid]));
+        if heap.len() > k {
+            heap.pop();
+        }
+        let diff = p[axis] - median[axis];
+        let (near, far) = match diff < 0. {
+            true => (range.start..mid, mid + 1..range.end),
+            false => (mid + 1..range.end, range.start..mid),
+        };
+        self.nearest_in(near, 1 - axis, p, k, heap);
+        let worst = heap
+            .peek()
+            .map_or(f64::INFINITY, |(d, _)| f64::from_bits(*d));
+        if heap.len() < k || diff * diff < worst {
+            self.nearest_in(far, 1 - axis, p, k, heap);
+        }
+    }
+
+    /// Return the indices of the elements within `radius` of `(x, y)`, in ascending order.
+    /// Returns no elements if `radius` is negative or `NaN`.
+    pub fn within_radius(&self, x: f64, y: f64, radius: f64) -> Vec<u32> {
+        let mut found = Vec::new();
+        if radius.is_nan() || radius < 0. {
+            return found;
+        }
+        self.radius_in(0..self.len(), 0, [x, y], radius * radius, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    fn radius_in(
+        &self,
+        range: Range<usize>,
+        axis: usize,
+        p: Point,
+        radius2: f64,
+        found: &mut Vec<u32>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let mid = (range.start + range.end) / 2;
+        let median = self.point(mid);
+        if distance2(p, median) <= radius2 {
+            found.push(self.order[mid]);
+        }
+        let diff = p[axis] - median[axis];
+        if diff <= 0. || diff * diff <= radius2 {
+            self.radius_in(range.start..mid, 1 - axis, p, radius2, found);
+        }
+        if diff >= 0. || diff * diff <= radius2 {
+            self.radius_in(mid + 1..range.end, 1 - axis, p, radius2, found);
+        }
+    }
+
+    /// Return the indices of the elements inside the polygon of `[x, y]` `vertices`, the last
+    /// connected to the first, in ascending order.
+    pub fn within_polygon(&self, vertices: &[[f64; 2]]) -> Vec<u32> {
+        let mut found = Vec::new();
+        let bbox = polygon::bbox(vertices);
+        self.polygon_in(0..self.len(), 0, vertices, bbox, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    fn polygon_in(
+        &self,
+        range: Range<usize>,
+        axis: usize,
+        poly: &[Point],
+        bbox: [f64; 4],
+        found: &mut Vec<u32>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let mid = (range.start + range.end) / 2;
+        let median = self.point(mid);
+        let in_bbox =
+            (bbox[0]..=bbox[2]).contains(&median[0]) && (bbox[1]..=bbox[3]).contains(&median[1]);
+        if in_bbox && polygon::contains(poly, median) {
+            found.push(self.order[mid]);
+        }
+        if bbox[axis] <= median[axis] {
+            self.polygon_in(range.start..mid, 1 - axis, poly, bbox, found);
+        }
+        if bbox[axis + 2] >= median[axis] {
+            self.polygon_in(mid + 1..range.end, 1 - axis, poly, bbox, found);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::rng::Rng;
+    use crate::testing::triangles;
+
+    #[test]
+    fn test_queries() {
+        let mut rng = Rng::new(5);
+        let (x, y): (Vec<f64>, Vec<f64>) = (0..500).map(|_| (rng.uniform(), rng.uniform())).unzip();
+        let index = KdTree::new(&x, &y);
+        assert_eq!(index.len(), 500);
+        let distance = |i: u32, p: Point| distance2([x[i as usize], y[i as usize]], p);
+        for _ in 0..20 {
+            let p = [rng.uniform(), rng.uniform()];
+            // brute force
+            let mut all: Vec<u32> = (0..500).collect();
+            all.sort_by(|a, b| distance(*a, p).total_cmp(&distance(*b, p)));
+            assert_eq!(index.nearest(p[0], p[1], 5), all[..5]);
+            let mut within: Vec<u32> = all
+                .into_iter()
+                .filter(|i| distance(*i, p) <= 0.01)
+                .collect();
+            within.sort();
+            assert_eq!(index.within_radius(p[0], p[1], 0.1), within);
+        }
+        let triangle = [[0., 0.], [0.5, 0.], [0., 0.5]];
+        let inside: Vec<u32> = (0..500)
+            .filter(|i| x[*i as usize] + y[*i as usize] < 0.5)
+            .collect();
+        assert_eq!(index.within_polygon(&triangle), inside);
+        assert!(index.nearest(0., 0., 0).is_empty());
+        assert_eq!(index.nearest(0., 0., 1000).len(), 500);
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = triangles();
+        let faces = KdTree::from_grid(&grid, Location::Face).unwrap();
+        assert_eq!(faces.nearest(0.9, 0.8, 1), [1]);
+        let edges = KdTree::from_grid(&grid, Location::Edge).unwrap();
+        assert_eq!(edges.nearest(0.5, 0.5, 1), [1]);
+        assert!(KdTree::from_grid(&Grid::Scalar, Location::Node).is_err());
+        assert!(KdTree::from_grid(&Grid::Scalar, Location::Face).is_err());
+        let cells = Grid::rectilinear(vec![0., 1., 3.], vec![0., 2.], vec![]);
+        let faces = KdTree::from_grid(&cells, Location::Face).unwrap();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(faces.nearest(2., 1., 2), [1, 0]);
+        assert_eq!(faces.within_radius(2., 1., 0.), [1]);
+        assert!(faces.within_radius(2., 1., -1.).is_empty());
+
+        let grid = Grid::UniformRectilinear {
+            shape: vec![3, 4],
+            spacing: vec![1., 1.],
+            origin: vec![0., 0.],
+        };
+        let mut model =
+            SyntheticForcing::new(0., 1., 1.)
+                .grid(grid)
+                .variable("a", "1", Signal::Constant(1.));
+        model.initialize("").unwrap();
+        let index = KdTree::from_model(&model, 0, Location::Node).unwrap();
+        let inds = index.within_radius(3., 2., 1.);
+        assert_eq!(inds, [7, 10, 11]);
+        let values = model.get_value_at_indices("a", &inds).unwrap();
+        assert_eq!(values.len(), 3);
+    }
+}
@@ -0,0 +1,508 @@
+use crate::errors::BmiNotImplementedError;
+use crate::{Bmi, BmiResult, GridType};
+use std::fmt;
+
+/// A violated grid invariant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridIssue {
+    /// A method required by the grid's type returned an error.
+    Missing { method: &'static str, error: String },
+    /// A method's slice length disagrees with the length implied by other methods.
+    Length {
+        method: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A count disagrees with the count implied by other methods.
+    Count {
+        method: &'static str,
+        expected: u32,
+        found: u32,
+    },
+    /// A connectivity index at `position` is not less than `bound`.
+    Index {
+        method: &'static str,
+        position: usize,
+        index: u32,
+        bound: u32,
+    },
+    /// A value at `position` is invalid, e.g. not finite or not increasing.
+    Value {
+        method: &'static str,
+        position: usize,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for GridIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridIssue::Missing { method, error } => write!(f, "{method}: {error}"),
+            GridIssue::Length {
+                method,
+                expected,
+                found,
+            } => write!(f, "{method}: length {found}, expected {expected}"),
+            GridIssue::Count {
+                method,
+                expected,
+                found,
+            } => write!(f, "{method}: {found}, expected {expected}"),
+            GridIssue::Index {
+                method,
+                position,
+                index,
+                bound,
+            } => write!(
+                f,
+                "{method}[{position}]: index {index} not less than {bound}"
+            ),
+            GridIssue::Value {
+                method,
+                position,
+                reason,
+            } => write!(f, "{method}[{position}]: {reason}"),
+        }
+    }
+}
+
+/// Result of [`validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridReport {
+    pub grid: i32,
+    /// The grid's reported type, `None` if [`get_grid_type`] failed.
+    ///
+    /// [`get_grid_type`]: Bmi::get_grid_type
+    pub grid_type: Option<GridType>,
+    pub issues: Vec<GridIssue>,
+}
+
+impl GridReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for GridReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.grid_type {
+            Some(grid_type) => write!(f, "grid {} ({grid_type})", self.grid)?,
+            None => write!(f, "grid {}", self.grid)?,
+        }
+        match self.issues.len() {
+            0 => write!(f, ": valid"),
+            n => {
+                write!(f, ": {n} issue(s)")?;
+                self.issues
+                    .iter()
+                    .try_for_each(|issue| write!(f, "\n  {issue}"))
+            }
+        }
+    }
+}
+
+struct Checker {
+    issues: Vec<GridIssue>,
+}
+
+impl Checker {
+    /// Record an error from `method`, returning its value if it succeeded.
+    fn call<T>(&mut self, method: &'static str, result: BmiResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.issues.push(GridIssue::Missing {
+                    method,
+                    error: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Like [`call`](Checker::call) for optional methods: not implemented is not an issue.
+    fn optional<T>(&mut self, method: &'static str, result: BmiResult<T>) -> Option<T> {
+        match result {
+            Err(err) if err.is::<BmiNotImplementedError>() => None,
+            result => self.call(method, result),
+        }
+    }
+
+    fn count(&mut self, method: &'static str, expected: u32, found: u32) {
+        if expected != found {
+            self.issues.push(GridIssue::Count {
+                method,
+                expected,
+                found,
+            });
+        }
+    }
+
+    /// Check the product of `shape` is `size`, without overflowing.
+    fn product(&mut self, method: &'static str, shape: &[u32], size: u32) {
+        let mut product = 1u64;
+        for (position, n) in shape.iter().enumerate() {
+            match product
+                .checked_mul(*n as u64)
+                .filter(|p| *p <= u32::MAX as u64)
+            {
+                Some(p) => product = p,
+                None => {
+                    self.issues.push(GridIssue::Value {
+                        method,
+                        position,
+                        reason: "product exceeds u32",
+                    });
+                    return;
+                }
+            }
+        }
+        self.count("get_grid_size", product as u32, size);
+    }
+
+    fn len<T>(&mut self, method: &'static str, values: &[T], expected: usize) -> bool {
+        if values.len() != expected {
+            self.issues.push(GridIssue::Length {
+                method,
+                expected,
+                found: values.len(),
+            });
+        }
+        values.len() == expected
+    }
+
+    fn indices(&mut self, method: &'static str, values: &[u32], bound: u32) {
+        if let Some((position, index)) = values.iter().enumerate().find(|(_, i)| **i >= bound) {
+            self.issues.push(GridIssue::Index {
+                method,
+                position,
+                index: *index,
+                bound,
+            });
+        }
+    }
+
+    fn value(
+        &mut self,
+        method: &'static str,
+        values: &[f64],
+        reason: &'static str,
+        ok: impl Fn(usize, f64) -> bool,
+    ) {
+        if let Some(position) = values.iter().enumerate().position(|(i, v)| !ok(i, *v)) {
+            self.issues.push(GridIssue::Value {
+                method,
+                position,
+                reason,
+            });
+        }
+    }
+
+    fn finite(&mut self, method: &'static str, values: &[f64]) {
+        self.value(method, values, "not finite", |_, v| v.is_finite());
+    }
+
+    /// Check node coordinate `method` has `len` finite values.
+    fn coords(&mut self, method: &'static str, values: Option<&[f64]>, len: usize) {
+        if let Some(values) = values
+            && self.len(method, values, len)
+        {
+            self.finite(method, values);
+        }
+    }
+}
+
+/// Check the invariants of a model's grid `grid` for its reported [`GridType`]:
+///
+/// - rank and size: scalar grids have rank 0 and size 1, vector grids rank 1, and structured
+///   grids a size of the product of their shape.
+/// - shape, spacing, and origin have rank values, spacings are positive, and values are
+///   finite.
+/// - node coordinates: rectilinear `x`, `y`, `z` lengths match the shape (last dimension
+///   first) and increase; structured quadrilateral, points, and unstructured coordinates have a
+///   value per node. `z` is checked for rank 3 grids.
+/// - unstructured connectivity: node count equals size, `edge_nodes` has two values per edge,
+///   `nodes_per_face` a value (at least 3) per face, `face_nodes` and `face_edges` the sum of
+///   `nodes_per_face` values, and node and edge indices are in range.
+///
+/// A method required by the grid type that returns an error is reported as
+/// [`GridIssue::Missing`]; checks depending on it are skipped. Edge data is optional, as in
+/// [`Grid::from_model`](crate::grid::Grid::from_model): `get_grid_edge_count`,
+/// `get_grid_edge_nodes`, and `get_grid_face_edges` returning
+/// Err([`BmiNotImplementedError`]) or no values are not checked.
+///
+/// Example:
+/// ```
+/// use bmi_rs::forcing::synthetic::SyntheticForcing;
+/// use bmi_rs::grid::Grid;
+/// use bmi_rs::grid::validate::validate;
+///
+/// let grid = Grid::UniformRectilinear { shape: vec![2, 3], spacing: vec![1.], origin: vec![0., 0.] };
+/// let report = validate(&SyntheticForcing::new(0., 1., 1.).grid(grid), 0);
+/// assert!(!report.is_valid());
+/// println!("{report}");
+/// ```
+pub fn validate<M: Bmi>(model: &M, grid: i32) -> GridReport {
+    let mut c = Checker { issues: Vec::new() };
+    let grid_type = c.call("get_grid_type", model.get_grid_type(grid));
+    let rank = c.call("get_grid_rank", model.get_grid_rank(grid));
+    let size = c.call("get_grid_size", model.get_grid_size(grid));
+    let (Some(grid_type), Some(rank), Some(size)) = (grid_type, rank, size) else {
+        return GridReport {
+            grid,
+            grid_type,
+            issues: c.issues,
+        };
+    };
+    let structured = matches!(
+        grid_type,
+        GridType::UniformRectilinear | GridType::Rectilinear | GridType::StructuredQuadrilateral
+    );
+    let mut shape = None;
+    if structured
+        && let Some(s) = c.call("get_grid_shape", model.get_grid_shape(grid))
+        && c.len("get_grid_shape", s, rank as usize)
+    {
+        c.product("get_grid_shape", s, size);
+        shape = Some(s);
+    }
+    let xyz = [
+        ("get_grid_x", model.get_grid_x(grid)),
+        ("get_grid_y", model.get_grid_y(grid)),
+        ("get_grid_z", model.get_grid_z(grid)),
+    ];
+    match grid_type {
+        GridType::Scalar => {
+            c.count("get_grid_rank", 0, rank);
+            c.count("get_grid_size", 1, size);
+        }
+        GridType::Vector => c.count("get_grid_rank", 1, rank),
+        GridType::UniformRectilinear => {
+            if let Some(spacing) = c.call("get_grid_spacing", model.get_grid_spacing(grid))
+                && c.len("get_grid_spacing", spacing, rank as usize)
+            {
+                c.value(
+                    "get_grid_spacing",
+                    spacing,
+                    "not positive and finite",
+                    |_, v| v > 0. && v.is_finite(),
+                );
+            }
+            if let Some(origin) = c.call("get_grid_origin", model.get_grid_origin(grid))
+                && c.len("get_grid_origin", origin, rank as usize)
+            {
+                c.finite("get_grid_origin", origin);
+            }
+        }
+        GridType::Rectilinear => {
+            let Some(shape) = shape else {
+                return GridReport {
+                    grid,
+                    grid_type: Some(grid_type),
+                    issues: c.issues,
+                };
+            };
+            for (axis, (method, coords)) in xyz.into_iter().enumerate().take(rank as usize) {
+                if let Some(coords) = c.call(method, coords)
+                    && c.len(method, coords, shape[shape.len() - 1 - axis] as usize)
+                {
+                    c.value(method, coords, "not finite and increasing", |i, v| {
+                        v.is_finite() && (i == 0 || v > coords[i - 1])
+                    });
+                }
+            }
+        }
+        GridType::StructuredQuadrilateral | GridType::Points | GridType::Unstructured => {
+            let mut nodes = size;
+            if grid_type == GridType::Unstructured
+                && let Some(count) = c.call("get_grid_node_count", model.get_grid_node_count(grid))
+            {
+                c.count("get_grid_node_count", size, count);
+                nodes = count;
+            }
+            let dims = match grid_type {
+                GridType::StructuredQuadrilateral => rank,
+                _ => rank.clamp(2, 3),
+            };
+            for (method, coords) in xyz.into_iter().take(dims as usize) {
+                let coords = c.call(method, coords);
+                c.coords(method, coords, nodes as usize);
+            }
+            if grid_type == GridType::Unstructured {
+                unstructured(&mut c, model, grid, nodes);
+            }
+        }
+    }
+    GridReport {
+        grid,
+        grid_type: Some(grid_type),
+        issues: c.issues,
+    }
+}
+
+fn unstructured<M: Bmi>(c: &mut Checker, model: &M, grid: i32, nodes: u32) {
+    let edges = c.optional("get_grid_edge_count", model.get_grid_edge_count(grid));
+    let faces = c.call("get_grid_face_count", model.get_grid_face_count(grid));
+    if let Some(edges) = edges
+        && let Some(edge_nodes) = c
+            .optional("get_grid_edge_nodes", model.get_grid_edge_nodes(grid))
+            .filter(|edge_nodes| !edge_nodes.is_empty())
+        && c.len("get_grid_edge_nodes", edge_nodes, 2 * edges as usize)
+    {
+        c.indices("get_grid_edge_nodes", edge_nodes, nodes);
+    }
+    let Some(faces) = faces else {
+        return;
+    };
+    let Some(per_face) = c.call(
+        "get_grid_nodes_per_face",
+        model.get_grid_nodes_per_face(grid),
+    ) else {
+        return;
+    };
+    if !c.len("get_grid_nodes_per_face", per_face, faces as usize) {
+        return;
+    }
+    if let Some(position) = per_face.iter().position(|n| *n < 3) {
+        c.issues.push(GridIssue::Value {
+            method: "get_grid_nodes_per_face",
+            position,
+            reason: "fewer than 3 nodes",
+        });
+    }
+    let total = per_face.iter().map(|n| *n as usize).sum();
+    if let Some(face_nodes) = c.call("get_grid_face_nodes", model.get_grid_face_nodes(grid))
+        && c.len("get_grid_face_nodes", face_nodes, total)
+    {
+        c.indices("get_grid_face_nodes", face_nodes, nodes);
+    }
+    if let Some(edges) = edges
+        && let Some(face_edges) = c
+            .optional("get_grid_face_edges", model.get_grid_face_edges(grid))
+            .filter(|face_edges| !face_edges.is_empty())
+        && c.len("get_grid_face_edges", face_edges, total)
+    {
+        c.indices("get_grid_face_edges", face_edges, edges);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::SyntheticForcing;
+    use crate::grid::Grid;
+    use crate::testing::triangles;
+
+    fn check(grid: Grid) -> GridReport {
+        validate(&SyntheticForcing::new(0., 1., 1.).grid(grid), 0)
+    }
+
+    #[test]
+    fn test_valid() {
+        let grids = [
+            Grid::Scalar,
+            Grid::Points {
+                x: vec![0., 1.],
+                y: vec![0., 1.],
+                z: vec![],
+            },
+            Grid::rectilinear(vec![0., 1., 3.], vec![0., 2.], vec![]),
+            Grid::UniformRectilinear {
+                shape: vec![2, 3],
+                spacing: vec![1., 1.],
+                origin: vec![0., 0.],
+            },
+            triangles(),
+            // without edges
+            Grid::Unstructured {
+                x: vec![0., 1., 0.],
+                y: vec![0., 0., 1.],
+                z: vec![],
+                edge_nodes: vec![],
+                face_edges: vec![],
+                face_nodes: vec![0, 1, 2],
+                nodes_per_face: vec![3],
+            },
+        ];
+        for grid in grids {
+            let report = check(grid);
+            assert!(report.is_valid(), "{report}");
+        }
+        let report = validate(&SyntheticForcing::new(0., 1., 1.), 1);
+        assert!(matches!(
+            report.issues[0],
+            GridIssue::Missing {
+                method: "get_grid_type",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let report = check(Grid::Rectilinear {
+            shape: vec![2, 3],
+            x: vec![0., 2., 1.],
+            y: vec![0.],
+            z: vec![],
+        });
+        assert_eq!(
+            report.issues,
+            [
+                GridIssue::Value {
+                    method: "get_grid_x",
+                    position: 2,
+                    reason: "not finite and increasing"
+                },
+                GridIssue::Length {
+                    method: "get_grid_y",
+                    expected: 2,
+                    found: 1
+                },
+            ]
+        );
+
+        let report = check(Grid::Unstructured {
+            x: vec![0., 1., 0.],
+            y: vec![0., 0., 1.],
+            z: vec![],
+            edge_nodes: vec![0, 1, 1, 2, 2, 3],
+            face_edges: vec![0, 1, 2],
+            face_nodes: vec![0, 1, 2, 0],
+            nodes_per_face: vec![3],
+        });
+        assert_eq!(
+            report.issues,
+            [
+                GridIssue::Index {
+                    method: "get_grid_edge_nodes",
+                    position: 5,
+                    index: 3,
+                    bound: 3
+                },
+                GridIssue::Length {
+                    method: "get_grid_face_nodes",
+                    expected: 3,
+                    found: 4
+                },
+            ]
+        );
+        assert!(
+            report
+                .to_string()
+                .starts_with("grid 0 (unstructured): 2 issue(s)")
+        );
+
+        // a shape whose product does not fit the reported u32 size
+        let mut c = Checker { issues: Vec::new() };
+        c.product("get_grid_shape", &[100000, 100000], 0);
+        assert_eq!(
+            c.issues,
+            [GridIssue::Value {
+                method: "get_grid_shape",
+                position: 1,
+                reason: "product exceeds u32"
+            }]
+        );
+    }
+}
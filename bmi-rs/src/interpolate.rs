@@ -0,0 +1,353 @@
+use crate::bmi::forward_grid_funcs;
+use crate::errors::{BmiIndexOutOfBounds, BmiUnknownVariable};
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values};
+use std::collections::HashMap;
+
+/// How an [`Interpolate`] input is evaluated between the times its values were set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Linearly interpolate between the two most recent values.
+    Linear,
+    /// Take the most recent value closest in time.
+    Nearest,
+}
+
+/// How an [`Interpolate`] input is evaluated outside the times its values were set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// Hold the closest value.
+    Hold,
+    /// Extend the line through the two most recent values.
+    Linear,
+}
+
+#[derive(Debug)]
+struct Forcing {
+    method: Method,
+    value_type: ValueType,
+    prev: Option<(f64, Vec<f64>)>,
+    next: Option<(f64, Vec<f64>)>,
+    values: Option<Values>,
+}
+
+impl Forcing {
+    fn push(&mut self, time: f64, v: Vec<f64>) {
+        match self.next.take() {
+            // replace a value set at the same time
+            Some((t, _)) if t == time => {}
+            next => self.prev = next,
+        }
+        self.values = Some(Values::from_f64_slice(self.value_type, &v));
+        self.next = Some((time, v));
+    }
+
+    fn evaluate(&self, time: f64, extrapolation: Extrapolation) -> Option<Vec<f64>> {
+        let (t1, v1) = self.next.as_ref()?;
+        let Some((t0, v0)) = self.prev.as_ref() else {
+            return Some(v1.clone());
+        };
+        let outside = time < *t0 || time > *t1;
+        if outside && extrapolation == Extrapolation::Hold {
+            let v = if time < *t0 { v0 } else { v1 };
+            return Some(v.clone());
+        }
+        match self.method {
+            Method::Nearest if !outside => {
+                let v = if time - t0 < t1 - time { v0 } else { v1 };
+                Some(v.clone())
+            }
+            _ => {
+                let frac = (time - t0) / (t1 - t0);
+                let v = std::iter::zip(v0, v1)
+                    .map(|(v0, v1)| v0 + (v1 - v0) * frac)
+                    .collect();
+                Some(v)
+            }
+        }
+    }
+}
+
+/// [`Bmi`] adapter that time interpolates input variables set at intervals coarser than the
+/// wrapped model's time step.
+///
+/// Each value set on a configured input is stored with the time it is valid at:
+/// the adapter's current time plus an optional [`valid_offset`].
+/// Before each wrapped model [`update`], the value at the midpoint of the step being taken is
+/// computed from the two most recently set values using the input's [`Method`] and pushed into
+/// the wrapped model.
+///
+/// If a single value has been set, it is held.
+/// If no value has been set, the wrapped model's value is left untouched.
+/// Times outside the two most recent values are handled according to the adapter's
+/// [`Extrapolation`], [`Extrapolation::Hold`] by default.
+///
+/// Example:
+/// ```compile_fail
+/// // hourly forcings valid at the end of the hour, 15 minute model
+/// let model = Interpolate::new(RunoffModel::new())
+///     .input("precipitation_rate", Method::Linear)
+///     .valid_offset(3600.);
+/// ```
+///
+/// [`valid_offset`]: Interpolate::valid_offset
+/// [`update`]: Bmi::update
+pub struct Interpolate<M> {
+    model: M,
+    offset: f64,
+    extrapolation: Extrapolation,
+    inputs: HashMap<String, Forcing>,
+}
+
+impl<M: Bmi> Interpolate<M> {
+    /// Wrap `model`.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            offset: 0.,
+            extrapolation: Extrapolation::Hold,
+            inputs: HashMap::new(),
+        }
+    }
+
+    /// Interpolate input variable `name` using `method`.
+    pub fn input(mut self, name: &str, method: Method) -> Self {
+        let forcing = Forcing {
+            method,
+            value_type: ValueType::F64,
+            prev: None,
+            next: None,
+            values: None,
+        };
+        self.inputs.insert(name.to_string(), forcing);
+        self
+    }
+
+    /// Treat values as valid `offset` time units after the time they are set.
+    /// e.g. set `offset` to the forcing interval if forcings are set at the start of an interval
+    /// but represent its end.
+    pub fn valid_offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Evaluate times outside the two most recently set values using `extrapolation`.
+    pub fn extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    /// Return a reference to the wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    /// Consume the adapter and return the wrapped model.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+}
+
+impl<M: Bmi> Bmi for Interpolate<M> {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        self.model.initialize(config_file)?;
+        for (name, forcing) in self.inputs.iter_mut() {
+            if !self.model.get_input_var_names().contains(&name.as_str()) {
+                return BmiUnknownVariable.into();
+            }
+            forcing.value_type = self.model.get_var_type(name)?;
+            forcing.prev = None;
+            forcing.next = None;
+            forcing.values = None;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        let time = self.model.get_current_time() + self.model.get_time_step() / 2.;
+        for (name, forcing) in self.inputs.iter() {
+            let Some(v) = forcing.evaluate(time, self.extrapolation) else {
+                continue;
+            };
+            let v = Values::from_f64_slice(forcing.value_type, &v);
+            self.model.set_value(name, (&v).into())?;
+        }
+        self.model.update()
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        self.model.finalize()
+    }
+
+    fn get_component_name(&self) -> &str {
+        self.model.get_component_name()
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        self.model.get_input_var_names()
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.model.get_output_var_names()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        self.model.get_var_grid(name)
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        self.model.get_var_type(name)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        self.model.get_var_units(name)
+    }
+
+    fn get_var_itemsize(&self, name: &str) -> BmiResult<u32> {
+        self.model.get_var_itemsize(name)
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        self.model.get_var_nbytes(name)
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        self.model.get_var_location(name)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.model.get_current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.model.get_start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.model.get_end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        self.model.get_time_units()
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.model.get_time_step()
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        if let Some(Forcing {
+            values: Some(values),
+            ..
+        }) = self.inputs.get(name)
+        {
+            return Ok(values.into());
+        }
+        self.model.get_value_ptr(name)
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        let time = self.model.get_current_time() + self.offset;
+        match self.inputs.get_mut(name) {
+            Some(forcing) => {
+                if src.len() != self.model.get_value_ptr(name)?.len() {
+                    return BmiIndexOutOfBounds.into();
+                }
+                forcing.push(time, src.to_f64_vec());
+                Ok(())
+            }
+            None => self.model.set_value(name, src),
+        }
+    }
+
+    fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()> {
+        let time = self.model.get_current_time() + self.offset;
+        match self.inputs.get_mut(name) {
+            Some(forcing) => {
+                let mut v = match &forcing.next {
+                    Some((_, v)) => v.clone(),
+                    None => self.model.get_value_ptr(name)?.to_f64_vec(),
+                };
+                if inds.iter().any(|i| *i as usize >= v.len()) {
+                    return BmiIndexOutOfBounds.into();
+                }
+                for (i, item) in std::iter::zip(inds, src.to_f64_vec()) {
+                    v[*i as usize] = item;
+                }
+                forcing.push(time, v);
+                Ok(())
+            }
+            None => self.model.set_value_at_indices(name, inds, src),
+        }
+    }
+
+    forward_grid_funcs!(model);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+
+    fn flux(model: &impl Bmi) -> f64 {
+        model.get_value_ptr("flux").unwrap().to_f64_vec()[0]
+    }
+
+    #[test]
+    fn test_linear() {
+        let mut model = Interpolate::new(Reservoir::new(1.))
+            .input("rate", Method::Linear)
+            .valid_offset(4.);
+        model.initialize("").unwrap();
+
+        // first value is held
+        model.set_value("rate", RefValues::F64(&[4.])).unwrap();
+        model.update().unwrap();
+        assert_eq!(flux(&model), 4.);
+        model.update_until(4.).unwrap();
+
+        // (4, 4.) -> (8, 8.)
+        model.set_value("rate", RefValues::F64(&[8.])).unwrap();
+        for expected in [4.5, 5.5, 6.5, 7.5] {
+            model.update().unwrap();
+            assert_eq!(flux(&model), expected);
+        }
+        // past the most recent value
+        model.update().unwrap();
+        assert_eq!(flux(&model), 8.);
+        assert_eq!(model.get_value_ptr("rate").unwrap().to_f64_vec(), [8.]);
+    }
+
+    #[test]
+    fn test_nearest_and_extrapolate() {
+        let mut model = Interpolate::new(Reservoir::new(1.))
+            .input("rate", Method::Nearest)
+            .extrapolation(Extrapolation::Linear);
+        model.initialize("").unwrap();
+
+        // no value set leaves the wrapped model untouched
+        model.update().unwrap();
+        assert_eq!(flux(&model), 0.);
+
+        model.set_value("rate", RefValues::F64(&[1.])).unwrap();
+        model.update_until(3.).unwrap();
+        model.set_value("rate", RefValues::F64(&[3.])).unwrap();
+        // (1, 1.) -> (3, 3.), evaluated at 3.5
+        model.update().unwrap();
+        assert_eq!(flux(&model), 3.5);
+    }
+
+    #[test]
+    fn test_set_value_at_indices() {
+        let mut model = Interpolate::new(Reservoir::new(1.)).input("rate", Method::Linear);
+        model.initialize("").unwrap();
+        model
+            .set_value_at_indices("rate", &[0], RefValues::F64(&[2.]))
+            .unwrap();
+        model.update().unwrap();
+        assert_eq!(flux(&model), 2.);
+        let err = model
+            .set_value_at_indices("rate", &[1], RefValues::F64(&[2.]))
+            .unwrap_err();
+        assert!(err.is::<BmiIndexOutOfBounds>());
+    }
+}
@@ -0,0 +1,241 @@
+use crate::grid::validate::{GridReport, validate};
+use crate::{Bmi, BmiResult, GridType, Location, ValueType};
+
+/// Metadata of a model variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarInfo {
+    pub name: String,
+    pub grid: i32,
+    pub location: Location,
+    pub value_type: ValueType,
+    pub units: String,
+    pub nbytes: u32,
+    /// Listed by [`get_input_var_names`].
+    ///
+    /// [`get_input_var_names`]: Bmi::get_input_var_names
+    pub input: bool,
+    /// Listed by [`get_output_var_names`].
+    ///
+    /// [`get_output_var_names`]: Bmi::get_output_var_names
+    pub output: bool,
+}
+
+impl VarInfo {
+    /// Return the number of values, [`nbytes`] / item size.
+    ///
+    /// [`nbytes`]: VarInfo::nbytes
+    pub fn len(&self) -> usize {
+        self.nbytes as usize / self.value_type.bytes()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Metadata of a model grid. Grid methods the model does not implement are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridInfo {
+    pub id: i32,
+    pub grid_type: Option<GridType>,
+    pub rank: Option<u32>,
+    pub size: Option<u32>,
+    /// Indices of the grid's variables in [`Inventory::vars`].
+    pub vars: Vec<usize>,
+}
+
+/// A model's grids and the variables defined on them.
+///
+/// BMI has no call to list grids, so grid ids are discovered from [`get_var_grid`] of each
+/// input and output variable. A variable that is both an input and output is listed once.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Bmi;
+/// use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+/// use bmi_rs::inventory::Inventory;
+///
+/// let mut forcing = SyntheticForcing::new(0., 1., 1.).variable("precip", "mm", Signal::Constant(1.));
+/// forcing.initialize("").unwrap();
+/// let inventory = Inventory::new(&forcing).unwrap();
+/// assert_eq!(inventory.grids().len(), 1);
+/// assert_eq!(inventory.vars_on(0).map(|v| v.name.as_str()).collect::<Vec<_>>(), ["precip"]);
+/// ```
+///
+/// [`get_var_grid`]: Bmi::get_var_grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inventory {
+    vars: Vec<VarInfo>,
+    grids: Vec<GridInfo>,
+}
+
+impl Inventory {
+    /// Build the inventory of an initialized `model`.
+    pub fn new<M: Bmi>(model: &M) -> BmiResult<Self> {
+        let mut vars: Vec<VarInfo> = Vec::new();
+        let inputs = model.get_input_var_names().iter().map(|name| (name, true));
+        let outputs = model
+            .get_output_var_names()
+            .iter()
+            .map(|name| (name, false));
+        for (name, input) in inputs.chain(outputs) {
+            if let Some(var) = vars.iter_mut().find(|var| var.name == *name) {
+                var.output |= !input;
+                continue;
+            }
+            vars.push(VarInfo {
+                name: name.to_string(),
+                grid: model.get_var_grid(name)?,
+                location: model.get_var_location(name)?,
+                value_type: model.get_var_type(name)?,
+                units: model.get_var_units(name)?.to_string(),
+                nbytes: model.get_var_nbytes(name)?,
+                input,
+                output: !input,
+            });
+        }
+        let mut grids: Vec<GridInfo> = Vec::new();
+        for (i, var) in vars.iter().enumerate() {
+            match grids.iter_mut().find(|grid| grid.id == var.grid) {
+                Some(grid) => grid.vars.push(i),
+                None => grids.push(GridInfo {
+                    id: var.grid,
+                    grid_type: model.get_grid_type(var.grid).ok(),
+                    rank: model.get_grid_rank(var.grid).ok(),
+                    size: model.get_grid_size(var.grid).ok(),
+                    vars: vec![i],
+                }),
+            }
+        }
+        grids.sort_by_key(|grid| grid.id);
+        Ok(Self { vars, grids })
+    }
+
+    /// Return the grids, ordered by id.
+    pub fn grids(&self) -> &[GridInfo] {
+        &self.grids
+    }
+
+    pub fn grid(&self, id: i32) -> Option<&GridInfo> {
+        self.grids.iter().find(|grid| grid.id == id)
+    }
+
+    /// Return the variables, inputs first, in the model's order.
+    pub fn vars(&self) -> &[VarInfo] {
+        &self.vars
+    }
+
+    pub fn var(&self, name: &str) -> Option<&VarInfo> {
+        self.vars.iter().find(|var| var.name == name)
+    }
+
+    /// Return the variables on grid `id`.
+    pub fn vars_on(&self, id: i32) -> impl Iterator<Item = &VarInfo> {
+        self.grid(id)
+            .into_iter()
+            .flat_map(|grid| grid.vars.iter().map(|i| &self.vars[*i]))
+    }
+
+    /// [`validate`] each grid of `model`.
+    pub fn validate<M: Bmi>(&self, model: &M) -> Vec<GridReport> {
+        self.grids
+            .iter()
+            .map(|grid| validate(model, grid.id))
+            .collect()
+    }
+
+    /// Return `true` if `model`'s variable names or their grid ids differ from the inventory.
+    pub fn is_stale<M: Bmi>(&self, model: &M) -> bool {
+        let mut names: Vec<&str> = Vec::with_capacity(self.vars.len());
+        for name in model
+            .get_input_var_names()
+            .iter()
+            .chain(model.get_output_var_names())
+        {
+            if !names.contains(name) {
+                names.push(name);
+            }
+        }
+        names.len() != self.vars.len()
+            || names.iter().zip(&self.vars).any(|(name, var)| {
+                *name != var.name || model.get_var_grid(name).ok() != Some(var.grid)
+            })
+    }
+}
+
+/// Lazily built [`Inventory`], rebuilt when it is [`is_stale`] or was [`invalidate`]d.
+///
+/// Grid ranks and sizes are not compared when checking staleness, so call [`invalidate`] after
+/// calls that may change them, e.g. [`initialize`].
+///
+/// [`is_stale`]: Inventory::is_stale
+/// [`invalidate`]: InventoryCache::invalidate
+/// [`initialize`]: Bmi::initialize
+#[derive(Debug, Clone, Default)]
+pub struct InventoryCache {
+    inventory: Option<Inventory>,
+}
+
+impl InventoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the inventory of `model`, building it if needed.
+    pub fn get<M: Bmi>(&mut self, model: &M) -> BmiResult<&Inventory> {
+        if let Some(inventory) = &self.inventory
+            && inventory.is_stale(model)
+        {
+            self.inventory = None;
+        }
+        if self.inventory.is_none() {
+            self.inventory = Some(Inventory::new(model)?);
+        }
+        Ok(self.inventory.as_ref().unwrap())
+    }
+
+    /// Discard the cached inventory.
+    pub fn invalidate(&mut self) {
+        self.inventory = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::grid::Grid;
+    use crate::testing::Reservoir;
+
+    #[test]
+    fn test_inventory() {
+        let model = Reservoir::new(1.);
+        let inventory = Inventory::new(&model).unwrap();
+        assert_eq!(inventory.vars().len(), 3);
+        assert!(inventory.var("rate").unwrap().input);
+        assert!(inventory.var("flux").unwrap().output);
+        assert_eq!(inventory.var("storage").unwrap().len(), 1);
+        let grid = &inventory.grids()[0];
+        assert_eq!(grid.grid_type, Some(GridType::Scalar));
+        assert_eq!((grid.rank, grid.size), (Some(0), Some(1)));
+        assert_eq!(inventory.vars_on(0).count(), 3);
+        assert_eq!(inventory.vars_on(1).count(), 0);
+        assert!(inventory.validate(&model).iter().all(GridReport::is_valid));
+        assert!(!inventory.is_stale(&model));
+    }
+
+    #[test]
+    fn test_cache() {
+        let forcing = SyntheticForcing::new(0., 1., 1.)
+            .grid(Grid::Vector { size: 3 })
+            .variable("a", "1", Signal::Constant(1.));
+        let mut cache = InventoryCache::new();
+        assert_eq!(cache.get(&forcing).unwrap().vars().len(), 1);
+        assert_eq!(cache.get(&forcing).unwrap().grids()[0].size, Some(3));
+
+        let forcing = forcing.variable("b", "1", Signal::Constant(2.));
+        let inventory = cache.get(&forcing).unwrap();
+        assert_eq!(inventory.vars().len(), 2);
+        assert_eq!(inventory.grids()[0].vars, [0, 1]);
+    }
+}
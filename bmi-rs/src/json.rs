@@ -0,0 +1,263 @@
+use std::fmt;
+
+/// Return `s` as a JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Return `values` as a JSON array.
+pub(crate) fn json_list<T: fmt::Display>(values: impl IntoIterator<Item = T>) -> String {
+    let values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+/// Parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a JSON document, `None` if it is not valid JSON.
+    pub(crate) fn parse(json: &str) -> Option<Json> {
+        let mut parser = JsonParser {
+            bytes: json.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        (parser.pos == parser.bytes.len()).then_some(value)
+    }
+
+    /// Return the value at `path` of nested object keys.
+    pub(crate) fn get(&self, path: &[&str]) -> Option<&Json> {
+        path.iter().try_fold(self, |value, key| match value {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return an array of non-negative integers.
+    pub(crate) fn as_usizes(&self) -> Option<Vec<usize>> {
+        let Json::Array(values) = self else {
+            return None;
+        };
+        values
+            .iter()
+            .map(|v| match v {
+                Json::Number(n) if *n >= 0. && n.fract() == 0. => Some(*n as usize),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Recursive descent [RFC 8259](https://datatracker.ietf.org/doc/html/rfc8259) parser.
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace and consume `byte` if it is next.
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.bytes.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn literal(&mut self, literal: &str, value: Json) -> Option<Json> {
+        let end = self.pos + literal.len();
+        (self.bytes.get(self.pos..end)? == literal.as_bytes()).then(|| {
+            self.pos = end;
+            value
+        })
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        if !self.eat(b':') {
+                            return None;
+                        }
+                        members.push((key, self.value()?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Json::Object(members))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        values.push(self.value()?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Json::Array(values))
+            }
+            b'"' => self.string().map(Json::String),
+            b't' => self.literal("true", Json::Bool(true)),
+            b'f' => self.literal("false", Json::Bool(false)),
+            b'n' => self.literal("null", Json::Null),
+            _ => {
+                let start = self.pos;
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| b.is_ascii_digit() || b"+-.eE".contains(b))
+                {
+                    self.pos += 1;
+                }
+                let number = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+                number.parse().ok().map(Json::Number)
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let hex = std::str::from_utf8(self.bytes.get(self.pos..self.pos + 4)?).ok()?;
+        self.pos += 4;
+        u32::from_str_radix(hex, 16).ok()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.bytes.get(self.pos) != Some(&b'"') {
+            return None;
+        }
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            let byte = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            match byte {
+                b'"' => return String::from_utf8(out).ok(),
+                b'\\' => {
+                    let escaped = *self.bytes.get(self.pos)?;
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // UTF-16 surrogate pair
+                            if (0xd800..0xdc00).contains(&code) {
+                                if self.bytes.get(self.pos..self.pos + 2)? != b"\\u" {
+                                    return None;
+                                }
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return None;
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            char::from_u32(code)?
+                        }
+                        _ => return None,
+                    };
+                    out.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte if byte < 0x20 => return None,
+                byte => out.push(byte),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let json = Json::parse(
+            r#" {"units": "shape", "shape": [2, 3], "a": {"b": [true, null, -1.5e1]},
+                "s": "\"\u00e9\ud83d\ude00\n"} "#,
+        )
+        .unwrap();
+        assert_eq!(json.get(&["shape"]).unwrap().as_usizes().unwrap(), [2, 3]);
+        assert_eq!(json.get(&["units"]).unwrap().as_str(), Some("shape"));
+        let b = Json::Array(vec![Json::Bool(true), Json::Null, Json::Number(-15.)]);
+        assert_eq!(json.get(&["a", "b"]), Some(&b));
+        assert_eq!(
+            json.get(&["s"]).unwrap().as_str(),
+            Some("\"\u{e9}\u{1f600}\n")
+        );
+        assert_eq!(json.get(&["a", "c"]), None);
+        assert_eq!(json.get(&["units", "b"]), None);
+        assert_eq!(b.as_usizes(), None);
+        for invalid in [
+            "",
+            "{",
+            "[1,]",
+            r#"{"a" 1}"#,
+            "nul",
+            "1 2",
+            r#""\u""#,
+            r#""\ud83d""#,
+        ] {
+            assert_eq!(Json::parse(invalid), None, "{invalid}");
+        }
+    }
+}
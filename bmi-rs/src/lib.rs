@@ -4,9 +4,86 @@
 /// [bmi-c interface](https://github.com/csdms/bmi-c).
 pub mod bmi;
 
+/// Calibration of [`Bmi`] model parameters against observations.
+pub mod calibrate;
+
+/// Drift-free fixed time step [`Clock`] for [`Bmi`] implementations.
+pub mod clock;
+
+/// `key = value` configuration files for [`Bmi::initialize`].
+pub mod config;
+
+/// Ensemble Kalman filter data assimilation for [`Bmi`] models.
+pub mod da;
+
+/// Concurrent ensembles of [`Bmi`] model instances.
+pub mod ensemble;
+
 pub mod errors;
 
+/// [`Bmi`] implementations that provide forcing data to other models.
+pub mod forcing;
+
+/// GeoJSON export of [`Bmi`] grid faces and face variables.
+pub mod geojson;
+
+/// Typed descriptors of [`Bmi`] model grids.
+pub mod grid;
+
+/// [`Bmi`] adapter that time interpolates coarsely set input variables.
+pub mod interpolate;
+
+/// Discovery of a [`Bmi`] model's grids and variables.
+pub mod inventory;
+
+/// Pure Rust NetCDF-3 output writer for [`Bmi`] variables.
+pub mod netcdf;
+
+/// Owned variable name lists for [`Bmi`] implementations.
+pub mod names;
+
+/// Calibration parameters of [`Bmi`] models.
+pub mod param;
+
+/// Parquet time series output for [`Bmi`] variables.
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+/// Regridding of [`Bmi`] variables between grids.
+pub mod regrid;
+
+/// [`Bmi`] adapter that presents a model with a different time step.
+pub mod resample;
+
+/// Morris and Sobol global sensitivity analysis of [`Bmi`] model parameters.
+pub mod sensitivity;
+
+/// Checkpoint and restart of [`Bmi`] model state.
+pub mod state;
+
+/// VTK XML output of [`Bmi`] grids and variables.
+pub mod vtk;
+
+/// Local Zarr v2/v3 output store for [`Bmi`] variables.
+pub mod zarr;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+
+mod json;
+
+mod polygon;
+
+mod rng;
+
+mod spatial;
+
+#[cfg(test)]
+mod testing;
+
 mod wrapper;
 pub use crate::bmi::{
     Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values, register_model,
 };
+pub use crate::clock::Clock;
+pub use crate::state::BmiState;
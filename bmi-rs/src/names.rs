@@ -0,0 +1,56 @@
+/// Owned list of names that can be borrowed as a `&[&str]`, as returned by
+/// [`Bmi::get_input_var_names`] and [`Bmi::get_output_var_names`].
+///
+/// Useful for [`Bmi`] implementations whose variables are only known at runtime.
+///
+/// Example:
+/// ```
+/// use bmi_rs::names::Names;
+///
+/// let names = Names::new(["a", "b"]);
+/// assert_eq!(names.as_slice(), ["a", "b"]);
+/// assert_eq!(names.position("b"), Some(1));
+/// ```
+///
+/// [`Bmi`]: crate::Bmi
+/// [`Bmi::get_input_var_names`]: crate::Bmi::get_input_var_names
+/// [`Bmi::get_output_var_names`]: crate::Bmi::get_output_var_names
+#[derive(Debug, Default)]
+pub struct Names {
+    // NOTE: `refs` borrows from the heap allocations owned by `owned`. The allocations do not
+    //       move when `Names` moves and `owned` is never mutated, so `refs` is valid for as long
+    //       as `self` is.
+    refs: Vec<&'static str>,
+    owned: Vec<Box<str>>,
+}
+
+impl Names {
+    /// Create from an iterator of names.
+    pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        let owned: Vec<Box<str>> = names
+            .into_iter()
+            .map(|name| name.into().into_boxed_str())
+            .collect();
+        let refs = owned
+            .iter()
+            .map(|name| unsafe { &*(name.as_ref() as *const str) })
+            .collect();
+        Self { refs, owned }
+    }
+
+    /// Return the names as a slice.
+    pub fn as_slice(&self) -> &[&str] {
+        &self.refs
+    }
+
+    /// Return the index of `name`, if present.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.owned.iter().position(|n| n.as_ref() == name)
+    }
+}
+
+impl Clone for Names {
+    fn clone(&self) -> Self {
+        Names::new(self.owned.iter().map(|name| name.to_string()))
+    }
+}
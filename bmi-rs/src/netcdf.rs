@@ -0,0 +1,926 @@
+use crate::bmi::{Bmi, BmiResult, GridType, Location, RefValues, ValueType};
+use crate::errors::OutputError;
+use crate::grid::Grid;
+use crate::spatial;
+use std::collections::{HashMap, HashSet};
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const NC_DIMENSION: u32 = 0x0a;
+const NC_VARIABLE: u32 = 0x0b;
+const NC_ATTRIBUTE: u32 = 0x0c;
+
+/// Offset of `numrecs` in the header.
+const NUMRECS_OFFSET: u64 = 4;
+
+/// NetCDF-3 file format variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Classic format. Variable offsets are limited to 2 GiB.
+    #[default]
+    Classic,
+    /// 64-bit offset format.
+    Offset64,
+}
+
+/// NetCDF-3 external data types.
+/// Classic files have no unsigned or 64-bit integer types, see [`NetcdfWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NcType {
+    Char = 2,
+    Short = 3,
+    Int = 4,
+    Float = 5,
+    Double = 6,
+}
+
+impl NcType {
+    fn size(self) -> usize {
+        match self {
+            NcType::Char => 1,
+            NcType::Short => 2,
+            NcType::Int | NcType::Float => 4,
+            NcType::Double => 8,
+        }
+    }
+}
+
+impl From<ValueType> for NcType {
+    fn from(value: ValueType) -> Self {
+        match value {
+            ValueType::I16 => NcType::Short,
+            ValueType::U16 | ValueType::I32 => NcType::Int,
+            ValueType::F32 => NcType::Float,
+            ValueType::U32 | ValueType::I64 | ValueType::U64 | ValueType::F64 => NcType::Double,
+        }
+    }
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend(value.to_be_bytes());
+}
+
+fn put_name(buf: &mut Vec<u8>, name: &str) {
+    put_u32(buf, name.len() as u32);
+    buf.extend(name.as_bytes());
+    pad(buf);
+}
+
+/// Append `values` as big-endian `nc_type` items, see [`NcType`]'s `From<ValueType>`.
+fn put_values(buf: &mut Vec<u8>, values: &RefValues) {
+    match values {
+        RefValues::I16(v) => v.iter().for_each(|x| buf.extend(x.to_be_bytes())),
+        RefValues::U16(v) => v.iter().for_each(|x| buf.extend((*x as i32).to_be_bytes())),
+        RefValues::I32(v) => v.iter().for_each(|x| buf.extend(x.to_be_bytes())),
+        RefValues::U32(v) => v.iter().for_each(|x| buf.extend((*x as f64).to_be_bytes())),
+        RefValues::I64(v) => v.iter().for_each(|x| buf.extend((*x as f64).to_be_bytes())),
+        RefValues::U64(v) => v.iter().for_each(|x| buf.extend((*x as f64).to_be_bytes())),
+        RefValues::F32(v) => v.iter().for_each(|x| buf.extend(x.to_be_bytes())),
+        RefValues::F64(v) => v.iter().for_each(|x| buf.extend(x.to_be_bytes())),
+    }
+    pad(buf);
+}
+
+/// Attribute value.
+#[derive(Debug, Clone, PartialEq)]
+enum Attr {
+    Text(String),
+    Int(i32),
+}
+
+impl From<&str> for Attr {
+    fn from(value: &str) -> Self {
+        Attr::Text(value.to_string())
+    }
+}
+
+#[derive(Debug)]
+struct Dim {
+    name: String,
+    /// 0 for the record dimension
+    len: usize,
+}
+
+#[derive(Debug)]
+enum Data {
+    /// Coordinate values written once after the header
+    Fixed(Vec<f64>),
+    /// Mesh connectivity written once after the header
+    Index(Vec<i32>),
+    /// Model time, one value per record
+    Time,
+    /// Model variable, one array per record
+    Record { len: usize },
+}
+
+#[derive(Debug)]
+struct Var {
+    name: String,
+    dims: Vec<usize>,
+    attrs: Vec<(String, Attr)>,
+    nc_type: NcType,
+    data: Data,
+    vsize: usize,
+    begin: u64,
+}
+
+impl Var {
+    fn is_record(&self) -> bool {
+        !matches!(self.data, Data::Fixed(_) | Data::Index(_))
+    }
+}
+
+fn put_attrs(buf: &mut Vec<u8>, attrs: &[(String, Attr)]) {
+    if attrs.is_empty() {
+        put_u32(buf, 0);
+        put_u32(buf, 0);
+        return;
+    }
+    put_u32(buf, NC_ATTRIBUTE);
+    put_u32(buf, attrs.len() as u32);
+    for (name, value) in attrs {
+        put_name(buf, name);
+        match value {
+            Attr::Text(value) => {
+                put_u32(buf, NcType::Char as u32);
+                put_name(buf, value);
+            }
+            Attr::Int(value) => {
+                put_u32(buf, NcType::Int as u32);
+                put_u32(buf, 1);
+                buf.extend(value.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// `get_grid_x`, `get_grid_y`, or `get_grid_z`.
+type GridCoords<M> = fn(&M, i32) -> BmiResult<&[f64]>;
+
+/// UGRID mesh variable name and dimensions by location.
+type Mesh = (String, HashMap<Location, usize>);
+
+/// File layout: dimensions, variables, and attributes.
+#[derive(Debug, Default)]
+struct Layout {
+    dims: Vec<Dim>,
+    attrs: Vec<(String, Attr)>,
+    vars: Vec<Var>,
+    /// spatial dimensions by (grid, location, length)
+    spatial: HashMap<(i32, Location, usize), Vec<usize>>,
+    /// UGRID meshes by grid, `None` if not unstructured
+    meshes: HashMap<i32, Option<Mesh>>,
+}
+
+impl Layout {
+    fn dim(&mut self, name: String, len: usize) -> usize {
+        self.dims.push(Dim { name, len });
+        self.dims.len() - 1
+    }
+
+    fn coord(&mut self, name: String, dims: Vec<usize>, values: Vec<f64>) {
+        self.vars.push(Var {
+            name,
+            dims,
+            attrs: Vec::new(),
+            nc_type: NcType::Double,
+            data: Data::Fixed(values),
+            vsize: 0,
+            begin: 0,
+        });
+    }
+
+    /// Add an integer connectivity variable with zero based indices.
+    fn index(&mut self, name: String, dims: Vec<usize>, values: Vec<i32>, cf_role: &str) {
+        let mut attrs = vec![
+            ("cf_role".to_string(), cf_role.into()),
+            ("start_index".to_string(), Attr::Int(0)),
+        ];
+        if values.contains(&-1) {
+            attrs.push(("_FillValue".to_string(), Attr::Int(-1)));
+        }
+        self.vars.push(Var {
+            name,
+            dims,
+            attrs,
+            nc_type: NcType::Int,
+            data: Data::Index(values),
+            vsize: 0,
+            begin: 0,
+        });
+    }
+
+    /// Return the UGRID mesh variable name and location dimensions of unstructured grid
+    /// `grid`, adding the mesh topology on first use.
+    fn mesh<M: Bmi>(&mut self, model: &M, grid: i32, suffix: bool) -> Option<&Mesh> {
+        if !self.meshes.contains_key(&grid) {
+            let mesh = match Grid::from_model(model, grid) {
+                Ok(Grid::Unstructured {
+                    x,
+                    y,
+                    z,
+                    edge_nodes,
+                    face_nodes,
+                    nodes_per_face,
+                    ..
+                }) if !x.is_empty() && x.len() == y.len() => {
+                    let name = |name: &str| spatial::suffixed(name, grid, suffix);
+                    let mut dims = HashMap::new();
+                    let node = self.dim(name("node"), x.len());
+                    dims.insert(Location::Node, node);
+                    let coordinates = match z.len() == x.len() {
+                        true => ["x", "y", "z"].map(name).join(" "),
+                        false => ["x", "y"].map(name).join(" "),
+                    };
+                    for (axis, values) in [("z", z), ("y", y), ("x", x)] {
+                        if values.len() == self.dims[node].len {
+                            self.coord(name(axis), vec![node], values);
+                        }
+                    }
+                    let mesh = name("mesh");
+                    let mut attrs = vec![
+                        ("cf_role".to_string(), "mesh_topology".into()),
+                        ("topology_dimension".to_string(), Attr::Int(2)),
+                        ("node_coordinates".to_string(), coordinates.as_str().into()),
+                        ("node_dimension".to_string(), name("node").as_str().into()),
+                    ];
+                    if !nodes_per_face.is_empty() {
+                        let face = self.dim(name("face"), nodes_per_face.len());
+                        let max = *nodes_per_face.iter().max().unwrap_or(&0) as usize;
+                        let max_nodes = self.dim(name("max_face_nodes"), max);
+                        // pad faces with fewer than the maximum number of nodes with -1
+                        let mut nodes = face_nodes.iter().map(|i| *i as i32);
+                        let padded = nodes_per_face.iter().flat_map(|n| {
+                            let face: Vec<i32> = nodes.by_ref().take(*n as usize).collect();
+                            let padding = max - face.len();
+                            face.into_iter().chain(std::iter::repeat_n(-1, padding))
+                        });
+                        let connectivity = name("face_nodes");
+                        let padded = padded.collect();
+                        self.index(
+                            connectivity.clone(),
+                            vec![face, max_nodes],
+                            padded,
+                            "face_node_connectivity",
+                        );
+                        attrs.push((
+                            "face_node_connectivity".to_string(),
+                            connectivity.as_str().into(),
+                        ));
+                        attrs.push(("face_dimension".to_string(), name("face").as_str().into()));
+                        dims.insert(Location::Face, face);
+                    }
+                    if !edge_nodes.is_empty() {
+                        let edge = self.dim(name("edge"), edge_nodes.len() / 2);
+                        let two = self.dim(name("two"), 2);
+                        let connectivity = name("edge_nodes");
+                        let values = edge_nodes.iter().map(|i| *i as i32).collect();
+                        self.index(
+                            connectivity.clone(),
+                            vec![edge, two],
+                            values,
+                            "edge_node_connectivity",
+                        );
+                        attrs.push((
+                            "edge_node_connectivity".to_string(),
+                            connectivity.as_str().into(),
+                        ));
+                        attrs.push(("edge_dimension".to_string(), name("edge").as_str().into()));
+                        dims.insert(Location::Edge, edge);
+                    }
+                    self.vars.push(Var {
+                        name: mesh.clone(),
+                        dims: Vec::new(),
+                        attrs,
+                        nc_type: NcType::Int,
+                        data: Data::Index(vec![0]),
+                        vsize: 0,
+                        begin: 0,
+                    });
+                    if !self.attrs.iter().any(|(name, _)| name == "Conventions") {
+                        self.attrs
+                            .push(("Conventions".to_string(), "CF-1.8 UGRID-1.0".into()));
+                    }
+                    Some((mesh, dims))
+                }
+                _ => None,
+            };
+            self.meshes.insert(grid, mesh);
+        }
+        self.meshes[&grid].as_ref()
+    }
+
+    /// Return the spatial dimensions of a `len` value variable on `grid` at `location`,
+    /// adding dimensions and coordinate variables on first use.
+    ///
+    /// Variables on unstructured grids use the dimensions of the grid's UGRID mesh, all
+    /// others those of [`spatial::spatial`].
+    fn spatial<M: Bmi>(
+        &mut self,
+        model: &M,
+        grid: i32,
+        location: Location,
+        len: usize,
+        suffix: bool,
+    ) -> Vec<usize> {
+        if let Some(dims) = self.spatial.get(&(grid, location, len)) {
+            return dims.clone();
+        }
+        let spatial = spatial::spatial(model, grid, location, len, suffix);
+        if spatial.grid_type == Some(GridType::Unstructured)
+            && let Some((_, dims)) = self.mesh(model, grid, suffix)
+            && let Some(dim) = dims.get(&location).copied()
+            && self.dims[dim].len == len
+        {
+            self.spatial.insert((grid, location, len), vec![dim]);
+            return vec![dim];
+        }
+        let dims: Vec<usize> = spatial
+            .dims
+            .into_iter()
+            .map(|(name, len)| self.dim(name, len))
+            .collect();
+        let xyz: [GridCoords<M>; 3] = [M::get_grid_z, M::get_grid_y, M::get_grid_x];
+        let rank = dims.len();
+        match spatial.grid_type {
+            Some(grid_type) if spatial.shaped => {
+                let shape: Vec<usize> = dims.iter().map(|dim| self.dims[*dim].len).collect();
+                let axes = &spatial::AXES[3 - rank..];
+                let xyz = &xyz[3 - rank..];
+                let structured = grid_type == GridType::StructuredQuadrilateral;
+                let spacing = model.get_grid_spacing(grid).ok();
+                let origin = model.get_grid_origin(grid).ok();
+                for (i, axis) in axes.iter().enumerate() {
+                    let values = match grid_type {
+                        GridType::UniformRectilinear => match (spacing, origin) {
+                            (Some(spacing), Some(origin))
+                                if spacing.len() == rank && origin.len() == rank =>
+                            {
+                                let values =
+                                    (0..shape[i]).map(|j| origin[i] + j as f64 * spacing[i]);
+                                Some(values.collect())
+                            }
+                            _ => None,
+                        },
+                        GridType::Rectilinear => xyz[i](model, grid)
+                            .ok()
+                            .filter(|v| v.len() == shape[i])
+                            .map(<[f64]>::to_vec),
+                        _ => xyz[i](model, grid)
+                            .ok()
+                            .filter(|v| v.len() == len)
+                            .map(<[f64]>::to_vec),
+                    };
+                    if let Some(values) = values {
+                        let dims = match structured {
+                            true => dims.clone(),
+                            false => vec![dims[i]],
+                        };
+                        self.coord(spatial::suffixed(axis, grid, suffix), dims, values);
+                    }
+                }
+            }
+            _ if location == Location::Node && rank == 1 => {
+                for (xyz, axis) in xyz.iter().zip(spatial::AXES) {
+                    if let Some(values) = xyz(model, grid).ok().filter(|v| v.len() == len) {
+                        let name = spatial::suffixed(axis, grid, suffix);
+                        self.coord(name, dims.clone(), values.to_vec());
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.spatial.insert((grid, location, len), dims.clone());
+        dims
+    }
+
+    /// Return a name used by more than one dimension or variable, or by a variable that is
+    /// not the coordinate variable of the dimension of the same name.
+    fn conflict(&self) -> Option<&str> {
+        let mut dims = HashMap::new();
+        for (i, dim) in self.dims.iter().enumerate() {
+            if dims.insert(dim.name.as_str(), i).is_some() {
+                return Some(&dim.name);
+            }
+        }
+        let mut vars = HashSet::new();
+        for var in self.vars.iter() {
+            if !vars.insert(var.name.as_str()) {
+                return Some(&var.name);
+            }
+            if let Some(dim) = dims.get(var.name.as_str())
+                && var.dims != [*dim]
+            {
+                return Some(&var.name);
+            }
+        }
+        None
+    }
+
+    fn header(&self, format: Format, numrecs: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend(b"CDF");
+        buf.push(match format {
+            Format::Classic => 1,
+            Format::Offset64 => 2,
+        });
+        put_u32(&mut buf, numrecs);
+
+        put_u32(&mut buf, NC_DIMENSION);
+        put_u32(&mut buf, self.dims.len() as u32);
+        for dim in self.dims.iter() {
+            put_name(&mut buf, &dim.name);
+            put_u32(&mut buf, dim.len as u32);
+        }
+
+        put_attrs(&mut buf, &self.attrs);
+
+        put_u32(&mut buf, NC_VARIABLE);
+        put_u32(&mut buf, self.vars.len() as u32);
+        for var in self.vars.iter() {
+            put_name(&mut buf, &var.name);
+            put_u32(&mut buf, var.dims.len() as u32);
+            var.dims
+                .iter()
+                .for_each(|dim| put_u32(&mut buf, *dim as u32));
+            put_attrs(&mut buf, &var.attrs);
+            put_u32(&mut buf, var.nc_type as u32);
+            put_u32(&mut buf, var.vsize as u32);
+            match format {
+                Format::Classic => put_u32(&mut buf, var.begin as u32),
+                Format::Offset64 => buf.extend(var.begin.to_be_bytes()),
+            }
+        }
+        buf
+    }
+}
+
+/// Pure Rust [NetCDF-3](https://docs.unidata.ucar.edu/netcdf-c/current/file_format_specifications.html)
+/// writer that records selected [`Bmi`] variables each time step.
+///
+/// All selected variables share the unlimited `time` dimension. A `time` variable holds
+/// [`get_current_time`] and carries [`get_time_units`] as its `units` attribute.
+/// Spatial dimensions come from [`get_grid_shape`] (or the variable's length), and coordinate
+/// variables from [`get_grid_origin`] and [`get_grid_spacing`] (uniform rectilinear) or
+/// [`get_grid_x`], [`get_grid_y`], and [`get_grid_z`] where the model implements them.
+/// Dimension and coordinate names are suffixed with `_<grid id>` if variables are on more
+/// than one grid.
+///
+/// Unstructured grids are written as
+/// [UGRID](https://ugrid-conventions.github.io/ugrid-conventions/) 2-d mesh topologies: a
+/// `mesh` variable with `node`, `face`, and `edge` dimensions, `x` and `y` node coordinates,
+/// and zero based `face_nodes` (padded with `-1`) and `edge_nodes` connectivity from
+/// [`get_grid_face_nodes`], [`get_grid_nodes_per_face`], and [`get_grid_edge_nodes`].
+/// Variables on the mesh carry `mesh` and `location` attributes.
+///
+/// NetCDF-3 lacks unsigned and 64-bit integer types: `u16` values are written as `int` and
+/// `u32`, `i64`, and `u64` values as `double`.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Bmi;
+/// use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+/// use bmi_rs::netcdf::{Format, NetcdfWriter};
+///
+/// let signal = Signal::Step { before: 0., after: 1., at: 2. };
+/// let mut model = SyntheticForcing::new(0., 4., 1.).variable("precip", "mm", signal);
+/// model.initialize("").unwrap();
+///
+/// let out = std::io::Cursor::new(Vec::new());
+/// let mut writer = NetcdfWriter::new(out, &model, &["precip"], Format::Classic).unwrap();
+/// writer.record(&model).unwrap();
+/// while model.get_current_time() < model.get_end_time() {
+///     model.update().unwrap();
+///     writer.record(&model).unwrap();
+/// }
+/// assert_eq!(writer.records(), 5);
+/// ```
+///
+/// [`get_current_time`]: Bmi::get_current_time
+/// [`get_time_units`]: Bmi::get_time_units
+/// [`get_grid_shape`]: Bmi::get_grid_shape
+/// [`get_grid_origin`]: Bmi::get_grid_origin
+/// [`get_grid_spacing`]: Bmi::get_grid_spacing
+/// [`get_grid_x`]: Bmi::get_grid_x
+/// [`get_grid_y`]: Bmi::get_grid_y
+/// [`get_grid_z`]: Bmi::get_grid_z
+/// [`get_grid_face_nodes`]: Bmi::get_grid_face_nodes
+/// [`get_grid_nodes_per_face`]: Bmi::get_grid_nodes_per_face
+/// [`get_grid_edge_nodes`]: Bmi::get_grid_edge_nodes
+pub struct NetcdfWriter<W: Write + Seek> {
+    out: W,
+    layout: Layout,
+    numrecs: u32,
+}
+
+impl NetcdfWriter<BufWriter<File>> {
+    /// Create a NetCDF file at `path`, see [`NetcdfWriter::new`].
+    pub fn create<M: Bmi>(
+        path: impl AsRef<Path>,
+        model: &M,
+        names: &[&str],
+        format: Format,
+    ) -> BmiResult<Self> {
+        let out = BufWriter::new(File::create(path)?);
+        NetcdfWriter::new(out, model, names, format)
+    }
+}
+
+impl<W: Write + Seek> NetcdfWriter<W> {
+    /// Write the header and coordinate variables for `names` of an initialized `model` to `out`.
+    ///
+    /// Returns Err([`OutputError::Conflict`]) if a variable is selected twice or shares its
+    /// name with a generated dimension or variable, e.g. `time`, `x`, `node`, or `mesh`.
+    pub fn new<M: Bmi>(mut out: W, model: &M, names: &[&str], format: Format) -> BmiResult<Self> {
+        let mut layout = Layout::default();
+        layout
+            .attrs
+            .push(("source".to_string(), model.get_component_name().into()));
+        let time = layout.dim("time".to_string(), 0);
+        let units = model.get_time_units();
+        layout.vars.push(Var {
+            name: "time".to_string(),
+            dims: vec![time],
+            attrs: match units.is_empty() {
+                true => Vec::new(),
+                false => vec![("units".to_string(), units.into())],
+            },
+            nc_type: NcType::Double,
+            data: Data::Time,
+            vsize: 0,
+            begin: 0,
+        });
+
+        let mut grids = Vec::with_capacity(names.len());
+        for name in names {
+            grids.push(model.get_var_grid(name)?);
+        }
+        let suffix = grids.iter().any(|grid| *grid != grids[0]);
+        for (name, grid) in names.iter().zip(grids) {
+            let location = model.get_var_location(name)?;
+            let len = model.get_value_ptr(name)?.len();
+            let mut dims = vec![time];
+            dims.extend(layout.spatial(model, grid, location, len, suffix));
+            let units = model.get_var_units(name)?;
+            let mut attrs = match units.is_empty() {
+                true => Vec::new(),
+                false => vec![("units".to_string(), units.into())],
+            };
+            if let Some(Some((mesh, mesh_dims))) = layout.meshes.get(&grid)
+                && mesh_dims.get(&location) == dims.last()
+            {
+                attrs.push(("mesh".to_string(), mesh.as_str().into()));
+                attrs.push(("location".to_string(), location.to_string().as_str().into()));
+            }
+            layout.vars.push(Var {
+                name: name.to_string(),
+                dims,
+                attrs,
+                nc_type: model.get_var_type(name)?.into(),
+                data: Data::Record { len },
+                vsize: 0,
+                begin: 0,
+            });
+        }
+
+        if let Some(name) = layout.conflict() {
+            return Err(Box::new(OutputError::Conflict(name.to_string())));
+        }
+
+        // NOTE: header length does not depend on sizes or offsets
+        let mut offset = layout.header(format, 0).len() as u64;
+        let limit = match format {
+            Format::Classic => i32::MAX as u64,
+            Format::Offset64 => i64::MAX as u64,
+        };
+        let dims = &layout.dims;
+        for var in layout.vars.iter_mut() {
+            let len: usize = var.dims.iter().map(|dim| dims[*dim].len.max(1)).product();
+            var.vsize = (len * var.nc_type.size()).next_multiple_of(4);
+            if var.vsize > (u32::MAX - 3) as usize {
+                return Err(Box::new(OutputError::TooLarge(var.name.clone())));
+            }
+        }
+        for var in layout.vars.iter_mut().filter(|var| !var.is_record()) {
+            var.begin = offset;
+            offset += var.vsize as u64;
+        }
+        for var in layout.vars.iter_mut().filter(|var| var.is_record()) {
+            var.begin = offset;
+            offset += var.vsize as u64;
+        }
+        if let Some(var) = layout.vars.iter().find(|var| var.begin > limit) {
+            return Err(Box::new(OutputError::TooLarge(var.name.clone())));
+        }
+
+        let mut buf = layout.header(format, 0);
+        for var in layout.vars.iter() {
+            match &var.data {
+                Data::Fixed(values) => put_values(&mut buf, &RefValues::F64(values)),
+                Data::Index(values) => put_values(&mut buf, &RefValues::I32(values)),
+                _ => {}
+            }
+        }
+        out.write_all(&buf)?;
+        Ok(Self {
+            out,
+            layout,
+            numrecs: 0,
+        })
+    }
+
+    /// Append the current time and values of the selected variables of `model` as a record.
+    pub fn record<M: Bmi>(&mut self, model: &M) -> BmiResult<()> {
+        let mut buf = Vec::new();
+        for var in self.layout.vars.iter() {
+            match var.data {
+                Data::Fixed(_) | Data::Index(_) => continue,
+                Data::Time => put_values(&mut buf, &RefValues::F64(&[model.get_current_time()])),
+                Data::Record { len } => {
+                    let values = model.get_value_ptr(&var.name)?;
+                    if values.len() != len {
+                        return Err(Box::new(OutputError::Length {
+                            name: var.name.clone(),
+                            expected: len,
+                            found: values.len(),
+                        }));
+                    }
+                    put_values(&mut buf, &values);
+                }
+            }
+        }
+        self.out.seek(SeekFrom::End(0))?;
+        self.out.write_all(&buf)?;
+        self.numrecs += 1;
+        self.out.seek(SeekFrom::Start(NUMRECS_OFFSET))?;
+        self.out.write_all(&self.numrecs.to_be_bytes())?;
+        self.out.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+
+    /// Number of records written.
+    pub fn records(&self) -> u32 {
+        self.numrecs
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> BmiResult<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::grid::Grid;
+    use crate::testing::Reservoir;
+    use std::io::Cursor;
+
+    fn f64s(bytes: &[u8]) -> Vec<f64> {
+        bytes
+            .chunks(8)
+            .map(|b| f64::from_be_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Classic format header reader.
+    struct Header<'a> {
+        buf: &'a [u8],
+        pos: usize,
+    }
+
+    impl Header<'_> {
+        fn u32(&mut self) -> u32 {
+            let value = u32::from_be_bytes(self.buf[self.pos..self.pos + 4].try_into().unwrap());
+            self.pos += 4;
+            value
+        }
+
+        fn bytes(&mut self, len: usize) -> &[u8] {
+            let bytes = &self.buf[self.pos..self.pos + len];
+            self.pos += len.next_multiple_of(4);
+            bytes
+        }
+
+        fn name(&mut self) -> String {
+            let len = self.u32() as usize;
+            String::from_utf8(self.bytes(len).to_vec()).unwrap()
+        }
+
+        fn skip_attrs(&mut self) {
+            self.u32();
+            for _ in 0..self.u32() {
+                self.name();
+                let size = match self.u32() {
+                    1 | 2 => 1,
+                    3 => 2,
+                    4 | 5 => 4,
+                    _ => 8,
+                };
+                let len = self.u32() as usize;
+                self.bytes(len * size);
+            }
+        }
+    }
+
+    /// Variable name, dimension ids, and offset.
+    type VarHeader = (String, Vec<u32>, usize);
+
+    /// Return the dimensions and variables of a classic format file.
+    fn parse_header(buf: &[u8]) -> (Vec<(String, u32)>, Vec<VarHeader>) {
+        assert_eq!(&buf[..4], b"CDF\x01");
+        let mut header = Header { buf, pos: 8 };
+        assert_eq!(header.u32(), NC_DIMENSION);
+        let dims = (0..header.u32())
+            .map(|_| (header.name(), header.u32()))
+            .collect();
+        header.skip_attrs();
+        assert_eq!(header.u32(), NC_VARIABLE);
+        let vars = (0..header.u32())
+            .map(|_| {
+                let name = header.name();
+                let dims = (0..header.u32()).map(|_| header.u32()).collect();
+                header.skip_attrs();
+                header.u32();
+                header.u32();
+                (name, dims, header.u32() as usize)
+            })
+            .collect();
+        (dims, vars)
+    }
+
+    #[test]
+    fn test_uniform_rectilinear() {
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![10., 20.],
+            origin: vec![0., 5.],
+        };
+        let ramp = Signal::Ramp {
+            value: 0.,
+            start: 0.,
+            slope: 1.,
+        };
+        let mut model = SyntheticForcing::new(0., 10., 2.)
+            .grid(grid)
+            .variable("ramp", "mm", ramp);
+        model.initialize("").unwrap();
+
+        let out = Cursor::new(Vec::new());
+        let mut writer = NetcdfWriter::new(out, &model, &["ramp"], Format::Classic).unwrap();
+        for _ in 0..3 {
+            writer.record(&model).unwrap();
+            model.update().unwrap();
+        }
+        let buf = writer.finish().unwrap().into_inner();
+
+        assert_eq!(&buf[..4], b"CDF\x01");
+        assert_eq!(&buf[4..8], 3u32.to_be_bytes());
+        // coordinates: y (2), x (3); records: time (1), ramp (6)
+        let header = buf.len() - 5 * 8 - 3 * 7 * 8;
+        assert_eq!(f64s(&buf[header..header + 40]), [0., 10., 5., 25., 45.]);
+        let last = f64s(&buf[buf.len() - 7 * 8..]);
+        assert_eq!(last, [4., 4., 4., 4., 4., 4., 4.]);
+        for name in ["time", "y", "x", "ramp", "units", "mm"] {
+            assert!(buf.windows(name.len()).any(|w| w == name.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_ugrid() {
+        // a triangle and a quadrilateral sharing the edge (1, 2)
+        let grid = Grid::Unstructured {
+            x: vec![0., 1., 0., 2., 1.],
+            y: vec![0., 0., 1., 1., 2.],
+            z: vec![],
+            edge_nodes: vec![],
+            face_edges: vec![],
+            face_nodes: vec![0, 1, 2, 1, 3, 4, 2],
+            nodes_per_face: vec![3, 4],
+        };
+        let mut model = SyntheticForcing::new(0., 1., 1.).grid(grid).variable(
+            "depth",
+            "m",
+            Signal::Constant(1.),
+        );
+        model.initialize("").unwrap();
+
+        let out = Cursor::new(Vec::new());
+        let mut writer = NetcdfWriter::new(out, &model, &["depth"], Format::Classic).unwrap();
+        writer.record(&model).unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+        let found = |s: &str| buf.windows(s.len()).any(|w| w == s.as_bytes());
+        for name in [
+            "UGRID-1.0",
+            "mesh_topology",
+            "face_node_connectivity",
+            "max_face_nodes",
+            "location",
+            "node",
+        ] {
+            assert!(found(name), "{name}");
+        }
+
+        let (dims, vars) = parse_header(&buf);
+        let dims: Vec<(&str, u32)> = dims
+            .iter()
+            .map(|(name, len)| (name.as_str(), *len))
+            .collect();
+        assert_eq!(
+            dims,
+            [("time", 0), ("node", 5), ("face", 2), ("max_face_nodes", 4)]
+        );
+        let names: Vec<&str> = vars.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(names, ["time", "y", "x", "face_nodes", "mesh", "depth"]);
+        let var = |name: &str| vars.iter().find(|var| var.0 == name).unwrap();
+        assert_eq!(var("depth").1, [0, 1]);
+        assert_eq!(var("face_nodes").1, [2, 3]);
+        let begin = var("face_nodes").2;
+        let face_nodes: Vec<i32> = buf[begin..begin + 32]
+            .chunks(4)
+            .map(|b| i32::from_be_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(face_nodes, [0, 1, 2, -1, 1, 3, 4, 2]);
+        let begin = var("y").2;
+        assert_eq!(f64s(&buf[begin..begin + 40]), [0., 0., 1., 1., 2.]);
+        let begin = var("depth").2;
+        assert_eq!(f64s(&buf[begin..]), [1.; 5]);
+    }
+
+    #[test]
+    fn test_conflict() {
+        for name in ["time", "x", "node", "mesh"] {
+            let grid = Grid::Unstructured {
+                x: vec![0., 1., 0.],
+                y: vec![0., 0., 1.],
+                z: vec![],
+                edge_nodes: vec![],
+                face_edges: vec![],
+                face_nodes: vec![0, 1, 2],
+                nodes_per_face: vec![3],
+            };
+            let mut model = SyntheticForcing::new(0., 1., 1.).grid(grid).variable(
+                name,
+                "m",
+                Signal::Constant(1.),
+            );
+            model.initialize("").unwrap();
+            let out = Cursor::new(Vec::new());
+            let err = NetcdfWriter::new(out, &model, &[name], Format::Classic)
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.downcast_ref::<OutputError>(),
+                Some(&OutputError::Conflict(name.to_string()))
+            );
+        }
+        let mut model = Reservoir::new(1.);
+        model.initialize("").unwrap();
+        let out = Cursor::new(Vec::new());
+        let names = ["flux", "flux"];
+        assert!(NetcdfWriter::new(out, &model, &names, Format::Classic).is_err());
+    }
+
+    #[test]
+    fn test_scalar_offset64() {
+        let mut model = Reservoir::new(1.);
+        model.initialize("").unwrap();
+        model.rate = vec![2.];
+        let out = Cursor::new(Vec::new());
+        let names = ["storage", "flux"];
+        let mut writer = NetcdfWriter::new(out, &model, &names, Format::Offset64).unwrap();
+        model.update().unwrap();
+        writer.record(&model).unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+
+        assert_eq!(&buf[..4], b"CDF\x02");
+        assert_eq!(&buf[4..8], 1u32.to_be_bytes());
+        assert_eq!(f64s(&buf[buf.len() - 24..]), [1., 2., 2.]);
+    }
+
+    #[test]
+    fn test_length_changed() {
+        let mut model = Reservoir::new(1.);
+        model.initialize("").unwrap();
+        let out = Cursor::new(Vec::new());
+        let mut writer = NetcdfWriter::new(out, &model, &["flux"], Format::Classic).unwrap();
+        model.flux = vec![0.; 2];
+        let err = writer.record(&model).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<OutputError>(),
+            Some(OutputError::Length {
+                expected: 1,
+                found: 2,
+                ..
+            })
+        ));
+    }
+}
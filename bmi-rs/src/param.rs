@@ -0,0 +1,553 @@
+use crate::bmi::forward_grid_funcs;
+use crate::errors::BmiInvalidValue;
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values};
+use std::error::Error;
+use std::fmt;
+
+/// Prefix of the variables exposing a [`Parameterized`] model's parameters over ffi.
+pub const PREFIX: &str = "param.";
+/// Variable holding the number of parameters.
+pub const COUNT_VAR: &str = "param_count";
+/// Prefix of the variables whose units are the parameter names, by index.
+pub const NAME_PREFIX: &str = "param_name.";
+
+/// Parameter lookup and validation errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// The model has no parameter with the name.
+    Unknown(String),
+    /// A value is not finite or is outside the parameter's bounds.
+    OutOfBounds {
+        name: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Unknown(name) => write!(f, "unknown parameter `{name}`"),
+            ParamError::OutOfBounds {
+                name,
+                value,
+                min,
+                max,
+            } => write!(f, "`{name}` value {value} outside of [{min}, {max}]"),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+/// Metadata of a calibration parameter.
+///
+/// Example:
+/// ```
+/// use bmi_rs::ValueType;
+/// use bmi_rs::param::Parameter;
+///
+/// let k = Parameter::new("k", "d-1", 0.01, 1.).default(0.1);
+/// assert_eq!(k.value_type(), ValueType::F64);
+/// assert!(k.check(&[0.5]).is_ok());
+/// assert!(k.check(&[2.]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    name: String,
+    units: String,
+    value_type: ValueType,
+    // NOTE: stored as slices so they can be returned as `RefValues` by `Parameterized`
+    bounds: [f64; 2],
+    default: [f64; 1],
+}
+
+impl Parameter {
+    /// Create an `f64` parameter bounded by `[min, max]`, defaulting to `min`.
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn new(name: impl Into<String>, units: impl Into<String>, min: f64, max: f64) -> Self {
+        assert!(min <= max, "parameter bounds must be ordered");
+        Self {
+            name: name.into(),
+            units: units.into(),
+            value_type: ValueType::F64,
+            bounds: [min, max],
+            default: [min],
+        }
+    }
+
+    /// Set the parameter's default value.
+    ///
+    /// Panics if `value` is not within `[min, max]`.
+    pub fn default(mut self, value: f64) -> Self {
+        assert!(
+            (self.min()..=self.max()).contains(&value),
+            "parameter default must be within its bounds"
+        );
+        self.default = [value];
+        self
+    }
+
+    /// Set the parameter's value type.
+    pub fn of_type(mut self, value_type: ValueType) -> Self {
+        self.value_type = value_type;
+        self
+    }
+
+    /// Return the parameter's name, the model variable it sets.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the parameter's units.
+    pub fn units(&self) -> &str {
+        &self.units
+    }
+
+    /// Return the value type of the parameter's variable (default `f64`).
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Return the lower bound.
+    pub fn min(&self) -> f64 {
+        self.bounds[0]
+    }
+
+    /// Return the upper bound.
+    pub fn max(&self) -> f64 {
+        self.bounds[1]
+    }
+
+    /// Return the default value (default, the lower bound).
+    pub fn default_value(&self) -> f64 {
+        self.default[0]
+    }
+
+    /// Return Err([`ParamError::OutOfBounds`]) if any of `values` is not finite or is outside
+    /// of the parameter's bounds.
+    pub fn check(&self, values: &[f64]) -> Result<(), ParamError> {
+        match values
+            .iter()
+            .find(|v| !(v.is_finite() && (self.min()..=self.max()).contains(*v)))
+        {
+            None => Ok(()),
+            Some(value) => Err(ParamError::OutOfBounds {
+                name: self.name.clone(),
+                value: *value,
+                min: self.min(),
+                max: self.max(),
+            }),
+        }
+    }
+}
+
+/// Calibration parameters of a [`Bmi`] model.
+///
+/// Per [`get_input_item_count`], parameters are not listed as input variables. Instead, they
+/// are listed by [`parameters`] and set, by name, through the model's [`set_value`].
+/// Wrap a model in [`Parameterized`] to validate [`set_value`] calls against the parameters'
+/// bounds and to expose the parameters to C hosts.
+///
+/// [`get_input_item_count`]: Bmi::get_input_item_count
+/// [`parameters`]: BmiParameters::parameters
+/// [`set_value`]: Bmi::set_value
+pub trait BmiParameters: Bmi {
+    /// Return the model's parameters.
+    fn parameters(&self) -> &[Parameter];
+
+    /// Return the parameter `name`, if present.
+    fn parameter(&self, name: &str) -> Option<&Parameter> {
+        self.parameters().iter().find(|p| p.name() == name)
+    }
+
+    /// Return the current value of parameter `name`.
+    fn get_parameter(&self, name: &str) -> BmiResult<f64> {
+        if self.parameter(name).is_none() {
+            return Err(Box::new(ParamError::Unknown(name.to_string())));
+        }
+        match self.get_value_ptr(name)?.to_f64_vec().first() {
+            Some(value) => Ok(*value),
+            None => BmiInvalidValue.into(),
+        }
+    }
+
+    /// Check `value` against parameter `name`'s bounds and set it.
+    fn set_parameter(&mut self, name: &str, value: f64) -> BmiResult<()> {
+        let Some(param) = self.parameter(name) else {
+            return Err(Box::new(ParamError::Unknown(name.to_string())));
+        };
+        param.check(&[value])?;
+        let value_type = param.value_type();
+        let len = var_len(self, name)?;
+        let values = Values::from_f64_slice(value_type, &vec![value; len]);
+        self.set_value(name, (&values).into())
+    }
+
+    /// Set every parameter to its default value.
+    fn reset_parameters(&mut self) -> BmiResult<()> {
+        let defaults: Vec<(String, f64)> = self
+            .parameters()
+            .iter()
+            .map(|p| (p.name().to_string(), p.default_value()))
+            .collect();
+        for (name, value) in defaults {
+            self.set_parameter(&name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the number of elements of variable `name`, from its size in bytes, so models that
+/// only implement `get_value` can be parameterized.
+fn var_len<M: Bmi + ?Sized>(model: &M, name: &str) -> BmiResult<usize> {
+    match model.get_var_itemsize(name)? {
+        0 => BmiInvalidValue.into(),
+        itemsize => Ok((model.get_var_nbytes(name)? / itemsize) as usize),
+    }
+}
+
+/// Set each of `params` to its value in `values`, for every element of the parameter's
+/// variable, without checking bounds.
+pub(crate) fn set_values<M: Bmi>(
+    model: &mut M,
+    params: &[Parameter],
+    values: &[f64],
+) -> BmiResult<()> {
+    for (param, value) in params.iter().zip(values) {
+        let len = var_len(model, param.name())?;
+        let values = Values::from_f64_slice(param.value_type(), &vec![*value; len]);
+        model.set_value(param.name(), (&values).into())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParamVar {
+    Count,
+    Name(usize),
+    Value(usize),
+    Min(usize),
+    Max(usize),
+    Default(usize),
+}
+
+/// [`Bmi`] adapter that validates [`set_value`] calls against a model's [`BmiParameters`]
+/// bounds and exposes its parameters to C hosts using the following variables:
+///
+/// - `param_count`: `i32` number of parameters.
+/// - `param_name.<i>`: `i32` index `i`, its units are the name of the `i`th parameter.
+///   BMI has no string variables, so [`get_var_units`] is used to list names.
+/// - `param.<name>`: the parameter's value, of its [`ValueType`] and units.
+///   Setting it is equivalent to setting `<name>`.
+/// - `param.<name>.min`, `param.<name>.max`, `param.<name>.default`: `f64` read only bounds and
+///   default value.
+///
+/// Setting a parameter, with or without the `param.` prefix, out of its bounds returns
+/// Err([`ParamError::OutOfBounds`]) and leaves the model unchanged.
+/// None of these variables are listed as input or output variables.
+///
+/// [`set_value`]: Bmi::set_value
+/// [`get_var_units`]: Bmi::get_var_units
+pub struct Parameterized<M: BmiParameters> {
+    model: M,
+    count: [i32; 1],
+    indices: Vec<i32>,
+}
+
+impl<M: BmiParameters> Parameterized<M> {
+    /// Wrap `model`, adding the `param*` variables for its parameters.
+    pub fn new(model: M) -> Self {
+        let mut parameterized = Self {
+            model,
+            count: [0],
+            indices: Vec::new(),
+        };
+        parameterized.count_parameters();
+        parameterized
+    }
+
+    /// Update `param_count` and the `param_name.<i>` indices from the model's parameters,
+    /// which may be filled in by [`initialize`].
+    ///
+    /// [`initialize`]: Bmi::initialize
+    fn count_parameters(&mut self) {
+        let count = self.model.parameters().len();
+        self.count = [count as i32];
+        self.indices = (0..count as i32).collect();
+    }
+
+    /// Return the wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    /// Unwrap the model.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.model
+            .parameters()
+            .iter()
+            .position(|p| p.name() == name)
+    }
+
+    fn resolve(&self, name: &str) -> Option<ParamVar> {
+        if name == COUNT_VAR {
+            return Some(ParamVar::Count);
+        }
+        if let Some(i) = name.strip_prefix(NAME_PREFIX) {
+            return match i.parse::<usize>() {
+                Ok(i) if i < self.indices.len() => Some(ParamVar::Name(i)),
+                _ => None,
+            };
+        }
+        let name = name.strip_prefix(PREFIX)?;
+        if let Some(i) = self.position(name) {
+            return Some(ParamVar::Value(i));
+        }
+        let (name, suffix) = name.rsplit_once('.')?;
+        let i = self.position(name)?;
+        match suffix {
+            "min" => Some(ParamVar::Min(i)),
+            "max" => Some(ParamVar::Max(i)),
+            "default" => Some(ParamVar::Default(i)),
+            _ => None,
+        }
+    }
+
+    fn param(&self, i: usize) -> &Parameter {
+        &self.model.parameters()[i]
+    }
+}
+
+impl<M: BmiParameters> Bmi for Parameterized<M> {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        let result = self.model.initialize(config_file);
+        self.count_parameters();
+        result
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        self.model.update()
+    }
+
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        self.model.update_until(then)
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        self.model.finalize()
+    }
+
+    fn get_component_name(&self) -> &str {
+        self.model.get_component_name()
+    }
+
+    fn get_input_item_count(&self) -> u32 {
+        self.model.get_input_item_count()
+    }
+
+    fn get_output_item_count(&self) -> u32 {
+        self.model.get_output_item_count()
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        self.model.get_input_var_names()
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.model.get_output_var_names()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        match self.resolve(name) {
+            Some(ParamVar::Value(i)) => self.model.get_var_grid(self.param(i).name()),
+            Some(_) => BmiInvalidValue.into(),
+            None => self.model.get_var_grid(name),
+        }
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        match self.resolve(name) {
+            Some(ParamVar::Count | ParamVar::Name(_)) => Ok(ValueType::I32),
+            Some(ParamVar::Value(i)) => Ok(self.param(i).value_type()),
+            Some(_) => Ok(ValueType::F64),
+            None => self.model.get_var_type(name),
+        }
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        match self.resolve(name) {
+            Some(ParamVar::Count) => Ok(""),
+            Some(ParamVar::Name(i)) => Ok(self.param(i).name()),
+            Some(
+                ParamVar::Value(i) | ParamVar::Min(i) | ParamVar::Max(i) | ParamVar::Default(i),
+            ) => Ok(self.param(i).units()),
+            None => self.model.get_var_units(name),
+        }
+    }
+
+    fn get_var_itemsize(&self, name: &str) -> BmiResult<u32> {
+        match self.resolve(name) {
+            Some(_) => Ok(self.get_var_type(name)?.bytes() as u32),
+            None => self.model.get_var_itemsize(name),
+        }
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        match self.resolve(name) {
+            Some(ParamVar::Value(i)) => self.model.get_var_nbytes(self.param(i).name()),
+            Some(_) => self.get_var_itemsize(name),
+            None => self.model.get_var_nbytes(name),
+        }
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        match self.resolve(name) {
+            Some(ParamVar::Value(i)) => self.model.get_var_location(self.param(i).name()),
+            Some(_) => Ok(Location::Node),
+            None => self.model.get_var_location(name),
+        }
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.model.get_current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.model.get_start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.model.get_end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        self.model.get_time_units()
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.model.get_time_step()
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        match self.resolve(name) {
+            Some(ParamVar::Count) => Ok(RefValues::I32(&self.count)),
+            Some(ParamVar::Name(i)) => Ok(RefValues::I32(&self.indices[i..i + 1])),
+            Some(ParamVar::Value(i)) => self.model.get_value_ptr(self.param(i).name()),
+            Some(ParamVar::Min(i)) => Ok(RefValues::F64(&self.param(i).bounds[..1])),
+            Some(ParamVar::Max(i)) => Ok(RefValues::F64(&self.param(i).bounds[1..])),
+            Some(ParamVar::Default(i)) => Ok(RefValues::F64(&self.param(i).default)),
+            None => self.model.get_value_ptr(name),
+        }
+    }
+
+    fn get_value_at_indices(&self, name: &str, inds: &[u32]) -> BmiResult<Values> {
+        match self.resolve(name) {
+            Some(ParamVar::Value(i)) => self.model.get_value_at_indices(self.param(i).name(), inds),
+            Some(_) => BmiInvalidValue.into(),
+            None => self.model.get_value_at_indices(name, inds),
+        }
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        let i = match self.resolve(name) {
+            Some(ParamVar::Value(i)) => i,
+            Some(_) => return BmiInvalidValue.into(),
+            None => match self.position(name) {
+                Some(i) => i,
+                None => return self.model.set_value(name, src),
+            },
+        };
+        let param = self.param(i);
+        param.check(&src.to_f64_vec())?;
+        let name = param.name().to_string();
+        self.model.set_value(&name, src)
+    }
+
+    fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()> {
+        let i = match self.resolve(name) {
+            Some(ParamVar::Value(i)) => i,
+            Some(_) => return BmiInvalidValue.into(),
+            None => match self.position(name) {
+                Some(i) => i,
+                None => return self.model.set_value_at_indices(name, inds, src),
+            },
+        };
+        let param = self.param(i);
+        param.check(&src.to_f64_vec())?;
+        let name = param.name().to_string();
+        self.model.set_value_at_indices(&name, inds, src)
+    }
+
+    forward_grid_funcs!(model);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+    use std::sync::LazyLock;
+
+    static PARAMETERS: LazyLock<[Parameter; 1]> =
+        LazyLock::new(|| [Parameter::new("rate", "m s-1", 0., 2.).default(0.5)]);
+
+    impl BmiParameters for Reservoir {
+        fn parameters(&self) -> &[Parameter] {
+            PARAMETERS.as_slice()
+        }
+    }
+
+    #[test]
+    fn test_set_parameter() {
+        let mut model = Reservoir::new(1.);
+        model.reset_parameters().unwrap();
+        assert_eq!(model.get_parameter("rate").unwrap(), 0.5);
+        model.set_parameter("rate", 2.).unwrap();
+        assert_eq!(model.rate, [2.]);
+        let err = model.set_parameter("rate", 3.).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamError>(),
+            Some(ParamError::OutOfBounds { value: 3., .. })
+        ));
+        assert!(model.set_parameter("storage", 1.).is_err());
+        assert!(model.set_parameter("rate", f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_parameterized_vars() {
+        let mut model = Parameterized::new(Reservoir::new(1.));
+        let f64_value = |model: &Parameterized<Reservoir>, name| {
+            model.get_value_ptr(name).unwrap().to_f64_vec()[0]
+        };
+        assert_eq!(f64_value(&model, COUNT_VAR), 1.);
+        assert_eq!(model.get_var_units("param_name.0").unwrap(), "rate");
+        assert!(model.get_var_units("param_name.1").is_err());
+        assert_eq!(model.get_var_units("param.rate").unwrap(), "m s-1");
+        assert_eq!(f64_value(&model, "param.rate.min"), 0.);
+        assert_eq!(f64_value(&model, "param.rate.max"), 2.);
+        assert_eq!(f64_value(&model, "param.rate.default"), 0.5);
+        assert_eq!(model.get_var_nbytes("param.rate").unwrap(), 8);
+        assert!(model.get_var_units("param.storage").is_err());
+
+        model
+            .set_value("param.rate", RefValues::F64(&[1.5]))
+            .unwrap();
+        assert_eq!(f64_value(&model, "rate"), 1.5);
+        assert!(model.set_value("rate", RefValues::F64(&[-1.])).is_err());
+        assert!(
+            model
+                .set_value("param.rate.max", RefValues::F64(&[1.]))
+                .is_err()
+        );
+        assert_eq!(f64_value(&model, "param.rate"), 1.5);
+        // non parameters are forwarded unchecked
+        model.set_value("storage", RefValues::F64(&[-1.])).unwrap();
+        assert_eq!(f64_value(&model, "storage"), -1.);
+    }
+}
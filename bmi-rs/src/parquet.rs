@@ -0,0 +1,259 @@
+use crate::bmi::{Bmi, BmiResult, RefValues, ValueType, Values};
+use arrow_array::{ArrayRef, FixedSizeListArray, Float64Array, RecordBatch};
+use arrow_schema::{DataType, Field, FieldRef, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A variable's type or length changed since the [`ParquetWriter`] was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableChanged(pub String);
+
+impl fmt::Display for VariableChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` type or length changed", self.0)
+    }
+}
+
+impl Error for VariableChanged {}
+
+macro_rules! extend_values {
+    ($dest:expr, $src:expr; $($name:ident),*$(,)?) => {
+        match ($dest, $src) {
+            $((Values::$name(dest), RefValues::$name(src)) => dest.extend_from_slice(src),)*
+            _ => unreachable!("value types are checked before extending"),
+        }
+    };
+}
+
+#[derive(Debug)]
+struct Column {
+    name: String,
+    value_type: ValueType,
+    len: usize,
+    /// `FixedSizeList` item field if `len` is not 1
+    item: Option<FieldRef>,
+    /// buffered values, flattened
+    values: Values,
+}
+
+/// Writes selected [`Bmi`] variables to a [Parquet](https://parquet.apache.org) file as a time
+/// series, one row per time step.
+///
+/// The first column, `time`, holds [`get_current_time`]. Each variable is a column of its
+/// [`ValueType`], or a `FixedSizeList` of it if the variable has more than one value.
+/// Column metadata holds the variable's name (`variable`), `units`, `grid` id, and `location`.
+/// The `time` column's `units` metadata is [`get_time_units`].
+///
+/// Rows are buffered and written as one row group per [`rows_per_group`] time steps
+/// (default 1).
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::parquet::ParquetWriter;
+/// # fn run(mut model: impl Bmi) -> bmi_rs::BmiResult<()> {
+///
+/// let mut writer = ParquetWriter::create("out.parquet", &model, &["discharge"])?.rows_per_group(24);
+/// while model.get_current_time() < model.get_end_time() {
+///     model.update()?;
+///     writer.record(&model)?;
+/// }
+/// writer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`get_current_time`]: Bmi::get_current_time
+/// [`get_time_units`]: Bmi::get_time_units
+/// [`rows_per_group`]: ParquetWriter::rows_per_group
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    rows_per_group: usize,
+    times: Vec<f64>,
+    columns: Vec<Column>,
+}
+
+impl ParquetWriter<File> {
+    /// Create a Parquet file at `path`, see [`ParquetWriter::new`].
+    pub fn create<M: Bmi>(path: impl AsRef<Path>, model: &M, names: &[&str]) -> BmiResult<Self> {
+        ParquetWriter::new(File::create(path)?, model, names)
+    }
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Create a writer for `names` of an initialized `model`.
+    pub fn new<M: Bmi>(out: W, model: &M, names: &[&str]) -> BmiResult<Self> {
+        let mut fields =
+            vec![
+                Field::new("time", DataType::Float64, false).with_metadata(HashMap::from([(
+                    "units".to_string(),
+                    model.get_time_units().to_string(),
+                )])),
+            ];
+        let mut columns = Vec::with_capacity(names.len());
+        for name in names {
+            let value_type = model.get_var_type(name)?;
+            let len = model.get_value_ptr(name)?.len();
+            let item = match len {
+                1 => None,
+                _ => Some(Arc::new(Field::new("item", value_type.into(), false))),
+            };
+            let data_type = match &item {
+                None => value_type.into(),
+                Some(item) => DataType::FixedSizeList(item.clone(), len as i32),
+            };
+            let metadata = HashMap::from([
+                ("variable".to_string(), name.to_string()),
+                ("units".to_string(), model.get_var_units(name)?.to_string()),
+                ("grid".to_string(), model.get_var_grid(name)?.to_string()),
+                (
+                    "location".to_string(),
+                    model.get_var_location(name)?.to_string(),
+                ),
+            ]);
+            fields.push(Field::new(*name, data_type, false).with_metadata(metadata));
+            columns.push(Column {
+                name: name.to_string(),
+                value_type,
+                len,
+                item,
+                values: Values::from_f64_slice(value_type, &[]),
+            });
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let writer = ArrowWriter::try_new(out, schema.clone(), None)?;
+        Ok(Self {
+            writer,
+            schema,
+            rows_per_group: 1,
+            times: Vec::new(),
+            columns,
+        })
+    }
+
+    /// Write a row group every `rows` time steps.
+    pub fn rows_per_group(mut self, rows: usize) -> Self {
+        assert!(rows > 0, "rows per group must be positive");
+        self.rows_per_group = rows;
+        self
+    }
+
+    /// Buffer the current time and values of the selected variables of `model` as a row,
+    /// writing a row group when [`rows_per_group`] rows are buffered.
+    ///
+    /// [`rows_per_group`]: ParquetWriter::rows_per_group
+    pub fn record<M: Bmi>(&mut self, model: &M) -> BmiResult<()> {
+        let mut rows = Vec::with_capacity(self.columns.len());
+        for column in self.columns.iter() {
+            let values = model.get_value_ptr(&column.name)?;
+            if values.value_type() != column.value_type || values.len() != column.len {
+                return Err(Box::new(VariableChanged(column.name.clone())));
+            }
+            rows.push(values);
+        }
+        for (column, values) in self.columns.iter_mut().zip(rows) {
+            extend_values!(&mut column.values, values; I16, U16, I32, U32, I64, U64, F32, F64);
+        }
+        self.times.push(model.get_current_time());
+        if self.times.len() >= self.rows_per_group {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered rows, if any, as a row group.
+    pub fn flush(&mut self) -> BmiResult<()> {
+        if self.times.is_empty() {
+            return Ok(());
+        }
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(Float64Array::from(std::mem::take(
+            &mut self.times,
+        )))];
+        for column in self.columns.iter_mut() {
+            let empty = Values::from_f64_slice(column.value_type, &[]);
+            let values = std::mem::replace(&mut column.values, empty).into_arrow();
+            arrays.push(match &column.item {
+                None => values,
+                Some(item) => Arc::new(FixedSizeListArray::try_new(
+                    item.clone(),
+                    column.len as i32,
+                    values,
+                    None,
+                )?),
+            });
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Write buffered rows and the file footer and return the underlying writer.
+    pub fn finish(mut self) -> BmiResult<W> {
+        self.flush()?;
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::grid::Grid;
+    use crate::testing::tempdir;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_row_groups() {
+        let ramp = Signal::Ramp {
+            value: 0.,
+            start: 0.,
+            slope: 1.,
+        };
+        let mut model = SyntheticForcing::new(0., 10., 1.)
+            .time_units("s")
+            .grid(Grid::Points {
+                x: vec![0., 1.],
+                y: vec![0., 1.],
+                z: vec![],
+            })
+            .variable("ramp", "mm", ramp);
+        model.initialize("").unwrap();
+
+        let dir = tempdir("parquet");
+        let path = dir.join("ramp.parquet");
+        let mut writer = ParquetWriter::create(&path, &model, &["ramp"])
+            .unwrap()
+            .rows_per_group(2);
+        for _ in 0..5 {
+            writer.record(&model).unwrap();
+            model.update().unwrap();
+        }
+        writer.finish().unwrap();
+
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 3);
+        let field = builder.schema().field_with_name("ramp").unwrap().clone();
+        assert_eq!(field.metadata()["units"], "mm");
+        assert_eq!(field.metadata()["grid"], "0");
+        assert_eq!(field.metadata()["location"], "node");
+        let batches: Vec<RecordBatch> = builder.build().unwrap().map(Result::unwrap).collect();
+        let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 5);
+
+        let last = batches.last().unwrap();
+        let ramp = last.column_by_name("ramp").unwrap();
+        let ramp = ramp.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+        let values = Values::try_from_arrow(ramp.value(ramp.len() - 1).as_ref()).unwrap();
+        assert_eq!(values.to_f64_vec(), [4., 4.]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
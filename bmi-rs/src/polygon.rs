@@ -0,0 +1,215 @@
+/// A planar `[x, y]` point. Polygons are vertex lists, the last vertex connected to the first.
+pub(crate) type Point = [f64; 2];
+
+/// Return the z component of `(b - a) x (p - a)`, positive if `p` is left of `a -> b`.
+fn cross(a: Point, b: Point, p: Point) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Return the signed (shoelace) area, positive for counterclockwise polygons.
+pub(crate) fn signed_area(poly: &[Point]) -> f64 {
+    let n = poly.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+pub(crate) fn area(poly: &[Point]) -> f64 {
+    signed_area(poly).abs()
+}
+
+/// Return the area centroid, or the mean of the vertices of a polygon without area.
+pub(crate) fn centroid(poly: &[Point]) -> Point {
+    let (n, area) = (poly.len(), signed_area(poly));
+    if area == 0. {
+        let [x, y] = poly.iter().fold([0., 0.], |[x, y], p| [x + p[0], y + p[1]]);
+        return [x / n as f64, y / n as f64];
+    }
+    let [x, y] = (0..n).fold([0., 0.], |[x, y], i| {
+        let (a, b) = (poly[i], poly[(i + 1) % n]);
+        let cross = a[0] * b[1] - b[0] * a[1];
+        [x + (a[0] + b[0]) * cross, y + (a[1] + b[1]) * cross]
+    });
+    [x / (6. * area), y / (6. * area)]
+}
+
+/// Return `true` if `p` is inside `poly`, by the even-odd rule.
+pub(crate) fn contains(poly: &[Point], [x, y]: Point) -> bool {
+    let n = poly.len();
+    (0..n).fold(false, |inside, i| {
+        let (a, b) = (poly[i], poly[(i + 1) % n]);
+        let crosses =
+            (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]);
+        inside != crosses
+    })
+}
+
+/// Return `[x min, y min, x max, y max]`.
+pub(crate) fn bbox(poly: &[Point]) -> [f64; 4] {
+    poly.iter().fold(
+        [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ],
+        |[x0, y0, x1, y1], [x, y]| [x0.min(*x), y0.min(*y), x1.max(*x), y1.max(*y)],
+    )
+}
+
+/// Return `poly` in counterclockwise order.
+pub(crate) fn ccw(poly: &[Point]) -> Vec<Point> {
+    let mut poly = poly.to_vec();
+    if signed_area(&poly) < 0. {
+        poly.reverse();
+    }
+    poly
+}
+
+/// Return `true` if counterclockwise `poly` has no reflex vertices.
+fn is_convex(poly: &[Point]) -> bool {
+    let n = poly.len();
+    (0..n).all(|i| cross(poly[i], poly[(i + 1) % n], poly[(i + 2) % n]) >= 0.)
+}
+
+/// Clip `subject` by the convex counterclockwise polygon `clip` (Sutherland-Hodgman).
+///
+/// `subject` may be concave, in which case the result may contain zero area bridges; its
+/// area is still the area of the intersection.
+pub(crate) fn clip(subject: &[Point], clip: &[Point]) -> Vec<Point> {
+    let mut output = subject.to_vec();
+    for (i, a) in clip.iter().enumerate() {
+        let b = clip[(i + 1) % clip.len()];
+        let input = std::mem::take(&mut output);
+        let Some(mut prev) = input.last().copied() else {
+            break;
+        };
+        for p in input {
+            let (side_prev, side_p) = (cross(*a, b, prev), cross(*a, b, p));
+            if (side_prev >= 0.) != (side_p >= 0.) {
+                let t = side_prev / (side_prev - side_p);
+                output.push([
+                    prev[0] + t * (p[0] - prev[0]),
+                    prev[1] + t * (p[1] - prev[1]),
+                ]);
+            }
+            if side_p >= 0. {
+                output.push(p);
+            }
+            prev = p;
+        }
+    }
+    output
+}
+
+/// Triangulate the simple counterclockwise polygon `poly` by ear clipping.
+fn triangulate(poly: &[Point]) -> Vec<Vec<Point>> {
+    let in_triangle = |p: Point, [a, b, c]: [Point; 3]| {
+        cross(a, b, p) >= 0. && cross(b, c, p) >= 0. && cross(c, a, p) >= 0.
+    };
+    let mut remaining: Vec<usize> = (0..poly.len()).collect();
+    let mut triangles = Vec::with_capacity(poly.len().saturating_sub(2));
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let corners = |i: usize| {
+            [
+                poly[remaining[(i + m - 1) % m]],
+                poly[remaining[i]],
+                poly[remaining[(i + 1) % m]],
+            ]
+        };
+        let ear = (0..m).find(|i| {
+            let t = corners(*i);
+            cross(t[0], t[1], t[2]) > 0.
+                && !remaining
+                    .iter()
+                    .map(|k| poly[*k])
+                    .any(|p| !t.contains(&p) && in_triangle(p, t))
+        });
+        // only degenerate (e.g. collinear) vertices are left
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push(corners(i).to_vec());
+        remaining.remove(i);
+    }
+    let fan = remaining.windows(2).skip(1);
+    triangles.extend(fan.map(|w| vec![poly[remaining[0]], poly[w[0]], poly[w[1]]]));
+    triangles
+}
+
+/// Split `poly` into convex counterclockwise parts, itself if convex, else triangles.
+pub(crate) fn convex_parts(poly: &[Point]) -> Vec<Vec<Point>> {
+    let poly = ccw(poly);
+    match is_convex(&poly) {
+        true => vec![poly],
+        false => triangulate(&poly),
+    }
+}
+
+/// Return the area of the intersection of `subject` with the union of the `convex` parts of a
+/// polygon, see [`convex_parts`].
+pub(crate) fn intersection_area(subject: &[Point], convex: &[Vec<Point>]) -> f64 {
+    convex.iter().map(|part| area(&clip(subject, part))).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // counterclockwise L, the unit square without its upper right quarter
+    const L: [Point; 6] = [
+        [0., 0.],
+        [1., 0.],
+        [1., 0.5],
+        [0.5, 0.5],
+        [0.5, 1.],
+        [0., 1.],
+    ];
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point> {
+        vec![[x, y], [x + size, y], [x + size, y + size], [x, y + size]]
+    }
+
+    #[test]
+    fn test_area() {
+        assert_eq!(signed_area(&square(1., 1., 2.)), 4.);
+        assert_eq!(signed_area(&ccw(&[[0., 0.], [0., 1.], [1., 0.]])), 0.5);
+        assert_eq!(area(&L), 0.75);
+        assert_eq!(bbox(&L), [0., 0., 1., 1.]);
+        assert_eq!(centroid(&square(1., 1., 2.)), [2., 2.]);
+        let [x, y] = centroid(&L);
+        assert!((x - 5. / 12.).abs() < 1e-12 && (y - 5. / 12.).abs() < 1e-12);
+        assert_eq!(centroid(&[[0., 0.], [2., 0.]]), [1., 0.]);
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(contains(&L, [0.25, 0.75]));
+        assert!(!contains(&L, [0.75, 0.75]));
+        assert!(!contains(&L, [2., 0.25]));
+    }
+
+    #[test]
+    fn test_clip() {
+        let clipped = clip(&square(0., 0., 2.), &square(1., 1., 2.));
+        assert_eq!(area(&clipped), 1.);
+        assert!(clip(&square(0., 0., 1.), &square(2., 2., 1.)).is_empty());
+        // concave subject
+        assert_eq!(area(&clip(&L, &square(0.5, 0., 1.))), 0.25);
+    }
+
+    #[test]
+    fn test_convex_parts() {
+        assert_eq!(convex_parts(&square(0., 0., 1.)).len(), 1);
+        let parts = convex_parts(&L);
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts.iter().map(|p| area(p)).sum::<f64>(), 0.75);
+        assert_eq!(intersection_area(&square(0.5, 0.5, 1.), &parts), 0.);
+        assert_eq!(intersection_area(&square(0., 0., 1.), &parts), 0.75);
+    }
+}
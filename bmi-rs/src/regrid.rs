@@ -0,0 +1,701 @@
+use crate::errors::{BmiInvalidValue, BmiNotImplementedError};
+use crate::grid::Grid;
+use crate::grid::index::KdTree;
+use crate::polygon::{self, Point};
+use crate::{Bmi, BmiResult, GridType, RefValues};
+use std::error::Error;
+use std::fmt;
+
+/// Interpolation method of [`Weights`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Method {
+    /// Value of the nearest source node. Destination nodes outside of the source grid take
+    /// the value of the nearest edge node.
+    #[default]
+    Nearest,
+    /// Bilinear interpolation between the four surrounding source nodes. Requires a rank 2
+    /// uniform rectilinear or rectilinear source grid.
+    Bilinear,
+    /// First order conservative, area weighted average of the source cells overlapping a
+    /// destination cell. Destination cells partially covered by the source grid average over
+    /// the covered part, see [`Weights::coverage`].
+    ///
+    /// Cells of rank 2 uniform rectilinear and rectilinear grids are centered on nodes and
+    /// extend halfway to neighboring nodes, requiring at least two nodes per axis. Cells of
+    /// unstructured grids are their faces, which may be concave, with values on faces.
+    Conservative,
+}
+
+/// Regridding errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegridError {
+    /// The method does not support grids of the type.
+    Unsupported { method: Method, grid_type: GridType },
+    /// The number of values differs from the grid size.
+    Length { expected: usize, found: usize },
+}
+
+impl fmt::Display for RegridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegridError::Unsupported { method, grid_type } => {
+                write!(
+                    f,
+                    "{method:?} regridding does not support {grid_type} grids"
+                )
+            }
+            RegridError::Length { expected, found } => {
+                write!(f, "expected {expected} values, found {found}")
+            }
+        }
+    }
+}
+
+impl Error for RegridError {}
+
+fn unsupported<T>(method: Method, grid: &Grid) -> BmiResult<T> {
+    Err(Box::new(RegridError::Unsupported {
+        method,
+        grid_type: grid.grid_type(),
+    }))
+}
+
+/// Return the x and y coordinates of a grid's nodes.
+fn nodes(method: Method, grid: &Grid) -> BmiResult<(Vec<f64>, Vec<f64>)> {
+    match grid.node_xy()? {
+        Some(xy) => Ok(xy),
+        None => unsupported(method, grid),
+    }
+}
+
+/// Return the cell `i` of a monotonic axis of at least two nodes nearest to `v`, and `t`
+/// such that `v = axis[i] + t * (axis[i + 1] - axis[i])`.
+fn cell(axis: &[f64], v: f64) -> (usize, f64) {
+    let n = axis.len();
+    let ascending = axis[n - 1] >= axis[0];
+    let i = axis.partition_point(|a| match ascending {
+        true => *a <= v,
+        false => *a >= v,
+    });
+    let i = i.saturating_sub(1).min(n - 2);
+    (i, (v - axis[i]) / (axis[i + 1] - axis[i]))
+}
+
+/// Return the cell containing `v`, see [`cell`].
+fn locate(axis: &[f64], v: f64) -> Option<(usize, f64)> {
+    if axis.len() < 2 {
+        return None;
+    }
+    let (i, t) = cell(axis, v);
+    (0. ..=1.).contains(&t).then_some((i, t))
+}
+
+/// Return the index of the node of `axis` nearest to `v`.
+fn nearest(axis: &[f64], v: f64) -> usize {
+    if axis.len() < 2 {
+        return 0;
+    }
+    match cell(axis, v) {
+        (i, t) if t < 0.5 => i,
+        (i, _) => i + 1,
+    }
+}
+
+/// Return the `n + 1` cell boundaries of an axis of `n >= 2` nodes.
+fn bounds(axis: &[f64]) -> Vec<f64> {
+    let n = axis.len();
+    let mut bounds = Vec::with_capacity(n + 1);
+    bounds.push(axis[0] - (axis[1] - axis[0]) / 2.);
+    bounds.extend(axis.windows(2).map(|w| (w[0] + w[1]) / 2.));
+    bounds.push(axis[n - 1] + (axis[n - 1] - axis[n - 2]) / 2.);
+    bounds
+}
+
+/// Return, per destination axis cell, the overlapping source axis cells and overlap lengths,
+/// from the axes' cell bounds.
+fn overlaps(src: &[f64], dst: &[f64]) -> Vec<Vec<(usize, f64)>> {
+    dst.windows(2)
+        .map(|d| {
+            let (lo, hi) = (d[0].min(d[1]), d[0].max(d[1]));
+            src.windows(2)
+                .enumerate()
+                .filter_map(|(i, s)| {
+                    let len = hi.min(s[0].max(s[1])) - lo.max(s[0].min(s[1]));
+                    (len > 0.).then_some((i, len))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Cells of a grid for conservative regridding.
+enum Cells {
+    /// Node centered cells of a rank 2 (uniform) rectilinear grid, by x and y axis cell bounds.
+    Axes(Vec<f64>, Vec<f64>),
+    /// Faces of an unstructured grid and their bounding boxes.
+    Faces(Vec<Vec<Point>>, Vec<[f64; 4]>),
+}
+
+impl Cells {
+    fn new(grid: &Grid) -> BmiResult<Self> {
+        if let Some((x, y)) = grid.axes()? {
+            if x.len() < 2 || y.len() < 2 {
+                return BmiInvalidValue.into();
+            }
+            return Ok(Cells::Axes(bounds(&x), bounds(&y)));
+        }
+        if grid.grid_type() != GridType::Unstructured {
+            return unsupported(Method::Conservative, grid);
+        }
+        let faces = grid.face_polygons()?;
+        let bboxes = faces.iter().map(|face| polygon::bbox(face)).collect();
+        Ok(Cells::Faces(faces, bboxes))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Cells::Axes(x, y) => (x.len() - 1) * (y.len() - 1),
+            Cells::Faces(faces, _) => faces.len(),
+        }
+    }
+
+    fn polygon(&self, i: usize) -> Vec<Point> {
+        match self {
+            Cells::Axes(x, y) => {
+                let (i, j) = (i % (x.len() - 1), i / (x.len() - 1));
+                vec![
+                    [x[i], y[j]],
+                    [x[i + 1], y[j]],
+                    [x[i + 1], y[j + 1]],
+                    [x[i], y[j + 1]],
+                ]
+            }
+            Cells::Faces(faces, _) => faces[i].clone(),
+        }
+    }
+
+    /// Return the cells whose bounding boxes intersect `[x min, y min, x max, y max]`.
+    fn candidates(&self, [x0, y0, x1, y1]: [f64; 4]) -> Vec<usize> {
+        match self {
+            Cells::Axes(x, y) => {
+                let range = |bounds: &[f64], lo: f64, hi: f64| -> Vec<usize> {
+                    (0..bounds.len() - 1)
+                        .filter(|i| {
+                            let (a, b) = (bounds[*i], bounds[i + 1]);
+                            a.min(b) < hi && a.max(b) > lo
+                        })
+                        .collect()
+                };
+                let (nx, columns) = (x.len() - 1, range(x, x0, x1));
+                range(y, y0, y1)
+                    .into_iter()
+                    .flat_map(|j| columns.iter().map(move |i| j * nx + i))
+                    .collect()
+            }
+            Cells::Faces(_, bboxes) => (0..bboxes.len())
+                .filter(|i| {
+                    let b = bboxes[*i];
+                    b[0] < x1 && b[2] > x0 && b[1] < y1 && b[3] > y0
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Mass budget of conservative regridding, see [`Weights::conservation`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conservation {
+    /// Source mass, value times area summed over the source cells.
+    pub total: f64,
+    /// Source mass within the destination grid, value times the area of the cell covered by
+    /// destination cells.
+    pub source: f64,
+    /// Destination mass, regridded value times covered area summed over the destination
+    /// cells.
+    pub destination: f64,
+}
+
+impl Conservation {
+    /// Return the relative difference of the [`destination`] and [`source`] masses.
+    ///
+    /// [`destination`]: Conservation::destination
+    /// [`source`]: Conservation::source
+    pub fn relative_error(&self) -> f64 {
+        (self.destination - self.source).abs() / self.source.abs()
+    }
+}
+
+/// Sparse regridding weights from the nodes, or for [`Method::Conservative`] the cells, of a
+/// source grid to those of a destination grid, a row of source indices and weights per
+/// destination node or cell.
+///
+/// Weights are computed once from the grids' coordinates and applied to any number of
+/// variables on the source grid. Grids are read through the [`Bmi`] grid information
+/// methods, see [`Grid::from_model`]. Coordinates are planar; `z` is ignored.
+///
+/// Example:
+/// ```
+/// use bmi_rs::RefValues;
+/// use bmi_rs::grid::Grid;
+/// use bmi_rs::regrid::{Method, Weights};
+///
+/// let src = Grid::rectilinear(vec![0., 1., 2.], vec![0., 1.], vec![]);
+/// let dst = Grid::Points { x: vec![0.5, 1.5], y: vec![0.5, 0.], z: vec![] };
+/// let weights = Weights::between(&src, &dst, Method::Bilinear).unwrap();
+/// let values = weights.apply(RefValues::F64(&[0., 1., 2., 10., 11., 12.])).unwrap();
+/// assert_eq!(values, [5.5, 1.5]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weights {
+    method: Method,
+    src_size: usize,
+    offsets: Vec<usize>,
+    cols: Vec<u32>,
+    weights: Vec<f64>,
+    /// Conservative weights' source and destination cell areas and destination cell areas
+    /// covered by the source grid.
+    src_area: Vec<f64>,
+    dst_area: Vec<f64>,
+    covered: Vec<f64>,
+}
+
+impl Weights {
+    /// Compute weights from grid `src_grid` of `src` to grid `dst_grid` of `dst`.
+    pub fn new<S: Bmi, D: Bmi>(
+        src: &S,
+        src_grid: i32,
+        dst: &D,
+        dst_grid: i32,
+        method: Method,
+    ) -> BmiResult<Self> {
+        Self::between(
+            &Grid::from_model(src, src_grid)?,
+            &Grid::from_model(dst, dst_grid)?,
+            method,
+        )
+    }
+
+    /// Compute weights from grid `src` to grid `dst`.
+    ///
+    /// Returns Err([`RegridError::Unsupported`]) if `method` does not support the grids'
+    /// types or ranks.
+    pub fn between(src: &Grid, dst: &Grid, method: Method) -> BmiResult<Self> {
+        let src_size = src.size()? as usize;
+        let rows: Vec<Vec<(u32, f64)>> = match method {
+            Method::Nearest => {
+                let (x, y) = nodes(method, dst)?;
+                match src.axes()? {
+                    Some((sx, sy)) => x
+                        .iter()
+                        .zip(&y)
+                        .map(|(x, y)| {
+                            let i = nearest(&sy, *y) * sx.len() + nearest(&sx, *x);
+                            vec![(i as u32, 1.)]
+                        })
+                        .collect(),
+                    None => {
+                        let (sx, sy) = nodes(method, src)?;
+                        let index = KdTree::new(&sx, &sy);
+                        x.iter()
+                            .zip(&y)
+                            .map(|(x, y)| {
+                                let nearest = index.nearest(*x, *y, 1);
+                                nearest.into_iter().map(|i| (i, 1.)).collect()
+                            })
+                            .collect()
+                    }
+                }
+            }
+            Method::Bilinear => {
+                let Some((sx, sy)) = src.axes()? else {
+                    return unsupported(method, src);
+                };
+                let (x, y) = nodes(method, dst)?;
+                let nx = sx.len();
+                x.iter()
+                    .zip(&y)
+                    .map(|(x, y)| match (locate(&sx, *x), locate(&sy, *y)) {
+                        (Some((i, tx)), Some((j, ty))) => [
+                            (j * nx + i, (1. - tx) * (1. - ty)),
+                            (j * nx + i + 1, tx * (1. - ty)),
+                            ((j + 1) * nx + i, (1. - tx) * ty),
+                            ((j + 1) * nx + i + 1, tx * ty),
+                        ]
+                        .into_iter()
+                        .filter(|(_, w)| *w > 0.)
+                        .map(|(i, w)| (i as u32, w))
+                        .collect(),
+                        _ => Vec::new(),
+                    })
+                    .collect()
+            }
+            Method::Conservative => return Self::conservative(src, dst),
+        };
+        Ok(Self::from_rows(method, src_size, rows))
+    }
+
+    fn conservative(src: &Grid, dst: &Grid) -> BmiResult<Self> {
+        let (src, dst) = (Cells::new(src)?, Cells::new(dst)?);
+        // overlap areas of source cells per destination cell
+        let overlaps: Vec<Vec<(u32, f64)>> = match (&src, &dst) {
+            (Cells::Axes(sx, sy), Cells::Axes(dx, dy)) => {
+                let (ox, oy) = (overlaps(sx, dx), overlaps(sy, dy));
+                let nx = sx.len() - 1;
+                oy.iter()
+                    .flat_map(|oy| ox.iter().map(move |ox| (oy, ox)))
+                    .map(|(oy, ox)| {
+                        oy.iter()
+                            .flat_map(|(j, ly)| {
+                                ox.iter().map(move |(i, lx)| ((j * nx + i) as u32, lx * ly))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+            _ => (0..dst.len())
+                .map(|d| {
+                    let poly = dst.polygon(d);
+                    let parts = polygon::convex_parts(&poly);
+                    let min = 1e-12 * polygon::area(&poly);
+                    src.candidates(polygon::bbox(&poly))
+                        .into_iter()
+                        .filter_map(|s| {
+                            let area = polygon::intersection_area(&src.polygon(s), &parts);
+                            (area > min).then_some((s as u32, area))
+                        })
+                        .collect()
+                })
+                .collect(),
+        };
+        let covered: Vec<f64> = overlaps
+            .iter()
+            .map(|row| row.iter().map(|(_, area)| area).sum())
+            .collect();
+        let rows = overlaps
+            .into_iter()
+            .zip(&covered)
+            .map(|(row, covered)| row.into_iter().map(|(s, a)| (s, a / covered)).collect())
+            .collect();
+        let area = |cells: &Cells| -> Vec<f64> {
+            (0..cells.len())
+                .map(|i| polygon::area(&cells.polygon(i)))
+                .collect()
+        };
+        Ok(Self {
+            src_area: area(&src),
+            dst_area: area(&dst),
+            covered,
+            ..Self::from_rows(Method::Conservative, src.len(), rows)
+        })
+    }
+
+    fn from_rows(method: Method, src_size: usize, rows: Vec<Vec<(u32, f64)>>) -> Self {
+        let mut offsets = Vec::with_capacity(rows.len() + 1);
+        offsets.push(0);
+        let (mut cols, mut weights) = (Vec::new(), Vec::new());
+        for row in rows {
+            for (col, weight) in row {
+                cols.push(col);
+                weights.push(weight);
+            }
+            offsets.push(cols.len());
+        }
+        Self {
+            method,
+            src_size,
+            offsets,
+            cols,
+            weights,
+            src_area: Vec::new(),
+            dst_area: Vec::new(),
+            covered: Vec::new(),
+        }
+    }
+
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Return the number of source grid nodes or cells.
+    pub fn src_size(&self) -> usize {
+        self.src_size
+    }
+
+    /// Return the number of destination grid nodes or cells.
+    pub fn dst_size(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return the number of non-zero weights.
+    pub fn nnz(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Return the source indices and weights of destination node or cell `i`.
+    pub fn row(&self, i: usize) -> (&[u32], &[f64]) {
+        let range = self.offsets[i]..self.offsets[i + 1];
+        (&self.cols[range.clone()], &self.weights[range])
+    }
+
+    /// Return the fraction of each destination cell's area covered by the source grid, empty
+    /// unless the weights are [`Method::Conservative`].
+    pub fn coverage(&self) -> Vec<f64> {
+        self.covered
+            .iter()
+            .zip(&self.dst_area)
+            .map(|(covered, area)| covered / area)
+            .collect()
+    }
+
+    /// Return the mass budget of regridding `src`, to check mass conservation.
+    ///
+    /// Source cells partially outside of the destination grid only count the covered part of
+    /// their area towards [`Conservation::source`]. Destination cells overlapping each other
+    /// show as excess destination mass. Returns Err([`BmiNotImplementedError`]) unless the
+    /// weights are [`Method::Conservative`].
+    pub fn conservation(&self, src: RefValues) -> BmiResult<Conservation> {
+        if self.method != Method::Conservative {
+            return BmiNotImplementedError.into();
+        }
+        let values = src.to_f64_vec();
+        let dst = self.apply(src)?;
+        // source cell areas overlapped by destination cells
+        let mut overlap = vec![0.; self.src_size];
+        for (d, covered) in self.covered.iter().enumerate() {
+            let (cols, weights) = self.row(d);
+            for (s, weight) in cols.iter().zip(weights) {
+                overlap[*s as usize] += weight * covered;
+            }
+        }
+        let mass = |area: &dyn Fn(usize) -> f64| -> f64 {
+            values.iter().enumerate().map(|(s, v)| v * area(s)).sum()
+        };
+        Ok(Conservation {
+            total: mass(&|s| self.src_area[s]),
+            source: mass(&|s| overlap[s].min(self.src_area[s])),
+            destination: dst
+                .iter()
+                .zip(&self.covered)
+                .filter(|(_, covered)| **covered > 0.)
+                .map(|(v, covered)| v * covered)
+                .sum(),
+        })
+    }
+
+    fn apply_with(&self, dst: &mut [f64], value: impl Fn(usize) -> f64) {
+        for (i, dst) in dst.iter_mut().enumerate() {
+            let (cols, weights) = self.row(i);
+            *dst = match cols.is_empty() {
+                true => f64::NAN,
+                false => cols
+                    .iter()
+                    .zip(weights)
+                    .map(|(col, weight)| weight * value(*col as usize))
+                    .sum(),
+            };
+        }
+    }
+
+    /// Regrid `src` into `dst`. Destination nodes without weights are set to `NaN`.
+    ///
+    /// Returns Err([`RegridError::Length`]) if `src` or `dst` do not match the grid sizes.
+    pub fn apply_into(&self, src: RefValues, dst: &mut [f64]) -> BmiResult<()> {
+        for (expected, found) in [(self.src_size, src.len()), (self.dst_size(), dst.len())] {
+            if expected != found {
+                return Err(Box::new(RegridError::Length { expected, found }));
+            }
+        }
+        macro_rules! apply {
+            ($($name:ident),*$(,)?) => {
+                match src {
+                    $(RefValues::$name(v) => self.apply_with(dst, |i| v[i] as f64),)*
+                }
+            };
+        }
+        apply!(I16, U16, I32, U32, I64, U64, F32, F64);
+        Ok(())
+    }
+
+    /// Return `src` regridded, see [`apply_into`].
+    ///
+    /// [`apply_into`]: Weights::apply_into
+    pub fn apply(&self, src: RefValues) -> BmiResult<Vec<f64>> {
+        let mut dst = vec![0.; self.dst_size()];
+        self.apply_into(src, &mut dst)?;
+        Ok(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+
+    fn uniform(n: u32, spacing: f64) -> Grid {
+        Grid::UniformRectilinear {
+            shape: vec![n, n],
+            spacing: vec![spacing, spacing],
+            origin: vec![spacing / 2., spacing / 2.],
+        }
+    }
+
+    #[test]
+    fn test_axis() {
+        let descending = [3., 2., 1., 0.];
+        assert_eq!(locate(&descending, 2.5), Some((0, 0.5)));
+        assert_eq!(locate(&descending, 0.), Some((2, 1.)));
+        assert_eq!(locate(&descending, -1.), None);
+        assert_eq!(nearest(&descending, 1.4), 2);
+        assert_eq!(nearest(&descending, 9.), 0);
+        assert_eq!(nearest(&[0., 1., 2.], 9.), 2);
+        assert_eq!(bounds(&[0., 1., 3.]), [-0.5, 0.5, 2., 4.]);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let dst = Grid::Points {
+            x: vec![0.4, 2.9, 10.],
+            y: vec![0.6, 0.1, 10.],
+            z: vec![],
+        };
+        let weights = Weights::between(&uniform(3, 1.), &dst, Method::Nearest).unwrap();
+        let values: Vec<f64> = (0..9).map(f64::from).collect();
+        let regridded = weights.apply(RefValues::F64(&values)).unwrap();
+        assert_eq!(regridded, [0., 2., 8.]);
+
+        let src = Grid::Points {
+            x: vec![0., 5.],
+            y: vec![0., 5.],
+            z: vec![],
+        };
+        let weights = Weights::between(&src, &dst, Method::Nearest).unwrap();
+        let regridded = weights.apply(RefValues::I32(&[1, 2])).unwrap();
+        assert_eq!(regridded, [1., 1., 2.]);
+    }
+
+    #[test]
+    fn test_bilinear() {
+        let src = Grid::rectilinear(vec![0., 1., 3.], vec![2., 0.], vec![]);
+        // f(x, y) = x + 2y, in row major order
+        let values: Vec<f64> = [2., 0.]
+            .iter()
+            .flat_map(|y| [0., 1., 3.].map(|x| x + 2. * y))
+            .collect();
+        let dst = Grid::Points {
+            x: vec![0.5, 2., 3., 4.],
+            y: vec![0.5, 1.5, 2., 0.],
+            z: vec![],
+        };
+        let weights = Weights::between(&src, &dst, Method::Bilinear).unwrap();
+        let regridded = weights.apply(RefValues::F64(&values)).unwrap();
+        assert_eq!(regridded[..3], [1.5, 5., 7.]);
+        assert!(regridded[3].is_nan());
+        assert_eq!(weights.row(2), (&[2][..], &[1.][..]));
+        assert!(Weights::between(&dst, &src, Method::Bilinear).is_err());
+    }
+
+    #[test]
+    fn test_conservative() {
+        let (fine, coarse) = (uniform(4, 1.), uniform(2, 2.));
+        let values: Vec<f64> = (0..16).map(f64::from).collect();
+        let weights = Weights::between(&fine, &coarse, Method::Conservative).unwrap();
+        assert_eq!(weights.nnz(), 16);
+        let regridded = weights.apply(RefValues::F64(&values)).unwrap();
+        assert_eq!(regridded, [2.5, 4.5, 10.5, 12.5]);
+        // mass is conserved between grids covering the same area
+        let mass = |values: &[f64], area: f64| values.iter().sum::<f64>() * area;
+        assert_eq!(mass(&values, 1.), mass(&regridded, 4.));
+
+        let weights = Weights::between(&coarse, &fine, Method::Conservative).unwrap();
+        let regridded = weights.apply(RefValues::F64(&[1., 2., 3., 4.])).unwrap();
+        assert_eq!(regridded[..4], [1., 1., 2., 2.]);
+        let mut dst = vec![0.; 3];
+        assert!(weights.apply_into(RefValues::F64(&[1.]), &mut dst).is_err());
+    }
+
+    fn mesh(nodes: &[Point], faces: &[&[u32]]) -> Grid {
+        Grid::Unstructured {
+            x: nodes.iter().map(|p| p[0]).collect(),
+            y: nodes.iter().map(|p| p[1]).collect(),
+            z: vec![],
+            edge_nodes: vec![],
+            face_edges: vec![],
+            face_nodes: faces.concat(),
+            nodes_per_face: faces.iter().map(|face| face.len() as u32).collect(),
+        }
+    }
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-12)
+    }
+
+    #[test]
+    fn test_unstructured() {
+        // unit square split along its diagonal, below and above
+        let square = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        let triangles = mesh(&square, &[&[0, 1, 2], &[0, 2, 3]]);
+        let quarters = uniform(2, 0.5);
+
+        let weights = Weights::between(&triangles, &quarters, Method::Conservative).unwrap();
+        assert_eq!((weights.src_size(), weights.dst_size()), (2, 4));
+        let regridded = weights.apply(RefValues::F64(&[1., 3.])).unwrap();
+        assert!(close(&regridded, &[2., 1., 3., 2.]));
+        assert!(close(&weights.coverage(), &[1.; 4]));
+        let budget = weights.conservation(RefValues::F64(&[1., 3.])).unwrap();
+        assert!(close(&[budget.source, budget.destination], &[2., 2.]));
+        assert!(budget.relative_error() < 1e-12);
+
+        let weights = Weights::between(&quarters, &triangles, Method::Conservative).unwrap();
+        let regridded = weights.apply(RefValues::F64(&[1., 2., 3., 4.])).unwrap();
+        assert!(close(&regridded, &[2.25, 2.75]));
+
+        // concave destination face, the unit square without its upper right quarter
+        let l = [
+            [0., 0.],
+            [1., 0.],
+            [1., 0.5],
+            [0.5, 0.5],
+            [0.5, 1.],
+            [0., 1.],
+        ];
+        let l = mesh(&l, &[&[0, 1, 2, 3, 4, 5]]);
+        let weights = Weights::between(&quarters, &l, Method::Conservative).unwrap();
+        let regridded = weights.apply(RefValues::F64(&[1., 2., 3., 4.])).unwrap();
+        assert!(close(&regridded, &[2.]));
+
+        let nearest = Weights::between(&quarters, &l, Method::Nearest).unwrap();
+        assert!(nearest.conservation(RefValues::F64(&[1.; 4])).is_err());
+        assert!(Weights::between(&mesh(&square, &[&[0, 1, 9]]), &l, Method::Conservative).is_err());
+    }
+
+    #[test]
+    fn test_partial_overlap() {
+        let square = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        let triangles = mesh(&square, &[&[0, 1, 2], &[0, 2, 3]]);
+        let shifted = square.map(|[x, y]| [x + 0.5, y]);
+        let shifted = mesh(&shifted, &[&[0, 1, 2, 3]]);
+
+        let weights = Weights::between(&triangles, &shifted, Method::Conservative).unwrap();
+        assert!(close(&weights.coverage(), &[0.5]));
+        // the average over the covered part, 3/4 of the lower and 1/4 of the upper triangle
+        let regridded = weights.apply(RefValues::F64(&[1., 3.])).unwrap();
+        assert!(close(&regridded, &[1.5]));
+        let budget = weights.conservation(RefValues::F64(&[1., 3.])).unwrap();
+        assert!(close(
+            &[budget.total, budget.source, budget.destination],
+            &[2., 0.75, 0.75]
+        ));
+    }
+
+    #[test]
+    fn test_models() {
+        let mut src = SyntheticForcing::new(0., 1., 1.)
+            .grid(uniform(4, 1.))
+            .variable("precip", "mm", Signal::Constant(2.));
+        src.initialize("").unwrap();
+        let dst = SyntheticForcing::new(0., 1., 1.).grid(uniform(2, 2.));
+        let weights = Weights::new(&src, 0, &dst, 0, Method::Conservative).unwrap();
+        let regridded = weights.apply(src.get_value_ptr("precip").unwrap()).unwrap();
+        assert_eq!(regridded, [2.; 4]);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::bmi::forward_grid_funcs;
+use crate::clock::{Clock, steps_between};
+use crate::errors::{BmiIndexOutOfBounds, BmiInvalidTimeStep, BmiInvalidValue, BmiUnknownVariable};
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values};
+use std::collections::HashMap;
+
+/// How an output variable is aggregated when a [`Resample`] step spans more than one wrapped
+/// model step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    /// Sum the values of each wrapped model step.
+    /// Use for quantities accumulated over a step (e.g. a depth of runoff).
+    ///
+    /// When the wrapped model step spans more than one [`Resample`] step, the value is split
+    /// evenly across them.
+    Sum,
+    /// Average the values of each wrapped model step.
+    /// Use for rates and states.
+    Mean,
+    /// Take the value of the last wrapped model step.
+    Last,
+}
+
+/// How an input variable set on a [`Resample`] is passed to the wrapped model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputPolicy {
+    /// Pass the value through as soon as it is set.
+    Hold,
+    /// When a [`Resample`] step spans more than one wrapped model step, linearly interpolate
+    /// between the previous and current step's value.
+    /// e.g. the last wrapped model step receives the current value.
+    ///
+    /// When the wrapped model step spans more than one [`Resample`] step, pass the average of the
+    /// values set during each [`Resample`] step.
+    Interpolate,
+}
+
+/// The relationship between the [`Resample`] and wrapped model time steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ratio {
+    /// Run the wrapped model `n` times per step.
+    Substep(u64),
+    /// Run the wrapped model once every `n` steps.
+    Buffer(u64),
+}
+
+#[derive(Debug)]
+struct Output {
+    aggregation: Aggregation,
+    acc: Vec<f64>,
+    values: Option<Values>,
+}
+
+#[derive(Debug)]
+struct Input {
+    policy: InputPolicy,
+    value_type: ValueType,
+    prev: Vec<f64>,
+    next: Vec<f64>,
+    acc: Vec<f64>,
+    values: Option<Values>,
+}
+
+/// [`Bmi`] adapter that presents a wrapped model with a different [`get_time_step`].
+///
+/// The adapter's time step must be a whole multiple or a whole fraction of the wrapped model's
+/// time step.
+/// If it is a multiple, each [`update`] runs the wrapped model multiple times and aggregates the
+/// outputs according to their [`Aggregation`].
+/// If it is a fraction, inputs are buffered and the wrapped model is run once its time step has
+/// elapsed, i.e. when the adapter's and wrapped model's current times agree.
+///
+/// Outputs default to [`Aggregation::Last`] and inputs default to [`InputPolicy::Hold`].
+/// Before the first [`update`], the wrapped model's values are returned.
+///
+/// Example:
+/// ```compile_fail
+/// // present a 15 minute model as an hourly model
+/// let model = Resample::new(RunoffModel::new(), 3600.)
+///     .output("runoff", Aggregation::Sum)
+///     .input("precipitation_rate", InputPolicy::Interpolate);
+/// ```
+///
+/// [`get_time_step`]: Bmi::get_time_step
+/// [`update`]: Bmi::update
+pub struct Resample<M> {
+    model: M,
+    time_step: f64,
+    clock: Clock,
+    ratio: Ratio,
+    buffered: u64,
+    outputs: HashMap<String, Output>,
+    inputs: HashMap<String, Input>,
+}
+
+impl<M: Bmi> Resample<M> {
+    /// Wrap `model` presenting a time step of `time_step` in the wrapped model's time units.
+    ///
+    /// `time_step` is checked in [`initialize`](Bmi::initialize), which returns
+    /// Err([`BmiInvalidTimeStep`]) if it is not positive and finite, or Err([`BmiInvalidValue`])
+    /// if the wrapped model's end time is before its start time.
+    pub fn new(model: M, time_step: f64) -> Self {
+        Self {
+            model,
+            time_step,
+            // replaced in `initialize`
+            clock: Clock::new(0., 0., 1.),
+            ratio: Ratio::Substep(1),
+            buffered: 0,
+            outputs: HashMap::new(),
+            inputs: HashMap::new(),
+        }
+    }
+
+    /// Aggregate output variable `name` using `aggregation`.
+    pub fn output(mut self, name: &str, aggregation: Aggregation) -> Self {
+        let output = Output {
+            aggregation,
+            acc: Vec::new(),
+            values: None,
+        };
+        self.outputs.insert(name.to_string(), output);
+        self
+    }
+
+    /// Pass input variable `name` to the wrapped model using `policy`.
+    pub fn input(mut self, name: &str, policy: InputPolicy) -> Self {
+        let input = Input {
+            policy,
+            value_type: ValueType::F64,
+            prev: Vec::new(),
+            next: Vec::new(),
+            acc: Vec::new(),
+            values: None,
+        };
+        self.inputs.insert(name.to_string(), input);
+        self
+    }
+
+    /// Return a reference to the wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    /// Consume the adapter and return the wrapped model.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    fn interpolated_inputs(&mut self) -> impl Iterator<Item = (&String, &mut Input)> {
+        self.inputs
+            .iter_mut()
+            .filter(|(_, input)| input.policy == InputPolicy::Interpolate)
+    }
+
+    fn substep(&mut self, n: u64) -> BmiResult<()> {
+        for output in self.outputs.values_mut() {
+            output.acc.iter_mut().for_each(|v| *v = 0.);
+        }
+        for i in 1..=n {
+            let frac = i as f64 / n as f64;
+            for (name, input) in self.inputs.iter() {
+                if input.policy != InputPolicy::Interpolate {
+                    continue;
+                }
+                let v: Vec<f64> = std::iter::zip(&input.prev, &input.next)
+                    .map(|(prev, next)| prev + (next - prev) * frac)
+                    .collect();
+                let v = Values::from_f64_slice(input.value_type, &v);
+                self.model.set_value(name, (&v).into())?;
+            }
+            self.model.update()?;
+            for (name, output) in self.outputs.iter_mut() {
+                match output.aggregation {
+                    Aggregation::Sum | Aggregation::Mean => {
+                        let v = self.model.get_value_ptr(name)?.to_f64_vec();
+                        output.acc.resize(v.len(), 0.);
+                        std::iter::zip(&mut output.acc, v).for_each(|(acc, v)| *acc += v);
+                    }
+                    Aggregation::Last => {}
+                }
+            }
+        }
+        for (name, output) in self.outputs.iter_mut() {
+            if output.aggregation == Aggregation::Mean {
+                output.acc.iter_mut().for_each(|v| *v /= n as f64);
+            }
+            if output.aggregation != Aggregation::Last {
+                let value_type = self.model.get_var_type(name)?;
+                output.values = Some(Values::from_f64_slice(value_type, &output.acc));
+            }
+        }
+        for (_, input) in self.interpolated_inputs() {
+            input.prev.clone_from(&input.next);
+        }
+        Ok(())
+    }
+
+    fn buffer(&mut self, n: u64) -> BmiResult<()> {
+        for (_, input) in self.interpolated_inputs() {
+            std::iter::zip(&mut input.acc, &input.next).for_each(|(acc, v)| *acc += v);
+        }
+        self.buffered += 1;
+        if self.buffered < n {
+            return Ok(());
+        }
+        for (name, input) in self.inputs.iter_mut() {
+            if input.policy != InputPolicy::Interpolate {
+                continue;
+            }
+            input.acc.iter_mut().for_each(|v| *v /= n as f64);
+            let v = Values::from_f64_slice(input.value_type, &input.acc);
+            self.model.set_value(name, (&v).into())?;
+            input.acc.iter_mut().for_each(|v| *v = 0.);
+        }
+        self.model.update()?;
+        self.buffered = 0;
+        for (name, output) in self.outputs.iter_mut() {
+            if output.aggregation == Aggregation::Sum {
+                let v: Vec<f64> = self
+                    .model
+                    .get_value_ptr(name)?
+                    .to_f64_vec()
+                    .into_iter()
+                    .map(|v| v / n as f64)
+                    .collect();
+                let value_type = self.model.get_var_type(name)?;
+                output.values = Some(Values::from_f64_slice(value_type, &v));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<M: Bmi> Bmi for Resample<M> {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        if !(self.time_step > 0. && self.time_step.is_finite()) {
+            return BmiInvalidTimeStep.into();
+        }
+        self.model.initialize(config_file)?;
+
+        let inner_step = self.model.get_time_step();
+        self.ratio = if self.time_step >= inner_step {
+            Ratio::Substep(steps_between(0., self.time_step, inner_step)?)
+        } else {
+            Ratio::Buffer(steps_between(0., inner_step, self.time_step)?)
+        };
+        let (start, end) = (self.model.get_start_time(), self.model.get_end_time());
+        if start.is_nan() || end.is_nan() || end < start {
+            return BmiInvalidValue.into();
+        }
+        self.clock = Clock::new(start, end, self.time_step);
+        self.buffered = 0;
+
+        for (name, output) in self.outputs.iter_mut() {
+            if !self.model.get_output_var_names().contains(&name.as_str()) {
+                return BmiUnknownVariable.into();
+            }
+            output.acc.clear();
+            output.values = None;
+        }
+        for (name, input) in self.inputs.iter_mut() {
+            if !self.model.get_input_var_names().contains(&name.as_str()) {
+                return BmiUnknownVariable.into();
+            }
+            input.value_type = self.model.get_var_type(name)?;
+            input.next = self.model.get_value_ptr(name)?.to_f64_vec();
+            input.prev.clone_from(&input.next);
+            input.acc = vec![0.; input.next.len()];
+            input.values = None;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        match self.ratio {
+            Ratio::Substep(n) => self.substep(n)?,
+            Ratio::Buffer(n) => self.buffer(n)?,
+        }
+        self.clock.advance();
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        self.model.finalize()
+    }
+
+    fn get_component_name(&self) -> &str {
+        self.model.get_component_name()
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        self.model.get_input_var_names()
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.model.get_output_var_names()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        self.model.get_var_grid(name)
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        self.model.get_var_type(name)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        self.model.get_var_units(name)
+    }
+
+    fn get_var_itemsize(&self, name: &str) -> BmiResult<u32> {
+        self.model.get_var_itemsize(name)
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        self.model.get_var_nbytes(name)
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        self.model.get_var_location(name)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.clock.current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.model.get_start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.model.get_end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        self.model.get_time_units()
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.time_step
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        if let Some(Output {
+            values: Some(values),
+            ..
+        }) = self.outputs.get(name)
+        {
+            return Ok(values.into());
+        }
+        if let Some(Input {
+            values: Some(values),
+            ..
+        }) = self.inputs.get(name)
+        {
+            return Ok(values.into());
+        }
+        self.model.get_value_ptr(name)
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        match self.inputs.get_mut(name) {
+            Some(input) if input.policy == InputPolicy::Interpolate => {
+                if src.len() != input.next.len() {
+                    return BmiIndexOutOfBounds.into();
+                }
+                input.next = src.to_f64_vec();
+                input.values = Some(Values::from_f64_slice(input.value_type, &input.next));
+                Ok(())
+            }
+            _ => self.model.set_value(name, src),
+        }
+    }
+
+    fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()> {
+        match self.inputs.get_mut(name) {
+            Some(input) if input.policy == InputPolicy::Interpolate => {
+                if inds.iter().any(|i| *i as usize >= input.next.len()) {
+                    return BmiIndexOutOfBounds.into();
+                }
+                for (i, v) in std::iter::zip(inds, src.to_f64_vec()) {
+                    input.next[*i as usize] = v;
+                }
+                input.values = Some(Values::from_f64_slice(input.value_type, &input.next));
+                Ok(())
+            }
+            _ => self.model.set_value_at_indices(name, inds, src),
+        }
+    }
+
+    forward_grid_funcs!(model);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+
+    fn value(model: &impl Bmi, name: &str) -> f64 {
+        model.get_value_ptr(name).unwrap().to_f64_vec()[0]
+    }
+
+    #[test]
+    fn test_substep() {
+        let mut model = Resample::new(Reservoir::new(900.), 3600.)
+            .output("flux", Aggregation::Mean)
+            .input("rate", InputPolicy::Interpolate);
+        model.initialize("").unwrap();
+        assert_eq!(model.get_time_step(), 3600.);
+
+        model.set_value("rate", RefValues::F64(&[4.])).unwrap();
+        model.update().unwrap();
+        assert_eq!(model.inner().updates, 4);
+        assert_eq!(model.get_current_time(), 3600.);
+        assert_eq!(model.inner().get_current_time(), 3600.);
+        // rate ramps 1, 2, 3, 4 across the substeps
+        assert_eq!(value(&model, "flux"), 2.5);
+        assert_eq!(value(&model, "storage"), 900. * 10.);
+        assert_eq!(value(&model, "rate"), 4.);
+
+        model.update().unwrap();
+        assert_eq!(value(&model, "flux"), 4.);
+    }
+
+    #[test]
+    fn test_buffer() {
+        let mut model = Resample::new(Reservoir::new(3600.), 900.)
+            .output("storage", Aggregation::Sum)
+            .input("rate", InputPolicy::Interpolate);
+        model.initialize("").unwrap();
+
+        for rate in [1., 2., 3.] {
+            model.set_value("rate", RefValues::F64(&[rate])).unwrap();
+            model.update().unwrap();
+            assert_eq!(model.inner().updates, 0);
+        }
+        model.set_value("rate", RefValues::F64(&[6.])).unwrap();
+        model.update().unwrap();
+        assert_eq!(model.inner().updates, 1);
+        assert_eq!(model.get_current_time(), model.inner().get_current_time());
+        assert_eq!(value(&model, "flux"), 3.);
+        assert_eq!(value(&model, "storage"), 3600. * 3. / 4.);
+    }
+
+    #[test]
+    fn test_uneven_time_step() {
+        let mut model = Resample::new(Reservoir::new(900.), 1000.);
+        let err = model.initialize("").unwrap_err();
+        assert!(err.is::<crate::errors::BmiPartialTimeStep>());
+    }
+
+    #[test]
+    fn test_invalid_time_step() {
+        for time_step in [0., -900., f64::NAN, f64::INFINITY] {
+            let mut model = Resample::new(Reservoir::new(900.), time_step);
+            let err = model.initialize("").unwrap_err();
+            assert!(err.is::<BmiInvalidTimeStep>());
+        }
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        let mut model = Resample::new(Reservoir::new(900.), 1800.).output("foo", Aggregation::Sum);
+        assert!(model.initialize("").unwrap_err().is::<BmiUnknownVariable>());
+    }
+}
@@ -0,0 +1,80 @@
+/// Small, seedable, reproducible pseudo random number generator (SplitMix64).
+///
+/// Not suitable for cryptographic use.
+/// See https://prng.di.unimi.it/splitmix64.c
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create a generator whose stream depends on `seed` and each of `keys`.
+    /// Used to draw reproducible values for e.g. a (time step, node) pair without keeping state.
+    pub fn keyed(seed: u64, keys: &[u64]) -> Self {
+        let mut rng = Rng::new(seed);
+        for key in keys {
+            rng.state ^= key;
+            rng.state = rng.next_u64();
+        }
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample from `[0, 1)`.
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1. / (1u64 << 53) as f64)
+    }
+
+    /// Standard normal sample (Box-Muller transform).
+    pub fn normal(&mut self) -> f64 {
+        let u1 = 1. - self.uniform(); // (0, 1]
+        let u2 = self.uniform();
+        (-2. * u1.ln()).sqrt() * (2. * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        let a: Vec<u64> = (0..4).map(|_| Rng::new(7).next_u64()).collect();
+        assert!(a.iter().all(|v| *v == a[0]));
+        assert_eq!(
+            Rng::keyed(7, &[1, 2]).next_u64(),
+            Rng::keyed(7, &[1, 2]).next_u64()
+        );
+        assert_ne!(
+            Rng::keyed(7, &[1, 2]).next_u64(),
+            Rng::keyed(7, &[2, 1]).next_u64()
+        );
+    }
+
+    #[test]
+    fn test_normal_moments() {
+        let mut rng = Rng::new(42);
+        let n = 20_000;
+        let samples: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let var = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 0.03);
+        assert!((var - 1.).abs() < 0.05);
+        assert!(
+            (0..1000)
+                .map(|_| rng.uniform())
+                .all(|u| (0. ..1.).contains(&u))
+        );
+    }
+}
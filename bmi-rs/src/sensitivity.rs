@@ -0,0 +1,371 @@
+use crate::ensemble::for_each_par;
+use crate::param::{self, Parameter};
+use crate::rng::Rng;
+use crate::{Bmi, BmiResult};
+
+/// Morris elementary effects statistics of a parameter, in metric units per parameter range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementaryEffects {
+    /// Mean elementary effect.
+    pub mu: f64,
+    /// Mean absolute elementary effect (Campolongo et al. 2007), used to rank parameters.
+    pub mu_star: f64,
+    /// Standard deviation of the elementary effects, a measure of nonlinearity and
+    /// interaction.
+    pub sigma: f64,
+}
+
+/// Sobol sensitivity indices of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SobolIndices {
+    /// First order index, the fraction of the metric's variance due to the parameter alone.
+    pub first: f64,
+    /// Total order index, the fraction of the metric's variance due to the parameter and its
+    /// interactions.
+    pub total: f64,
+}
+
+fn scale(params: &[Parameter], unit: &[f64]) -> Vec<f64> {
+    params
+        .iter()
+        .zip(unit)
+        .map(|(p, u)| p.min() + u * (p.max() - p.min()))
+        .collect()
+}
+
+/// Return `trajectories` Morris (1991) one-at-a-time trajectories over `params`' bounds on a
+/// grid of `levels` levels, `params.len() + 1` points each, one after another.
+///
+/// Consecutive points of a trajectory differ in a single parameter by
+/// `delta = levels / (2 * (levels - 1))` of its range. Trajectories start at levels no
+/// greater than `1 - delta` and step up, so every point is within bounds.
+///
+/// Panics if `levels` is less than 2.
+pub fn morris_trajectories(
+    params: &[Parameter],
+    trajectories: usize,
+    levels: usize,
+    seed: u64,
+) -> Vec<Vec<f64>> {
+    morris_steps(params, trajectories, levels, seed).0
+}
+
+/// Return [`morris_trajectories`] and, for each step between consecutive points, the moved
+/// parameter and its change in unit space.
+fn morris_steps(
+    params: &[Parameter],
+    trajectories: usize,
+    levels: usize,
+    seed: u64,
+) -> (Vec<Vec<f64>>, Vec<(usize, f64)>) {
+    assert!(levels >= 2, "levels must be at least 2");
+    let k = params.len();
+    let delta = levels as f64 / (2. * (levels - 1) as f64);
+    let mut rng = Rng::new(seed);
+    let mut samples = Vec::with_capacity(trajectories * (k + 1));
+    let mut steps = Vec::with_capacity(trajectories * k);
+    // levels `i / (levels - 1) <= 1 - delta`, i.e. `i <= (levels - 2) / 2`
+    let base = (levels / 2) as u64;
+    for _ in 0..trajectories {
+        let mut x: Vec<f64> = (0..k)
+            .map(|_| (rng.next_u64() % base) as f64 / (levels - 1) as f64)
+            .collect();
+        samples.push(scale(params, &x));
+        // random order of the parameters, Fisher-Yates
+        let mut order: Vec<usize> = (0..k).collect();
+        for i in (1..k).rev() {
+            order.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+        }
+        for j in order {
+            x[j] += delta;
+            samples.push(scale(params, &x));
+            steps.push((j, delta));
+        }
+    }
+    (samples, steps)
+}
+
+/// Return Saltelli (2010) samples over `params`' bounds: the `n` rows of the independent
+/// matrices `A` and `B`, followed by, for each parameter `i`, the `n` rows of `A` with
+/// parameter `i` taken from `B`.
+pub fn saltelli_samples(params: &[Parameter], n: usize, seed: u64) -> Vec<Vec<f64>> {
+    let k = params.len();
+    let mut rng = Rng::new(seed);
+    let mut unit = || -> Vec<f64> { (0..k).map(|_| rng.uniform()).collect() };
+    let a: Vec<Vec<f64>> = (0..n).map(|_| unit()).collect();
+    let b: Vec<Vec<f64>> = (0..n).map(|_| unit()).collect();
+    let mut samples: Vec<Vec<f64>> = a.iter().chain(&b).map(|x| scale(params, x)).collect();
+    for i in 0..k {
+        samples.extend(a.iter().zip(&b).map(|(a, b)| {
+            let mut x = a.clone();
+            x[i] = b[i];
+            scale(params, &x)
+        }));
+    }
+    samples
+}
+
+/// Global sensitivity analysis of a scalar metric of a [`Bmi`] model to its parameters.
+///
+/// Each sample creates an initialized model with the factory, sets each [`Parameter`] with
+/// [`set_value`] (to every element of the variable), runs the model with [`update_until`] its
+/// end time, and computes the metric from the model. Samples are evaluated on [`threads`]
+/// threads, each creating its own models. A failed run's metric is `NaN`, and [`morris`] and
+/// [`sobol`] leave out samples with non-finite metrics.
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::param::Parameter;
+/// use bmi_rs::sensitivity::Sensitivity;
+/// # fn run<M: Bmi>(factory: impl Fn() -> bmi_rs::BmiResult<M> + Sync) {
+///
+/// let params = vec![
+///     Parameter::new("k", "d-1", 0.01, 1.),
+///     Parameter::new("smax", "mm", 10., 500.),
+/// ];
+/// let metric = |model: &M| Ok(model.get_value_ptr("discharge")?.to_f64_vec()[0]);
+/// let sensitivity = Sensitivity::new(factory, params, metric).threads(8);
+/// let screening = sensitivity.morris(20, 4);
+/// let indices = sensitivity.sobol(1024);
+/// # }
+/// ```
+///
+/// [`set_value`]: Bmi::set_value
+/// [`update_until`]: Bmi::update_until
+/// [`threads`]: Sensitivity::threads
+/// [`morris`]: Sensitivity::morris
+/// [`sobol`]: Sensitivity::sobol
+pub struct Sensitivity<F, G> {
+    factory: F,
+    params: Vec<Parameter>,
+    metric: G,
+    threads: usize,
+    seed: u64,
+}
+
+impl<M, F, G> Sensitivity<F, G>
+where
+    M: Bmi,
+    F: Fn() -> BmiResult<M> + Sync,
+    G: Fn(&M) -> BmiResult<f64> + Sync,
+{
+    /// Analyze `metric` of the models created by `factory` over `params`' bounds, evaluated
+    /// on one thread with seed 0.
+    pub fn new(factory: F, params: Vec<Parameter>, metric: G) -> Self {
+        Self {
+            factory,
+            params,
+            metric,
+            threads: 1,
+            seed: 0,
+        }
+    }
+
+    /// Evaluate samples on `threads` threads (default 1).
+    pub fn threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "threads must be positive");
+        self.threads = threads;
+        self
+    }
+
+    /// Seed the random number generator that draws samples (default 0).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn run(&self, values: &[f64]) -> BmiResult<f64> {
+        let mut model = (self.factory)()?;
+        param::set_values(&mut model, &self.params, values)?;
+        model.update_until(model.get_end_time())?;
+        let metric = (self.metric)(&model)?;
+        model.finalize()?;
+        Ok(metric)
+    }
+
+    /// Return the metric for each of `samples`, `NaN` for failed runs.
+    pub fn evaluate(&self, samples: &[Vec<f64>]) -> Vec<f64> {
+        let mut results: Vec<(&[f64], f64)> = samples.iter().map(|s| (&s[..], f64::NAN)).collect();
+        for_each_par(&mut results, self.threads, |_, (values, metric)| {
+            *metric = self.run(values).unwrap_or(f64::NAN);
+        });
+        results.into_iter().map(|(_, metric)| metric).collect()
+    }
+
+    /// Screen parameters with `trajectories` Morris trajectories on a grid of `levels`
+    /// levels, returning each parameter's elementary effects.
+    ///
+    /// Steps with a non-finite metric at either end are left out, so a parameter's statistics
+    /// are `NaN` if none of its steps succeeded. Parameters with `min == max` are not varied
+    /// and have zero effects. Returns no effects, without running the model, if there are no
+    /// parameters.
+    ///
+    /// Panics if `levels` is less than 2.
+    pub fn morris(&self, trajectories: usize, levels: usize) -> Vec<ElementaryEffects> {
+        let k = self.params.len();
+        if k == 0 {
+            return Vec::new();
+        }
+        let (samples, steps) = morris_steps(&self.params, trajectories, levels, self.seed);
+        let y = self.evaluate(&samples);
+        let mut effects = vec![Vec::with_capacity(trajectories); k];
+        for (y, steps) in y.chunks(k + 1).zip(steps.chunks(k)) {
+            for (step, (j, dx)) in steps.iter().enumerate() {
+                let param = &self.params[*j];
+                if param.min() == param.max() {
+                    effects[*j].push(0.);
+                } else if y[step].is_finite() && y[step + 1].is_finite() {
+                    effects[*j].push((y[step + 1] - y[step]) / dx);
+                }
+            }
+        }
+        effects
+            .iter()
+            .map(|ee| {
+                let n = ee.len() as f64;
+                let mu = ee.iter().sum::<f64>() / n;
+                let var = ee.iter().map(|e| (e - mu).powi(2)).sum::<f64>() / (n - 1.).max(1.);
+                ElementaryEffects {
+                    mu,
+                    mu_star: ee.iter().map(|e| e.abs()).sum::<f64>() / n,
+                    sigma: var.sqrt(),
+                }
+            })
+            .collect()
+    }
+
+    /// Estimate first (Saltelli 2010) and total (Jansen 1999) order Sobol indices from
+    /// `samples` base samples, `samples * (params + 2)` runs.
+    ///
+    /// Non-finite metrics are left out of the variance, and base samples with a non-finite
+    /// metric in `A`, `B`, or `A` with the parameter from `B` are left out of a parameter's
+    /// indices.
+    ///
+    /// Panics if `samples` is 0.
+    pub fn sobol(&self, samples: usize) -> Vec<SobolIndices> {
+        assert!(samples > 0, "samples must be positive");
+        let n = samples;
+        let y = self.evaluate(&saltelli_samples(&self.params, n, self.seed));
+        let (y_a, y_b) = (&y[..n], &y[n..2 * n]);
+        let finite: Vec<f64> = y[..2 * n]
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        let mean = finite.iter().sum::<f64>() / finite.len() as f64;
+        let var = finite.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / finite.len() as f64;
+        y[2 * n..]
+            .chunks(n)
+            .map(|y_ab| {
+                let (mut first, mut total, mut count) = (0., 0., 0);
+                for ((a, b), ab) in y_a.iter().zip(y_b).zip(y_ab) {
+                    if a.is_finite() && b.is_finite() && ab.is_finite() {
+                        first += b * (ab - a);
+                        total += (a - ab).powi(2);
+                        count += 1;
+                    }
+                }
+                SobolIndices {
+                    first: first / count as f64 / var,
+                    total: total / (2 * count) as f64 / var,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+
+    fn sensitivity() -> Sensitivity<
+        impl Fn() -> BmiResult<Reservoir> + Sync,
+        impl Fn(&Reservoir) -> BmiResult<f64> + Sync,
+    > {
+        // storage at the end time is `storage + 100 * rate`, `flux` is overwritten by `update`
+        let params = vec![
+            Parameter::new("rate", "m s-1", 0., 2.),
+            Parameter::new("storage", "m", 0., 10.),
+            Parameter::new("flux", "m s-1", 0., 1.),
+        ];
+        Sensitivity::new(
+            || Ok(Reservoir::new(1.)),
+            params,
+            |model: &Reservoir| Ok(model.storage[0]),
+        )
+        .threads(2)
+        .seed(11)
+    }
+
+    #[test]
+    fn test_morris() {
+        let params = [
+            Parameter::new("a", "", 0., 1.),
+            Parameter::new("b", "", -1., 1.),
+        ];
+        let samples = morris_trajectories(&params, 3, 4, 0);
+        assert_eq!(samples.len(), 9);
+        assert!(samples.iter().flatten().all(|v| (-1. ..=1.).contains(v)));
+        for levels in [2, 3, 5] {
+            let samples = morris_trajectories(&params[..1], 20, levels, 1);
+            assert!(samples.iter().flatten().all(|v| (0. ..=1.).contains(v)));
+        }
+
+        let effects = sensitivity().morris(10, 4);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(effects[0].mu, 200.) && close(effects[0].sigma, 0.));
+        assert!(close(effects[1].mu_star, 10.));
+        assert!(close(effects[2].mu_star, 0.));
+
+        let empty = Sensitivity::new(
+            || Ok(Reservoir::new(1.)),
+            Vec::new(),
+            |_: &Reservoir| Ok(0.),
+        );
+        assert_eq!(empty.morris(10, 4), []);
+        assert_eq!(empty.sobol(10), []);
+    }
+
+    #[test]
+    fn test_degenerate() {
+        // a fixed parameter, and runs that fail for large rates
+        let params = vec![
+            Parameter::new("rate", "m s-1", 0., 2.),
+            Parameter::new("storage", "m", 1., 1.),
+        ];
+        let sensitivity = Sensitivity::new(
+            || Ok(Reservoir::new(1.)),
+            params,
+            |model: &Reservoir| match model.rate[0] > 1.5 {
+                true => Err("failed".into()),
+                false => Ok(model.storage[0]),
+            },
+        )
+        .seed(3);
+        let effects = sensitivity.morris(10, 4);
+        assert!((effects[0].mu - 200.).abs() < 1e-9);
+        assert_eq!(effects[1].mu_star, 0.);
+        let indices = sensitivity.sobol(500);
+        assert!(indices[0].first.is_finite() && indices[0].total.is_finite());
+        assert_eq!(indices[1].total, 0.);
+    }
+
+    #[test]
+    fn test_sobol() {
+        let indices = sensitivity().sobol(2000);
+        // analytic first and total order indices, var(100 rate) / (var(100 rate) + var(storage))
+        let expected = 40000. / 12. / (40000. / 12. + 100. / 12.);
+        assert!((indices[0].first - expected).abs() < 0.05);
+        assert!((indices[0].total - expected).abs() < 0.05);
+        assert!(indices[1].total < 0.01);
+        assert_eq!(
+            indices[2],
+            SobolIndices {
+                first: 0.,
+                total: 0.
+            }
+        );
+    }
+}
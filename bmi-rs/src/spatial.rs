@@ -0,0 +1,80 @@
+use crate::{Bmi, GridType, Location};
+
+/// Spatial dimensions of a variable in file output, see [`spatial`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Spatial {
+    /// The grid's type, `None` if the model does not report it.
+    pub grid_type: Option<GridType>,
+    /// Dimension names and lengths, slowest varying first.
+    pub dims: Vec<(String, usize)>,
+    /// `true` if the dimensions are the axes of `get_grid_shape`, see [`AXES`].
+    pub shaped: bool,
+}
+
+/// Axis names of rank 3 grids. Lower rank grids use the trailing names.
+pub(crate) const AXES: [&str; 3] = ["z", "y", "x"];
+
+/// Return `name`, suffixed with `_<grid>` if `suffix`.
+pub(crate) fn suffixed(name: &str, grid: i32, suffix: bool) -> String {
+    match suffix {
+        true => format!("{name}_{grid}"),
+        false => name.to_string(),
+    }
+}
+
+/// Return the spatial dimensions of a `len` value variable on `grid` at `location`, with
+/// names suffixed by the grid id if `suffix`.
+///
+/// Node variables on rectilinear and structured quadrilateral grids are shaped by
+/// `get_grid_shape` with [`AXES`] dimensions (prefixed with `n` on structured quadrilateral
+/// grids, whose coordinates are not separable), single values on scalar grids have no
+/// spatial dimensions, and all other variables are flattened to a `node`, `edge`, or `face`
+/// dimension.
+pub(crate) fn spatial<M: Bmi>(
+    model: &M,
+    grid: i32,
+    location: Location,
+    len: usize,
+    suffix: bool,
+) -> Spatial {
+    let grid_type = model.get_grid_type(grid).ok();
+    let shape: Option<Vec<usize>> = model
+        .get_grid_shape(grid)
+        .ok()
+        .map(|shape| shape.iter().map(|v| *v as usize).collect())
+        .filter(|shape: &Vec<usize>| {
+            (1..=3).contains(&shape.len()) && shape.iter().product::<usize>() == len
+        });
+    let (dims, shaped) = match (location, grid_type, shape) {
+        (_, None | Some(GridType::Scalar), _) if len == 1 => (Vec::new(), false),
+        (
+            Location::Node,
+            Some(
+                grid_type @ (GridType::UniformRectilinear
+                | GridType::Rectilinear
+                | GridType::StructuredQuadrilateral),
+            ),
+            Some(shape),
+        ) => {
+            let prefix = match grid_type {
+                GridType::StructuredQuadrilateral => "n",
+                _ => "",
+            };
+            let dims = AXES[3 - shape.len()..]
+                .iter()
+                .zip(shape)
+                .map(|(axis, len)| (suffixed(&format!("{prefix}{axis}"), grid, suffix), len))
+                .collect();
+            (dims, true)
+        }
+        _ => (
+            vec![(suffixed(&location.to_string(), grid, suffix), len)],
+            false,
+        ),
+    };
+    Spatial {
+        grid_type,
+        dims,
+        shaped,
+    }
+}
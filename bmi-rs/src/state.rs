@@ -0,0 +1,607 @@
+use crate::bmi::{Sealed, forward_grid_funcs};
+use crate::errors::BmiInvalidValue;
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values};
+use std::error::Error;
+use std::fmt;
+
+/// Leading bytes of a state blob.
+pub const MAGIC: [u8; 4] = *b"BMIS";
+/// Version of the state blob layout (not of a model's state, see [`BmiState::STATE_VERSION`]).
+pub const FORMAT_VERSION: u32 = 1;
+/// Length in bytes of a state blob header: magic, format version, state version, reserved,
+/// and payload length.
+pub const HEADER_LEN: usize = 24;
+/// Maximum length in bytes of a state blob, the largest multiple of 8 a bmi-c
+/// `get_var_nbytes` can report.
+pub const MAX_BLOB_LEN: usize = i32::MAX as usize & !7;
+
+/// ngen convention variable set to any value to serialize the model's state.
+pub const CREATE_VAR: &str = "serialization_create";
+/// ngen convention variable holding the serialized state's length in bytes.
+pub const SIZE_VAR: &str = "serialization_size";
+/// ngen convention variable holding the serialized state, or set to restore it.
+pub const STATE_VAR: &str = "serialization_state";
+/// ngen convention variable set to any value to free the serialized state.
+pub const FREE_VAR: &str = "serialization_free";
+
+/// State blob errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The blob does not start with [`MAGIC`].
+    Magic,
+    /// The blob's layout version is not [`FORMAT_VERSION`].
+    Format(u32),
+    /// The blob's state version is not the model's [`BmiState::STATE_VERSION`].
+    Version { expected: u32, found: u32 },
+    /// The blob or a field ends early.
+    Truncated,
+    /// The blob's length exceeds [`MAX_BLOB_LEN`].
+    TooLarge(usize),
+    /// The payload has unread bytes.
+    Trailing(usize),
+    /// A string field is not UTF-8.
+    Utf8,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Magic => write!(f, "not a bmi-rs state blob"),
+            StateError::Format(found) => write!(f, "unsupported state blob format {found}"),
+            StateError::Version { expected, found } => {
+                write!(f, "state version {found}, expected {expected}")
+            }
+            StateError::Truncated => write!(f, "state blob is truncated"),
+            StateError::TooLarge(len) => {
+                write!(f, "state blob length {len} exceeds {MAX_BLOB_LEN} bytes")
+            }
+            StateError::Trailing(n) => write!(f, "state blob has {n} unread bytes"),
+            StateError::Utf8 => write!(f, "state string is not utf-8"),
+        }
+    }
+}
+
+impl Error for StateError {}
+
+/// Parse a state blob header, returning the state version and payload length.
+fn read_header(header: &[u8]) -> Result<(u32, usize), StateError> {
+    if header.len() < HEADER_LEN {
+        return Err(StateError::Truncated);
+    }
+    if header[..4] != MAGIC {
+        return Err(StateError::Magic);
+    }
+    let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+    if u32_at(4) != FORMAT_VERSION {
+        return Err(StateError::Format(u32_at(4)));
+    }
+    let len = u64::from_le_bytes(header[16..24].try_into().unwrap());
+    if len > (MAX_BLOB_LEN - HEADER_LEN) as u64 {
+        return Err(StateError::TooLarge(len as usize));
+    }
+    Ok((u32_at(8), len as usize))
+}
+
+/// Return the length of the state blob at `ptr` in `u64` words, at most
+/// [`MAX_BLOB_LEN`] / 8. The header's magic and format version are checked first.
+///
+/// # Safety
+/// `ptr` must point to at least [`HEADER_LEN`] readable bytes, and to as many bytes as the
+/// header's payload length claims for the returned length to be readable.
+pub(crate) unsafe fn blob_words(ptr: *const u8) -> Result<usize, StateError> {
+    let header = unsafe { std::slice::from_raw_parts(ptr, HEADER_LEN) };
+    let (_, len) = read_header(header)?;
+    Ok((HEADER_LEN + len).div_ceil(8))
+}
+
+/// Checkpoint and restart a model's state as a versioned byte blob.
+///
+/// A blob is a [`HEADER_LEN`] byte little-endian header ([`MAGIC`], [`FORMAT_VERSION`],
+/// [`STATE_VERSION`], a reserved `u32`, and the payload length as a `u64`), the payload
+/// written by [`write_state`], and zero padding to a multiple of 8 bytes.
+///
+/// Plain-data models can derive an implementation with [`impl_bmi_state!`].
+/// Wrap a model in [`Stateful`] to expose its state over ffi using the ngen
+/// `serialization_*` variable convention.
+///
+/// [`STATE_VERSION`]: BmiState::STATE_VERSION
+/// [`write_state`]: BmiState::write_state
+/// [`impl_bmi_state!`]: crate::impl_bmi_state
+pub trait BmiState {
+    /// Version of the model's state payload. Increment when the payload layout changes.
+    const STATE_VERSION: u32 = 1;
+
+    /// Append the model's state payload to `buf`.
+    fn write_state(&self, buf: &mut Vec<u8>) -> BmiResult<()>;
+
+    /// Restore the model's state from a payload of state `version`.
+    fn read_state(&mut self, version: u32, src: &[u8]) -> BmiResult<()>;
+
+    /// Serialize the model's state as a blob.
+    fn save_state(&self) -> BmiResult<Vec<u8>> {
+        let mut buf = Vec::with_capacity(HEADER_LEN);
+        buf.extend(MAGIC);
+        buf.extend(FORMAT_VERSION.to_le_bytes());
+        buf.extend(Self::STATE_VERSION.to_le_bytes());
+        buf.extend(0u32.to_le_bytes());
+        buf.extend(0u64.to_le_bytes());
+        self.write_state(&mut buf)?;
+        if buf.len() > MAX_BLOB_LEN {
+            return Err(Box::new(StateError::TooLarge(buf.len())));
+        }
+        let len = (buf.len() - HEADER_LEN) as u64;
+        buf[16..24].copy_from_slice(&len.to_le_bytes());
+        buf.resize(buf.len().next_multiple_of(8), 0);
+        Ok(buf)
+    }
+
+    /// Restore the model's state from a blob created by [`save_state`].
+    ///
+    /// [`save_state`]: BmiState::save_state
+    fn load_state(&mut self, state: &[u8]) -> BmiResult<()> {
+        let (version, len) = read_header(state)?;
+        let Some(payload) = state[HEADER_LEN..].get(..len) else {
+            return Err(Box::new(StateError::Truncated));
+        };
+        self.read_state(version, payload)
+    }
+}
+
+/// Field of a plain-data model's state, see [`impl_bmi_state!`].
+///
+/// [`impl_bmi_state!`]: crate::impl_bmi_state
+pub trait StateField {
+    /// Append the field to `buf`.
+    fn write_field(&self, buf: &mut Vec<u8>);
+
+    /// Read the field from the start of `src`, advancing `src` past it.
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError>;
+}
+
+fn take<'a>(src: &mut &'a [u8], n: usize) -> Result<&'a [u8], StateError> {
+    if src.len() < n {
+        return Err(StateError::Truncated);
+    }
+    let (head, tail) = src.split_at(n);
+    *src = tail;
+    Ok(head)
+}
+
+macro_rules! impl_state_field {
+    ($($t:ty),*$(,)?) => {
+        $(
+        impl StateField for $t {
+            fn write_field(&self, buf: &mut Vec<u8>) {
+                buf.extend(self.to_le_bytes());
+            }
+
+            fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+                let bytes = take(src, size_of::<$t>())?;
+                *self = <$t>::from_le_bytes(bytes.try_into().unwrap());
+                Ok(())
+            }
+        }
+        )*
+    };
+}
+
+impl_state_field!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+impl StateField for usize {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        (*self as u64).write_field(buf);
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        let mut value = 0u64;
+        value.read_field(src)?;
+        *self = value as usize;
+        Ok(())
+    }
+}
+
+impl StateField for bool {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        *self = take(src, 1)?[0] != 0;
+        Ok(())
+    }
+}
+
+impl StateField for String {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        self.len().write_field(buf);
+        buf.extend(self.as_bytes());
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        let mut len = 0usize;
+        len.read_field(src)?;
+        let bytes = take(src, len)?;
+        *self = String::from_utf8(bytes.to_vec()).map_err(|_| StateError::Utf8)?;
+        Ok(())
+    }
+}
+
+impl<T: StateField + Default> StateField for Vec<T> {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        self.len().write_field(buf);
+        self.iter().for_each(|item| item.write_field(buf));
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        let mut len = 0usize;
+        len.read_field(src)?;
+        // each item is at least a byte, guard against allocating for a corrupt length
+        if len > src.len() {
+            return Err(StateError::Truncated);
+        }
+        self.clear();
+        self.resize_with(len, T::default);
+        self.iter_mut().try_for_each(|item| item.read_field(src))
+    }
+}
+
+impl<T: StateField, const N: usize> StateField for [T; N] {
+    fn write_field(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|item| item.write_field(buf));
+    }
+
+    fn read_field(&mut self, src: &mut &[u8]) -> Result<(), StateError> {
+        self.iter_mut().try_for_each(|item| item.read_field(src))
+    }
+}
+
+/// Implement [`BmiState`] for a plain-data model by serializing the listed fields, in order.
+///
+/// Each field must implement [`StateField`], which is implemented for numeric primitives,
+/// `bool`, `String`, [`Clock`], and `Vec`s and arrays of them.
+/// Loading a blob of a different state version fails.
+///
+/// Example:
+/// ```
+/// use bmi_rs::Clock;
+/// use bmi_rs::state::BmiState;
+///
+/// struct Bucket {
+///     clock: Clock,
+///     storage: Vec<f64>,
+///     area: f64,
+/// }
+///
+/// // `area` is a parameter read from config, it is not part of the state.
+/// bmi_rs::impl_bmi_state!(Bucket, version = 2; clock, storage);
+///
+/// let mut bucket = Bucket { clock: Clock::new(0., 10., 1.), storage: vec![1.], area: 5. };
+/// let state = bucket.save_state().unwrap();
+/// bucket.clock.advance();
+/// bucket.storage[0] = 2.;
+/// bucket.load_state(&state).unwrap();
+/// assert_eq!(bucket.clock.current_time(), 0.);
+/// assert_eq!(bucket.storage, [1.]);
+/// ```
+///
+/// [`Clock`]: crate::Clock
+#[macro_export]
+macro_rules! impl_bmi_state {
+    ($model:ty, version = $version:expr; $($field:ident),*$(,)?) => {
+        impl $crate::state::BmiState for $model {
+            const STATE_VERSION: u32 = $version;
+
+            fn write_state(&self, buf: &mut Vec<u8>) -> $crate::BmiResult<()> {
+                $($crate::state::StateField::write_field(&self.$field, buf);)*
+                Ok(())
+            }
+
+            fn read_state(&mut self, version: u32, src: &[u8]) -> $crate::BmiResult<()> {
+                if version != Self::STATE_VERSION {
+                    return Err(Box::new($crate::state::StateError::Version {
+                        expected: Self::STATE_VERSION,
+                        found: version,
+                    }));
+                }
+                let mut src = src;
+                $($crate::state::StateField::read_field(&mut self.$field, &mut src)?;)*
+                if !src.is_empty() {
+                    return Err(Box::new($crate::state::StateError::Trailing(src.len())));
+                }
+                Ok(())
+            }
+        }
+    };
+    ($model:ty; $($field:ident),*$(,)?) => {
+        $crate::impl_bmi_state!($model, version = 1; $($field),*);
+    };
+}
+
+/// [`Bmi`] adapter that exposes a [`BmiState`] model's state through the ngen
+/// `serialization_*` variable convention:
+///
+/// - set [`CREATE_VAR`] to any value to serialize the model's state.
+/// - [`SIZE_VAR`] holds the state blob's length in bytes (`u64`).
+/// - [`STATE_VAR`] holds the state blob. Set it to a blob to restore the model's state.
+/// - set [`FREE_VAR`] to any value to free the state blob.
+///
+/// [`ValueType`] has no byte type, so [`STATE_VAR`] is typed as `u64` words whose native
+/// endian bytes are the blob. Blobs are a multiple of 8 bytes long.
+/// The variables are not listed in the model's input or output variable names.
+///
+/// Example:
+/// ```ignore
+/// bmi_rs::register_model(handle, Stateful::new(model));
+/// ```
+pub struct Stateful<M: Bmi + BmiState> {
+    model: M,
+    state: Vec<u64>,
+    size: [u64; 1],
+}
+
+impl<M: Bmi + BmiState> Stateful<M> {
+    /// Wrap `model`, adding the `serialization_*` variables.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            state: Vec::new(),
+            size: [0],
+        }
+    }
+
+    /// Return the wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    /// Unwrap the model, dropping any serialized state.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    fn is_state_var(name: &str) -> bool {
+        matches!(name, CREATE_VAR | SIZE_VAR | STATE_VAR | FREE_VAR)
+    }
+}
+
+impl<M: Bmi + BmiState> Bmi for Stateful<M> {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        self.model.initialize(config_file)
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        self.model.update()
+    }
+
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        self.model.update_until(then)
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        self.model.finalize()
+    }
+
+    fn get_component_name(&self) -> &str {
+        self.model.get_component_name()
+    }
+
+    fn get_input_item_count(&self) -> u32 {
+        self.model.get_input_item_count()
+    }
+
+    fn get_output_item_count(&self) -> u32 {
+        self.model.get_output_item_count()
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        self.model.get_input_var_names()
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.model.get_output_var_names()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        match Self::is_state_var(name) {
+            true => BmiInvalidValue.into(),
+            false => self.model.get_var_grid(name),
+        }
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        match name {
+            CREATE_VAR | FREE_VAR => Ok(ValueType::I32),
+            SIZE_VAR | STATE_VAR => Ok(ValueType::U64),
+            _ => self.model.get_var_type(name),
+        }
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        match Self::is_state_var(name) {
+            true => Ok(""),
+            false => self.model.get_var_units(name),
+        }
+    }
+
+    fn get_var_itemsize(&self, name: &str) -> BmiResult<u32> {
+        match Self::is_state_var(name) {
+            true => Ok(self.get_var_type(name)?.bytes() as u32),
+            false => self.model.get_var_itemsize(name),
+        }
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        match name {
+            CREATE_VAR | FREE_VAR => Ok(4),
+            SIZE_VAR => Ok(8),
+            STATE_VAR => Ok(self.size[0] as u32),
+            _ => self.model.get_var_nbytes(name),
+        }
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        match Self::is_state_var(name) {
+            true => Ok(Location::Node),
+            false => self.model.get_var_location(name),
+        }
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.model.get_current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.model.get_start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.model.get_end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        self.model.get_time_units()
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.model.get_time_step()
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        match name {
+            CREATE_VAR | FREE_VAR => Ok(RefValues::I32(&[0])),
+            SIZE_VAR => Ok(RefValues::U64(&self.size)),
+            STATE_VAR => Ok(RefValues::U64(&self.state)),
+            _ => self.model.get_value_ptr(name),
+        }
+    }
+
+    fn get_value_at_indices(&self, name: &str, inds: &[u32]) -> BmiResult<Values> {
+        match Self::is_state_var(name) {
+            true => BmiInvalidValue.into(),
+            false => self.model.get_value_at_indices(name, inds),
+        }
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        match name {
+            CREATE_VAR => {
+                let blob = self.model.save_state()?;
+                self.size = [blob.len() as u64];
+                self.state = blob
+                    .chunks(8)
+                    .map(|word| u64::from_ne_bytes(word.try_into().unwrap()))
+                    .collect();
+                Ok(())
+            }
+            FREE_VAR => {
+                self.state = Vec::new();
+                self.size = [0];
+                Ok(())
+            }
+            STATE_VAR => {
+                let RefValues::U64(words) = src else {
+                    return BmiInvalidValue.into();
+                };
+                let blob: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+                self.model.load_state(&blob)
+            }
+            SIZE_VAR => BmiInvalidValue.into(),
+            _ => self.model.set_value(name, src),
+        }
+    }
+
+    fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()> {
+        match Self::is_state_var(name) {
+            true => BmiInvalidValue.into(),
+            false => self.model.set_value_at_indices(name, inds, src),
+        }
+    }
+
+    fn serializes_state(&self, _: Sealed) -> bool {
+        true
+    }
+
+    forward_grid_funcs!(model);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Reservoir;
+
+    crate::impl_bmi_state!(Reservoir, version = 3; clock, storage, flux, updates);
+
+    fn run(model: &mut impl Bmi, steps: usize) {
+        model.set_value("rate", RefValues::F64(&[1.])).unwrap();
+        (0..steps).for_each(|_| model.update().unwrap());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut model = Reservoir::new(1.);
+        run(&mut model, 3);
+        let state = model.save_state().unwrap();
+        assert_eq!(state.len() % 8, 0);
+        assert_eq!(&state[..4], b"BMIS");
+
+        run(&mut model, 2);
+        model.load_state(&state).unwrap();
+        assert_eq!(model.get_current_time(), 3.);
+        assert_eq!(model.storage, [3.]);
+        assert_eq!(model.updates, 3);
+    }
+
+    #[test]
+    fn test_invalid_blob() {
+        let mut model = Reservoir::new(1.);
+        let mut state = model.save_state().unwrap();
+        assert!(model.load_state(&state[..HEADER_LEN - 1]).is_err());
+        assert!(model.load_state(&state[..state.len() - 8]).is_err());
+
+        state[8] = 1;
+        let err = model.load_state(&state).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<StateError>(),
+            Some(&StateError::Version {
+                expected: 3,
+                found: 1
+            })
+        );
+        let mut large = state.clone();
+        large[16..24].copy_from_slice(&(MAX_BLOB_LEN as u64).to_le_bytes());
+        let err = model.load_state(&large).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<StateError>(),
+            Some(&StateError::TooLarge(MAX_BLOB_LEN))
+        );
+        state[0] = b'X';
+        let err = model.load_state(&state).unwrap_err();
+        assert_eq!(err.downcast_ref::<StateError>(), Some(&StateError::Magic));
+    }
+
+    #[test]
+    fn test_stateful_vars() {
+        let mut model = Stateful::new(Reservoir::new(1.));
+        run(&mut model, 2);
+        model.set_value(CREATE_VAR, RefValues::I32(&[1])).unwrap();
+        let size = model.get_value_ptr(SIZE_VAR).unwrap().to_f64_vec()[0] as u32;
+        assert_eq!(model.get_var_nbytes(STATE_VAR).unwrap(), size);
+        let state = match model.get_value_ptr(STATE_VAR).unwrap() {
+            RefValues::U64(words) => words.to_vec(),
+            _ => panic!("expected u64 words"),
+        };
+        assert_eq!(state.len() * 8, size as usize);
+        let words = unsafe { blob_words(state.as_ptr() as *const u8) }.unwrap();
+        assert_eq!(words, state.len());
+
+        run(&mut model, 2);
+        model.set_value(FREE_VAR, RefValues::I32(&[1])).unwrap();
+        assert_eq!(model.get_var_nbytes(STATE_VAR).unwrap(), 0);
+        model.set_value(STATE_VAR, RefValues::U64(&state)).unwrap();
+        assert_eq!(model.get_current_time(), 2.);
+        assert_eq!(model.inner().storage, [2.]);
+        assert!(!model.get_output_var_names().contains(&STATE_VAR));
+        assert!(model.serializes_state(Sealed));
+        assert!(!model.inner().serializes_state(Sealed));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::errors::BmiNotImplementedError;
+use crate::grid::Grid;
+use crate::{Bmi, BmiResult, Clock, Location, RefValues, ValueType};
+use std::path::PathBuf;
+
+/// Return an empty directory `bmi-rs-<name>-<pid>` in the system temp directory.
+pub(crate) fn tempdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bmi-rs-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Two triangles sharing the edge (1, 2), with all edges and face edges.
+pub(crate) fn triangles() -> Grid {
+    Grid::Unstructured {
+        x: vec![0., 1., 0., 1.],
+        y: vec![0., 0., 1., 1.],
+        z: vec![],
+        edge_nodes: vec![0, 1, 1, 2, 2, 0, 1, 3, 3, 2],
+        face_edges: vec![0, 1, 2, 3, 4, 1],
+        face_nodes: vec![0, 1, 2, 1, 3, 2],
+        nodes_per_face: vec![3, 3],
+    }
+}
+
+/// Minimal scalar model used in unit tests.
+///
+/// Each `update` adds `rate * dt` to `storage` and records the last applied `rate` in `flux`.
+pub(crate) struct Reservoir {
+    pub clock: Clock,
+    pub rate: Vec<f64>,
+    pub storage: Vec<f64>,
+    pub flux: Vec<f64>,
+    pub updates: usize,
+}
+
+impl Reservoir {
+    pub fn new(dt: f64) -> Self {
+        Self {
+            clock: Clock::new(0., 100. * dt, dt),
+            rate: vec![0.],
+            storage: vec![0.],
+            flux: vec![0.],
+            updates: 0,
+        }
+    }
+}
+
+impl Bmi for Reservoir {
+    fn initialize(&mut self, _config_file: &str) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        self.storage[0] += self.rate[0] * self.clock.time_step();
+        self.flux[0] = self.rate[0];
+        self.updates += 1;
+        self.clock.advance();
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn get_component_name(&self) -> &str {
+        "reservoir"
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        &["rate"]
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        &["storage", "flux"]
+    }
+
+    fn get_var_grid(&self, _name: &str) -> BmiResult<i32> {
+        Ok(0)
+    }
+
+    fn get_var_type(&self, _name: &str) -> BmiResult<ValueType> {
+        Ok(ValueType::F64)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        match name {
+            "rate" | "flux" => Ok("m s-1"),
+            "storage" => Ok("m"),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        Ok((self.get_value_ptr(name)?.len() * 8) as u32)
+    }
+
+    fn get_var_location(&self, _name: &str) -> BmiResult<Location> {
+        Ok(Location::Node)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.clock.current_time()
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.clock.start_time()
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.clock.end_time()
+    }
+
+    fn get_time_units(&self) -> &str {
+        "s"
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.clock.time_step()
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        match name {
+            "rate" => Ok((&self.rate).into()),
+            "storage" => Ok((&self.storage).into()),
+            "flux" => Ok((&self.flux).into()),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        let dest = match name {
+            "rate" => &mut self.rate,
+            "storage" => &mut self.storage,
+            "flux" => &mut self.flux,
+            _ => return BmiNotImplementedError.into(),
+        };
+        match src {
+            RefValues::F64(src) => {
+                dest.copy_from_slice(src);
+                Ok(())
+            }
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    fn set_value_at_indices(
+        &mut self,
+        _name: &str,
+        _inds: &[u32],
+        _src: RefValues,
+    ) -> BmiResult<()> {
+        BmiNotImplementedError.into()
+    }
+
+    fn get_grid_type(&self, _grid: i32) -> BmiResult<crate::GridType> {
+        Ok(crate::GridType::Scalar)
+    }
+
+    fn get_grid_rank(&self, _grid: i32) -> BmiResult<u32> {
+        Ok(0)
+    }
+
+    fn get_grid_size(&self, _grid: i32) -> BmiResult<u32> {
+        Ok(1)
+    }
+}
+
+/// Static model on the [`triangles`] grid with a node, an edge, and a face variable.
+pub(crate) struct Mesh {
+    pub grid: Grid,
+    /// node variable
+    pub level: Vec<f64>,
+    /// edge variable
+    pub flow: Vec<f64>,
+    /// face variable, `NaN` on the second face
+    pub depth: Vec<f64>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self {
+            grid: triangles(),
+            level: vec![0., 1., 2., 3.],
+            flow: vec![0.5, 1., 1.5, 2., 2.5],
+            depth: vec![1.5, f64::NAN],
+        }
+    }
+}
+
+impl Bmi for Mesh {
+    fn initialize(&mut self, _config_file: &str) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        Ok(())
+    }
+
+    fn get_component_name(&self) -> &str {
+        "mesh"
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        &[]
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        &["level", "flow", "depth"]
+    }
+
+    fn get_var_grid(&self, _name: &str) -> BmiResult<i32> {
+        Ok(0)
+    }
+
+    fn get_var_type(&self, _name: &str) -> BmiResult<ValueType> {
+        Ok(ValueType::F64)
+    }
+
+    fn get_var_units(&self, _name: &str) -> BmiResult<&str> {
+        Ok("m")
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        Ok((self.get_value_ptr(name)?.len() * 8) as u32)
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        match name {
+            "level" => Ok(Location::Node),
+            "flow" => Ok(Location::Edge),
+            "depth" => Ok(Location::Face),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    fn get_current_time(&self) -> f64 {
+        0.
+    }
+
+    fn get_start_time(&self) -> f64 {
+        0.
+    }
+
+    fn get_end_time(&self) -> f64 {
+        0.
+    }
+
+    fn get_time_units(&self) -> &str {
+        "s"
+    }
+
+    fn get_time_step(&self) -> f64 {
+        1.
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        match name {
+            "level" => Ok((&self.level).into()),
+            "flow" => Ok((&self.flow).into()),
+            "depth" => Ok((&self.depth).into()),
+            _ => BmiNotImplementedError.into(),
+        }
+    }
+
+    fn set_value(&mut self, _name: &str, _src: RefValues) -> BmiResult<()> {
+        BmiNotImplementedError.into()
+    }
+
+    fn set_value_at_indices(
+        &mut self,
+        _name: &str,
+        _inds: &[u32],
+        _src: RefValues,
+    ) -> BmiResult<()> {
+        BmiNotImplementedError.into()
+    }
+
+    fn get_grid(&self, _grid: i32) -> BmiResult<&Grid> {
+        Ok(&self.grid)
+    }
+}
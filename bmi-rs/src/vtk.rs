@@ -0,0 +1,649 @@
+use crate::errors::{BmiInvalidValue, BmiNotImplementedError, OutputError};
+use crate::grid::Grid;
+use crate::{Bmi, BmiResult, GridType, Location, RefValues, ValueType};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// VTK cell types
+const VTK_VERTEX: u8 = 1;
+const VTK_LINE: u8 = 3;
+const VTK_TRIANGLE: u8 = 5;
+const VTK_POLYGON: u8 = 7;
+const VTK_QUAD: u8 = 9;
+
+fn vtk_type(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::I16 => "Int16",
+        ValueType::U16 => "UInt16",
+        ValueType::I32 => "Int32",
+        ValueType::U32 => "UInt32",
+        ValueType::I64 => "Int64",
+        ValueType::U64 => "UInt64",
+        ValueType::F32 => "Float32",
+        ValueType::F64 => "Float64",
+    }
+}
+
+fn le_bytes(values: &RefValues) -> Vec<u8> {
+    macro_rules! le_bytes {
+        ($($name:ident),*$(,)?) => {
+            match values {
+                $(RefValues::$name(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),)*
+            }
+        };
+    }
+    le_bytes!(I16, U16, I32, U32, I64, U64, F32, F64)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Append a `binary` (base64, `UInt64` length header) data array.
+fn data_array(xml: &mut String, name: &str, vtk_type: &str, components: usize, bytes: &[u8]) {
+    let mut block = (bytes.len() as u64).to_le_bytes().to_vec();
+    block.extend(bytes);
+    let _ = writeln!(
+        xml,
+        r#"<DataArray type="{vtk_type}" Name="{}" NumberOfComponents="{components}" format="binary">{}</DataArray>"#,
+        escape(name),
+        base64(&block)
+    );
+}
+
+fn f64_bytes(values: impl IntoIterator<Item = f64>) -> Vec<u8> {
+    values.into_iter().flat_map(f64::to_le_bytes).collect()
+}
+
+/// Interleaved x, y, z point coordinates, `z` 0 if empty.
+///
+/// Returns Err([`BmiInvalidValue`]) if `y`, or a non-empty `z`, is not as long as `x`.
+fn points(xml: &mut String, x: &[f64], y: &[f64], z: &[f64]) -> BmiResult<()> {
+    if y.len() != x.len() || !(z.is_empty() || z.len() == x.len()) {
+        return BmiInvalidValue.into();
+    }
+    let xyz = (0..x.len()).flat_map(|i| [x[i], y[i], z.get(i).copied().unwrap_or_default()]);
+    xml.push_str("<Points>\n");
+    data_array(xml, "Points", "Float64", 3, &f64_bytes(xyz));
+    xml.push_str("</Points>\n");
+    Ok(())
+}
+
+/// Unstructured cells from node lists.
+fn cells(xml: &mut String, cells: &[(&[u32], u8)]) {
+    let connectivity = cells
+        .iter()
+        .flat_map(|(nodes, _)| nodes.iter())
+        .map(|i| *i as i64);
+    let offsets = cells.iter().scan(0i64, |offset, (nodes, _)| {
+        *offset += nodes.len() as i64;
+        Some(*offset)
+    });
+    let connectivity: Vec<u8> = connectivity.flat_map(i64::to_le_bytes).collect();
+    let offsets: Vec<u8> = offsets.flat_map(i64::to_le_bytes).collect();
+    let types: Vec<u8> = cells.iter().map(|(_, cell_type)| *cell_type).collect();
+    xml.push_str("<Cells>\n");
+    data_array(xml, "connectivity", "Int64", 1, &connectivity);
+    data_array(xml, "offsets", "Int64", 1, &offsets);
+    data_array(xml, "types", "UInt8", 1, &types);
+    xml.push_str("</Cells>\n");
+}
+
+/// A grid as a VTK XML dataset, without data arrays.
+#[derive(Debug, Clone)]
+struct Dataset {
+    /// `RectilinearGrid`, `StructuredGrid`, or `UnstructuredGrid`
+    kind: &'static str,
+    extension: &'static str,
+    /// `WholeExtent` of structured datasets
+    extent: Option<String>,
+    nodes: usize,
+    faces: usize,
+    /// Edges written as line cells after the faces, if any edge variable is written.
+    edges: usize,
+    /// Points, coordinates, and cells
+    geometry: String,
+}
+
+impl Dataset {
+    fn new(grid: &Grid, edges: bool) -> BmiResult<Self> {
+        let mut geometry = String::new();
+        let structured = |shape: &[u32]| -> (String, usize) {
+            // VTK extents are in x, y, z order
+            let mut dims: Vec<u32> = shape.iter().rev().copied().collect();
+            dims.resize(3, 1);
+            let extent = dims.iter().map(|n| format!("0 {}", n.saturating_sub(1)));
+            let faces = dims.iter().filter(|n| **n > 1).map(|n| *n as usize - 1);
+            (extent.collect::<Vec<_>>().join(" "), faces.product())
+        };
+        let dataset = match grid {
+            Grid::UniformRectilinear { .. } | Grid::Rectilinear { .. } => {
+                let Grid::Rectilinear { shape, x, y, z } = grid.to_rectilinear()? else {
+                    return BmiNotImplementedError.into();
+                };
+                let (extent, faces) = structured(&shape);
+                geometry.push_str("<Coordinates>\n");
+                for (name, axis) in [("x", x), ("y", y), ("z", z)] {
+                    let axis = match axis.is_empty() {
+                        true => vec![0.],
+                        false => axis,
+                    };
+                    data_array(&mut geometry, name, "Float64", 1, &f64_bytes(axis));
+                }
+                geometry.push_str("</Coordinates>\n");
+                Dataset {
+                    kind: "RectilinearGrid",
+                    extension: "vtr",
+                    extent: Some(extent),
+                    nodes: grid.size()? as usize,
+                    faces,
+                    edges: 0,
+                    geometry,
+                }
+            }
+            Grid::StructuredQuadrilateral { shape, x, y, z } => {
+                if grid.size()? as usize != x.len() {
+                    return BmiInvalidValue.into();
+                }
+                let (extent, faces) = structured(shape);
+                points(&mut geometry, x, y, z)?;
+                Dataset {
+                    kind: "StructuredGrid",
+                    extension: "vts",
+                    extent: Some(extent),
+                    nodes: x.len(),
+                    faces,
+                    edges: 0,
+                    geometry,
+                }
+            }
+            Grid::Points { x, y, z } => {
+                points(&mut geometry, x, y, z)?;
+                let vertices: Vec<[u32; 1]> = (0..x.len() as u32).map(|i| [i]).collect();
+                let vertices: Vec<(&[u32], u8)> =
+                    vertices.iter().map(|i| (&i[..], VTK_VERTEX)).collect();
+                cells(&mut geometry, &vertices);
+                Dataset {
+                    kind: "UnstructuredGrid",
+                    extension: "vtu",
+                    extent: None,
+                    nodes: x.len(),
+                    faces: 0,
+                    edges: 0,
+                    geometry,
+                }
+            }
+            Grid::Unstructured {
+                x,
+                y,
+                z,
+                edge_nodes,
+                face_nodes,
+                nodes_per_face,
+                ..
+            } => {
+                points(&mut geometry, x, y, z)?;
+                let valid = |nodes: &[u32]| nodes.iter().all(|i| (*i as usize) < x.len());
+                let mut nodes = &face_nodes[..];
+                let mut list: Vec<(&[u32], u8)> = Vec::with_capacity(nodes_per_face.len());
+                for n in nodes_per_face.iter().map(|n| *n as usize) {
+                    if n < 3 || n > nodes.len() {
+                        return BmiInvalidValue.into();
+                    }
+                    let (face, rest) = nodes.split_at(n);
+                    if !valid(face) {
+                        return BmiInvalidValue.into();
+                    }
+                    let cell_type = match n {
+                        3 => VTK_TRIANGLE,
+                        4 => VTK_QUAD,
+                        _ => VTK_POLYGON,
+                    };
+                    list.push((face, cell_type));
+                    nodes = rest;
+                }
+                let faces = list.len();
+                if edges {
+                    if !edge_nodes.len().is_multiple_of(2) || !valid(edge_nodes) {
+                        return BmiInvalidValue.into();
+                    }
+                    list.extend(edge_nodes.chunks_exact(2).map(|edge| (edge, VTK_LINE)));
+                }
+                let edges = list.len() - faces;
+                cells(&mut geometry, &list);
+                Dataset {
+                    kind: "UnstructuredGrid",
+                    extension: "vtu",
+                    extent: None,
+                    nodes: x.len(),
+                    faces,
+                    edges,
+                    geometry,
+                }
+            }
+            Grid::Scalar | Grid::Vector { .. } => return BmiNotImplementedError.into(),
+        };
+        Ok(dataset)
+    }
+
+    /// Return the number of elements at `location`, `None` if the location has no cells.
+    fn count(&self, location: Location, edges: usize) -> Option<usize> {
+        match location {
+            Location::Node => Some(self.nodes),
+            Location::Face if self.extension != "vtu" || self.faces > 0 => Some(self.faces),
+            Location::Edge if edges > 0 => Some(edges),
+            _ => None,
+        }
+    }
+}
+
+/// Writes a [`Bmi`] grid and selected variables as
+/// [VTK XML](https://docs.vtk.org/en/latest/design_documents/VTKFileFormats.html) files for
+/// e.g. ParaView, one file per record tied together by a `.pvd` collection.
+///
+/// The dataset type follows the grid type: uniform rectilinear and rectilinear grids are
+/// written as `.vtr` rectilinear grids, structured quadrilateral grids as `.vts` structured
+/// grids, and unstructured and points grids as `.vtu` unstructured grids of polygons or
+/// vertices. Node variables are written as point data and face variables as cell data, faces
+/// of structured grids being the cells between nodes. Edge variables of unstructured grids
+/// are written as cell data of line cells following the faces, padded with `NaN` as `Float64`
+/// along with any face variables.
+///
+/// Grid geometry is read once, when the writer is created. Each [`record`] writes
+/// `<prefix>_<record>.<extension>` and rewrites `<prefix>.pvd` with
+/// [`get_current_time`] as the time step.
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::vtk::VtkWriter;
+/// # fn run(mut model: impl Bmi) -> bmi_rs::BmiResult<()> {
+///
+/// let grid = model.get_var_grid("depth")?;
+/// let mut writer = VtkWriter::new("out", "run", &model, grid, &["depth", "velocity"])?;
+/// while model.get_current_time() < model.get_end_time() {
+///     model.update()?;
+///     writer.record(&model)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`record`]: VtkWriter::record
+/// [`get_current_time`]: Bmi::get_current_time
+#[derive(Debug, Clone)]
+pub struct VtkWriter {
+    dir: PathBuf,
+    prefix: String,
+    dataset: Dataset,
+    vars: Vec<(String, Location, usize)>,
+    steps: Vec<(f64, String)>,
+}
+
+impl VtkWriter {
+    /// Create a writer of `names` on grid `grid` of an initialized `model`, writing to the
+    /// directory `dir`, created if missing.
+    ///
+    /// Returns Err([`OutputError`]) if a variable is not on `grid` or has no value per element
+    /// at its location, and Err([`BmiInvalidValue`]) if node coordinate lengths disagree with
+    /// each other or the grid's shape, a face has fewer than three nodes, or a face or edge
+    /// node index is out of bounds.
+    pub fn new<M: Bmi>(
+        dir: impl AsRef<Path>,
+        prefix: &str,
+        model: &M,
+        grid: i32,
+        names: &[&str],
+    ) -> BmiResult<Self> {
+        let mut vars = Vec::with_capacity(names.len());
+        for name in names {
+            if model.get_var_grid(name)? != grid {
+                return Err(Box::new(OutputError::Grid {
+                    name: name.to_string(),
+                    grid,
+                }));
+            }
+            let len = model.get_value_ptr(name)?.len();
+            vars.push((name.to_string(), model.get_var_location(name)?, len));
+        }
+        let grid = Grid::from_model(model, grid)?;
+        let edges = vars
+            .iter()
+            .any(|(_, location, _)| *location == Location::Edge);
+        let dataset = Dataset::new(&grid, edges && grid.grid_type() == GridType::Unstructured)?;
+        for (name, location, len) in vars.iter() {
+            let Some(expected) = dataset.count(*location, dataset.edges) else {
+                return Err(Box::new(OutputError::Location {
+                    name: name.clone(),
+                    location: *location,
+                }));
+            };
+            if expected != *len {
+                return Err(Box::new(OutputError::Length {
+                    name: name.clone(),
+                    expected,
+                    found: *len,
+                }));
+            }
+        }
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            dataset,
+            vars,
+            steps: Vec::new(),
+        })
+    }
+
+    /// Return the XML of a record of `model`.
+    fn piece<M: Bmi>(&self, model: &M) -> BmiResult<String> {
+        let dataset = &self.dataset;
+        let mut point_data = String::new();
+        let mut cell_data = String::new();
+        for (name, location, len) in self.vars.iter() {
+            let values = model.get_value_ptr(name)?;
+            if values.len() != *len {
+                return Err(Box::new(OutputError::Length {
+                    name: name.clone(),
+                    expected: *len,
+                    found: values.len(),
+                }));
+            }
+            let vtk_type = vtk_type(values.value_type());
+            match location {
+                Location::Node => {
+                    data_array(&mut point_data, name, vtk_type, 1, &le_bytes(&values))
+                }
+                _ if dataset.edges == 0 => {
+                    data_array(&mut cell_data, name, vtk_type, 1, &le_bytes(&values))
+                }
+                _ => {
+                    // faces then edges, padded with NaN
+                    let (before, after) = match location {
+                        Location::Edge => (dataset.faces, 0),
+                        _ => (0, dataset.edges),
+                    };
+                    let padded = std::iter::repeat_n(f64::NAN, before)
+                        .chain(values.to_f64_vec())
+                        .chain(std::iter::repeat_n(f64::NAN, after));
+                    data_array(&mut cell_data, name, "Float64", 1, &f64_bytes(padded));
+                }
+            }
+        }
+        let mut xml = String::new();
+        let kind = dataset.kind;
+        let _ = writeln!(xml, r#"<?xml version="1.0"?>"#);
+        let _ = writeln!(
+            xml,
+            r#"<VTKFile type="{kind}" version="1.0" byte_order="LittleEndian" header_type="UInt64">"#
+        );
+        match &dataset.extent {
+            Some(extent) => {
+                let _ = writeln!(xml, r#"<{kind} WholeExtent="{extent}">"#);
+                let _ = writeln!(xml, r#"<Piece Extent="{extent}">"#);
+            }
+            None => {
+                let cells = match dataset.faces + dataset.edges {
+                    0 => dataset.nodes,
+                    cells => cells,
+                };
+                let _ = writeln!(xml, "<{kind}>");
+                let _ = writeln!(
+                    xml,
+                    r#"<Piece NumberOfPoints="{}" NumberOfCells="{cells}">"#,
+                    dataset.nodes
+                );
+            }
+        }
+        let _ = write!(xml, "<PointData>\n{point_data}</PointData>\n");
+        let _ = write!(xml, "<CellData>\n{cell_data}</CellData>\n");
+        xml.push_str(&dataset.geometry);
+        let _ = write!(xml, "</Piece>\n</{kind}>\n</VTKFile>\n");
+        Ok(xml)
+    }
+
+    /// Write the selected variables of `model` at its current time, returning the written
+    /// file's path.
+    pub fn record<M: Bmi>(&mut self, model: &M) -> BmiResult<PathBuf> {
+        let file = format!(
+            "{}_{:06}.{}",
+            self.prefix,
+            self.steps.len(),
+            self.dataset.extension
+        );
+        let path = self.dir.join(&file);
+        fs::write(&path, self.piece(model)?)?;
+        self.steps.push((model.get_current_time(), file));
+
+        let mut pvd = String::new();
+        let _ = writeln!(pvd, r#"<?xml version="1.0"?>"#);
+        let _ = writeln!(
+            pvd,
+            r#"<VTKFile type="Collection" version="1.0" byte_order="LittleEndian">"#
+        );
+        pvd.push_str("<Collection>\n");
+        for (time, file) in self.steps.iter() {
+            let _ = writeln!(
+                pvd,
+                r#"<DataSet timestep="{time}" group="" part="0" file="{}"/>"#,
+                escape(file)
+            );
+        }
+        pvd.push_str("</Collection>\n</VTKFile>\n");
+        fs::write(self.dir.join(format!("{}.pvd", self.prefix)), pvd)?;
+        Ok(path)
+    }
+
+    /// Number of records written.
+    pub fn records(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::testing::{Mesh, tempdir, triangles};
+
+    /// Return the decoded payloads of the data arrays named `name`.
+    fn arrays(xml: &str, name: &str) -> Vec<Vec<u8>> {
+        const TABLE: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let tag = format!(r#"Name="{name}""#);
+        xml.lines()
+            .filter(|line| line.contains(&tag))
+            .map(|line| {
+                let data = &line[line.find('>').unwrap() + 1..line.rfind('<').unwrap()];
+                let sextets: Vec<u32> = data
+                    .bytes()
+                    .filter(|b| *b != b'=')
+                    .map(|b| TABLE.iter().position(|t| *t == b).unwrap() as u32)
+                    .collect();
+                let bytes: Vec<u8> = sextets
+                    .chunks(4)
+                    .flat_map(|s| {
+                        let n = s
+                            .iter()
+                            .enumerate()
+                            .map(|(i, v)| v << (18 - 6 * i))
+                            .sum::<u32>();
+                        n.to_be_bytes()[1..s.len()].to_vec()
+                    })
+                    .collect();
+                bytes[8..].to_vec()
+            })
+            .collect()
+    }
+
+    fn f64s(bytes: &[u8]) -> Vec<f64> {
+        bytes
+            .chunks(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_rectilinear() {
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![1., 2.],
+            origin: vec![0., 0.],
+        };
+        let mut model =
+            SyntheticForcing::new(0., 2., 1.)
+                .grid(grid)
+                .variable("a", "1", Signal::Constant(2.));
+        model.initialize("").unwrap();
+        let dir = tempdir("vtk-vtr");
+        let mut writer = VtkWriter::new(&dir, "run", &model, 0, &["a"]).unwrap();
+        writer.record(&model).unwrap();
+        model.update().unwrap();
+        let path = writer.record(&model).unwrap();
+        assert_eq!(path, dir.join("run_000001.vtr"));
+
+        let xml = fs::read_to_string(&path).unwrap();
+        assert!(xml.contains(r#"<RectilinearGrid WholeExtent="0 2 0 1 0 0">"#));
+        assert_eq!(f64s(&arrays(&xml, "x")[0]), [0., 2., 4.]);
+        assert_eq!(f64s(&arrays(&xml, "a")[0]), [2.; 6]);
+        let pvd = fs::read_to_string(dir.join("run.pvd")).unwrap();
+        assert!(pvd.contains(r#"<DataSet timestep="1" group="" part="0" file="run_000001.vtr"/>"#));
+        assert_eq!(writer.records(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unstructured() {
+        let grid = triangles();
+        let dataset = Dataset::new(&grid, true).unwrap();
+        assert_eq!((dataset.nodes, dataset.faces, dataset.edges), (4, 2, 5));
+        let types = &arrays(&dataset.geometry, "types")[0];
+        assert_eq!(types, &[5, 5, 3, 3, 3, 3, 3]);
+        assert_eq!(dataset.count(Location::Face, dataset.edges), Some(2));
+
+        let invalid = |edge_nodes: Vec<u32>, face_nodes: Vec<u32>, nodes_per_face: Vec<u32>| {
+            let grid = Grid::Unstructured {
+                x: vec![0., 1., 0.],
+                y: vec![0., 0., 1.],
+                z: vec![],
+                edge_nodes,
+                face_edges: vec![],
+                face_nodes,
+                nodes_per_face,
+            };
+            Dataset::new(&grid, true)
+                .unwrap_err()
+                .is::<BmiInvalidValue>()
+        };
+        // too few face nodes, out of bounds face node, too small face
+        assert!(invalid(vec![], vec![0, 1, 2], vec![4]));
+        assert!(invalid(vec![], vec![0, 1, 3], vec![3]));
+        assert!(invalid(vec![], vec![0, 1], vec![2]));
+        // out of bounds edge node, odd edge nodes
+        assert!(invalid(vec![0, 3], vec![0, 1, 2], vec![3]));
+        assert!(invalid(vec![0, 1, 2], vec![0, 1, 2], vec![3]));
+        // coordinates shorter than x, or than the shape
+        let short = [
+            Grid::Points {
+                x: vec![0., 1.],
+                y: vec![0.],
+                z: vec![],
+            },
+            Grid::Points {
+                x: vec![0., 1.],
+                y: vec![0., 1.],
+                z: vec![0.],
+            },
+            Grid::StructuredQuadrilateral {
+                shape: vec![2, 2],
+                x: vec![0., 1., 0.],
+                y: vec![0., 0., 1.],
+                z: vec![],
+            },
+        ];
+        for grid in short {
+            let err = Dataset::new(&grid, false).unwrap_err();
+            assert!(err.is::<BmiInvalidValue>());
+        }
+
+        let mut model = SyntheticForcing::new(0., 1., 1.).grid(grid).variable(
+            "depth",
+            "m",
+            Signal::Constant(1.),
+        );
+        model.initialize("").unwrap();
+        let dir = tempdir("vtk-vtu");
+        let mut writer = VtkWriter::new(&dir, "mesh", &model, 0, &["depth"]).unwrap();
+        let xml = fs::read_to_string(writer.record(&model).unwrap()).unwrap();
+        assert!(xml.contains(r#"<Piece NumberOfPoints="4" NumberOfCells="2">"#));
+        assert_eq!(f64s(&arrays(&xml, "depth")[0]), [1.; 4]);
+        let offsets: Vec<i64> = arrays(&xml, "offsets")[0]
+            .chunks(8)
+            .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(offsets, [3, 6]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let err = VtkWriter::new(&dir, "mesh", &model, 1, &["depth"]).unwrap_err();
+        assert!(err.is::<OutputError>());
+        let scalar = SyntheticForcing::new(0., 1., 1.).grid(Grid::Scalar);
+        assert!(VtkWriter::new(&dir, "mesh", &scalar, 0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_cell_data() {
+        // face and edge variables share the cells, faces then edges, padded with NaN
+        let model = Mesh::new();
+        let dir = tempdir("vtk-cells");
+        let names = ["level", "flow", "depth"];
+        let mut writer = VtkWriter::new(&dir, "mesh", &model, 0, &names).unwrap();
+        let xml = fs::read_to_string(writer.record(&model).unwrap()).unwrap();
+        assert!(xml.contains(r#"<Piece NumberOfPoints="4" NumberOfCells="7">"#));
+        let (point_data, cell_data) = xml.split_once("<CellData>").unwrap();
+        assert!(point_data.contains(r#"Name="level""#));
+        assert!(cell_data.contains(r#"Name="flow""#) && cell_data.contains(r#"Name="depth""#));
+        let flow = f64s(&arrays(&xml, "flow")[0]);
+        assert!(flow[..2].iter().all(|v| v.is_nan()));
+        assert_eq!(flow[2..], [0.5, 1., 1.5, 2., 2.5]);
+        let depth = f64s(&arrays(&xml, "depth")[0]);
+        assert_eq!(depth.len(), 7);
+        assert_eq!(depth[0], 1.5);
+        assert!(depth[1..].iter().all(|v| v.is_nan()));
+        let types = &arrays(&xml, "types")[0];
+        assert_eq!(types, &[5, 5, 3, 3, 3, 3, 3]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
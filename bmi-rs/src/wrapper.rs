@@ -1,4 +1,5 @@
-use crate::bmi::{Bmi, RefValues, ValueType, Values};
+use crate::bmi::{Bmi, RefValues, Sealed, ValueType, Values};
+use crate::state;
 use ffi::{BMI_FAILURE, BMI_SUCCESS};
 use std::ffi::{
     CStr, CString, c_char, c_double, c_float, c_int, c_long, c_short, c_uint, c_ulong, c_ushort,
@@ -359,12 +360,23 @@ pub extern "C" fn get_value<T: Bmi>(
 /// See
 /// (#3)[https://github.com/aaraney/bmi-rs/issues/3]
 /// for why this returns `BMI_FAILURE`.
+/// The exception is ngen's `serialization_state` variable of models that serve it, see
+/// [`state::Stateful`].
 #[allow(unused_variables)]
 pub extern "C" fn get_value_ptr<T: Bmi>(
     self_: *mut ffi::Bmi,
     name: *const c_char,
     dest: *mut *mut c_void,
 ) -> c_int {
+    let var_name = as_str_ref_or_fail!(name);
+    let data: &mut T = data_field!(&self_);
+    if var_name == state::STATE_VAR && data.serializes_state(Sealed) {
+        let RefValues::U64(state) = ok_or_fail!(data.get_value_ptr(var_name)) else {
+            return BMI_FAILURE;
+        };
+        unsafe { *dest = state.as_ptr() as *mut c_void };
+        return BMI_SUCCESS;
+    }
     BMI_FAILURE
     /*
     let var_name = as_str_ref_or_fail!(name);
@@ -445,7 +457,14 @@ pub extern "C" fn set_value<T: Bmi>(
     let var_name = as_str_ref_or_fail!(name);
 
     let data: &mut T = data_field!(&self_);
-    let len = ok_or_fail!(data.get_value_ptr(var_name)).len();
+    // NOTE: ngen sets serialized state without its length, read it from the state's header
+    let len = match var_name {
+        state::STATE_VAR if data.serializes_state(Sealed) => {
+            let words = unsafe { state::blob_words(value as *const u8) };
+            ok_or_fail!(words)
+        }
+        _ => ok_or_fail!(data.get_value_ptr(var_name)).len(),
+    };
     let var_type = ok_or_fail!(data.get_var_type(var_name));
 
     let res = match var_type {
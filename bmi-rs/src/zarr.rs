@@ -0,0 +1,833 @@
+use crate::bmi::{Bmi, BmiResult, RefValues, ValueType};
+use crate::json::{Json, json_list, json_string};
+use crate::spatial::spatial;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Zarr store format version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZarrFormat {
+    /// [Zarr v2](https://zarr-specs.readthedocs.io/en/latest/v2/v2.0.html)
+    V2,
+    /// [Zarr v3](https://zarr-specs.readthedocs.io/en/latest/v3/core/index.html)
+    #[default]
+    V3,
+}
+
+/// [`ZarrWriter`] errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZarrError {
+    /// Existing array metadata could not be read.
+    Metadata(PathBuf),
+    /// An existing array's data type, shape, or length does not match the model variable.
+    Mismatch(String),
+    /// A variable's type or length changed since the writer was opened.
+    Changed(String),
+    /// A record's time is not after the last record's time.
+    Time { last: f64, found: f64 },
+    /// A variable is selected twice, is named `time`, or its name is not a valid array name.
+    Name(String),
+}
+
+impl fmt::Display for ZarrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZarrError::Metadata(path) => write!(f, "invalid zarr metadata: {}", path.display()),
+            ZarrError::Mismatch(name) => write!(f, "`{name}` does not match the existing array"),
+            ZarrError::Changed(name) => write!(f, "`{name}` type or length changed"),
+            ZarrError::Time { last, found } => {
+                write!(
+                    f,
+                    "record time {found} is not after last record time {last}"
+                )
+            }
+            ZarrError::Name(name) => write!(f, "`{name}` is not a valid, unique array name"),
+        }
+    }
+}
+
+impl Error for ZarrError {}
+
+fn v2_dtype(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::I16 => "<i2",
+        ValueType::U16 => "<u2",
+        ValueType::I32 => "<i4",
+        ValueType::U32 => "<u4",
+        ValueType::I64 => "<i8",
+        ValueType::U64 => "<u8",
+        ValueType::F32 => "<f4",
+        ValueType::F64 => "<f8",
+    }
+}
+
+fn v3_dtype(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::I16 => "int16",
+        ValueType::U16 => "uint16",
+        ValueType::I32 => "int32",
+        ValueType::U32 => "uint32",
+        ValueType::I64 => "int64",
+        ValueType::U64 => "uint64",
+        ValueType::F32 => "float32",
+        ValueType::F64 => "float64",
+    }
+}
+
+fn fill_value(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::F32 | ValueType::F64 => "\"NaN\"",
+        _ => "0",
+    }
+}
+
+fn fill_bytes(value_type: ValueType) -> Vec<u8> {
+    match value_type {
+        ValueType::F32 => f32::NAN.to_le_bytes().to_vec(),
+        ValueType::F64 => f64::NAN.to_le_bytes().to_vec(),
+        _ => vec![0; value_type.bytes()],
+    }
+}
+
+fn le_bytes(values: &RefValues) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(values.len() * values.value_type().bytes());
+    match values {
+        RefValues::I16(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::U16(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::I32(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::U32(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::I64(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::U64(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::F32(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+        RefValues::F64(v) => v.iter().for_each(|x| buf.extend(x.to_le_bytes())),
+    }
+    buf
+}
+
+/// Copy the `chunk_shape` block at `origin` between a C-order `array` of `shape` and `chunk`.
+/// Elements of the block outside of the array are left untouched.
+fn copy_block(
+    array: &mut [u8],
+    shape: &[usize],
+    chunk: &mut [u8],
+    chunk_shape: &[usize],
+    origin: &[usize],
+    itemsize: usize,
+    to_chunk: bool,
+) {
+    let rank = shape.len();
+    let run = chunk_shape[rank - 1].min(shape[rank - 1] - origin[rank - 1]) * itemsize;
+    let rows: usize = chunk_shape[..rank - 1].iter().product();
+    let mut index = vec![0; rank];
+    for _ in 0..rows {
+        if (0..rank - 1).all(|d| origin[d] + index[d] < shape[d]) {
+            let (mut a, mut c) = (0, 0);
+            for d in 0..rank {
+                a = a * shape[d] + origin[d] + index[d];
+                c = c * chunk_shape[d] + index[d];
+            }
+            let (a, c) = (a * itemsize, c * itemsize);
+            match to_chunk {
+                true => chunk[c..c + run].copy_from_slice(&array[a..a + run]),
+                false => array[a..a + run].copy_from_slice(&chunk[c..c + run]),
+            }
+        }
+        // increment the row index, last dimension excluded
+        for d in (0..rank - 1).rev() {
+            index[d] += 1;
+            if index[d] < chunk_shape[d] {
+                break;
+            }
+            index[d] = 0;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Array {
+    name: String,
+    value_type: ValueType,
+    dims: Vec<String>,
+    /// spatial shape
+    shape: Vec<usize>,
+    /// chunk shape, time first
+    chunks: Vec<usize>,
+    attrs: Vec<(String, String)>,
+    /// current time chunk, shape `[chunks[0], shape..]`
+    buffer: Vec<u8>,
+}
+
+impl Array {
+    fn new(
+        name: &str,
+        value_type: ValueType,
+        dims: Vec<String>,
+        shape: Vec<usize>,
+        chunks: Vec<usize>,
+        attrs: Vec<(String, String)>,
+    ) -> Self {
+        let mut array = Array {
+            name: name.to_string(),
+            value_type,
+            dims,
+            shape,
+            chunks,
+            attrs,
+            buffer: Vec::new(),
+        };
+        array.clear();
+        array
+    }
+
+    fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    fn clear(&mut self) {
+        let n = self.chunks[0] * self.len();
+        self.buffer = fill_bytes(self.value_type).repeat(n);
+    }
+
+    fn metadata(&self, format: ZarrFormat, records: usize) -> String {
+        let shape = json_list(std::iter::once(records).chain(self.shape.iter().copied()));
+        let chunks = json_list(self.chunks.iter());
+        let dims = json_list(self.dims.iter().map(|d| json_string(d)));
+        let mut attrs: Vec<String> = self
+            .attrs
+            .iter()
+            .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+            .collect();
+        let fill = fill_value(self.value_type);
+        match format {
+            ZarrFormat::V2 => {
+                attrs.push(format!("\"_ARRAY_DIMENSIONS\":{dims}"));
+                let zarray = format!(
+                    "{{\"zarr_format\":2,\"shape\":{shape},\"chunks\":{chunks},\"dtype\":\"{}\",\"compressor\":null,\"fill_value\":{fill},\"order\":\"C\",\"filters\":null,\"dimension_separator\":\"/\"}}",
+                    v2_dtype(self.value_type)
+                );
+                let zattrs = format!("{{{}}}", attrs.join(","));
+                format!("{zarray}\n{zattrs}")
+            }
+            ZarrFormat::V3 => format!(
+                "{{\"zarr_format\":3,\"node_type\":\"array\",\"shape\":{shape},\"data_type\":\"{}\",\"chunk_grid\":{{\"name\":\"regular\",\"configuration\":{{\"chunk_shape\":{chunks}}}}},\"chunk_key_encoding\":{{\"name\":\"default\",\"configuration\":{{\"separator\":\"/\"}}}},\"fill_value\":{fill},\"codecs\":[{{\"name\":\"bytes\",\"configuration\":{{\"endian\":\"little\"}}}}],\"attributes\":{{{}}},\"dimension_names\":{dims}}}",
+                v3_dtype(self.value_type),
+                attrs.join(",")
+            ),
+        }
+    }
+
+    /// Chunk origins (in elements, time first) and keys of time chunk `t`.
+    fn chunk_keys(&self, t: usize) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let grid: Vec<usize> = (0..self.shape.len())
+            .map(|d| self.shape[d].div_ceil(self.chunks[d + 1]))
+            .collect();
+        let count: usize = grid.iter().product();
+        let mut keys = Vec::with_capacity(count);
+        let mut index = vec![0; grid.len()];
+        for _ in 0..count {
+            let mut key = vec![t];
+            key.extend(index.iter().copied());
+            let mut origin = vec![0];
+            origin.extend((0..grid.len()).map(|d| index[d] * self.chunks[d + 1]));
+            keys.push((origin, key));
+            for d in (0..grid.len()).rev() {
+                index[d] += 1;
+                if index[d] < grid[d] {
+                    break;
+                }
+                index[d] = 0;
+            }
+        }
+        keys
+    }
+
+    fn chunk_path(&self, root: &Path, format: ZarrFormat, key: &[usize]) -> PathBuf {
+        let mut path = root.join(&self.name);
+        if format == ZarrFormat::V3 {
+            path.push("c");
+        }
+        key.iter().for_each(|k| path.push(k.to_string()));
+        path
+    }
+
+    fn buffer_shape(&self) -> Vec<usize> {
+        std::iter::once(self.chunks[0])
+            .chain(self.shape.iter().copied())
+            .collect()
+    }
+
+    fn write_chunks(&mut self, root: &Path, format: ZarrFormat, t: usize) -> BmiResult<()> {
+        let itemsize = self.value_type.bytes();
+        let shape = self.buffer_shape();
+        let fill = fill_bytes(self.value_type);
+        let chunk_len: usize = self.chunks.iter().product();
+        for (origin, key) in self.chunk_keys(t) {
+            let mut chunk = fill.repeat(chunk_len);
+            copy_block(
+                &mut self.buffer,
+                &shape,
+                &mut chunk,
+                &self.chunks,
+                &origin,
+                itemsize,
+                true,
+            );
+            let path = self.chunk_path(root, format, &key);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, chunk)?;
+        }
+        Ok(())
+    }
+
+    fn read_chunks(&mut self, root: &Path, format: ZarrFormat, t: usize) -> BmiResult<()> {
+        self.clear();
+        let itemsize = self.value_type.bytes();
+        let shape = self.buffer_shape();
+        let chunk_len: usize = self.chunks.iter().product();
+        for (origin, key) in self.chunk_keys(t) {
+            let path = self.chunk_path(root, format, &key);
+            let Ok(mut chunk) = fs::read(&path) else {
+                continue;
+            };
+            if chunk.len() != chunk_len * itemsize {
+                return Err(Box::new(ZarrError::Mismatch(self.name.clone())));
+            }
+            copy_block(
+                &mut self.buffer,
+                &shape,
+                &mut chunk,
+                &self.chunks,
+                &origin,
+                itemsize,
+                false,
+            );
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self, root: &Path, format: ZarrFormat, records: usize) -> BmiResult<()> {
+        let dir = root.join(&self.name);
+        fs::create_dir_all(&dir)?;
+        let metadata = self.metadata(format, records);
+        match format {
+            ZarrFormat::V2 => {
+                let (zarray, zattrs) = metadata.split_once('\n').unwrap();
+                fs::write(dir.join(".zarray"), zarray)?;
+                fs::write(dir.join(".zattrs"), zattrs)?;
+            }
+            ZarrFormat::V3 => fs::write(dir.join("zarr.json"), metadata)?,
+        }
+        Ok(())
+    }
+
+    /// Read the existing array's metadata, adopting its chunk shape.
+    /// Returns the number of records, or None if the array does not exist.
+    fn read_metadata(&mut self, root: &Path, format: ZarrFormat) -> BmiResult<Option<usize>> {
+        let (path, dtype, chunks_key) = match format {
+            ZarrFormat::V2 => ("/.zarray", v2_dtype(self.value_type), &["chunks"][..]),
+            ZarrFormat::V3 => (
+                "/zarr.json",
+                v3_dtype(self.value_type),
+                &["chunk_grid", "configuration", "chunk_shape"][..],
+            ),
+        };
+        let path = root.join(format!("{}{path}", self.name));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = Json::parse(&fs::read_to_string(&path)?);
+        let dtype_key = match format {
+            ZarrFormat::V2 => "dtype",
+            ZarrFormat::V3 => "data_type",
+        };
+        let get = |path: &[&str]| json.as_ref().and_then(|json| json.get(path));
+        let (Some(shape), Some(chunks), Some(found)) = (
+            get(&["shape"]).and_then(Json::as_usizes),
+            get(chunks_key).and_then(Json::as_usizes),
+            get(&[dtype_key]).and_then(Json::as_str),
+        ) else {
+            return Err(Box::new(ZarrError::Metadata(path)));
+        };
+        if shape.is_empty() || chunks.contains(&0) {
+            return Err(Box::new(ZarrError::Metadata(path)));
+        }
+        if found != dtype || shape.len() != chunks.len() || shape[1..] != self.shape {
+            return Err(Box::new(ZarrError::Mismatch(self.name.clone())));
+        }
+        self.chunks = chunks;
+        self.clear();
+        Ok(Some(shape[0]))
+    }
+}
+
+/// Return the first of `names` that is selected twice, clashes with the `time` array, or is
+/// not a single path segment that can be stored next to the group metadata.
+fn invalid_name<'a>(names: &[&'a str]) -> Option<&'a str> {
+    let mut seen = HashSet::new();
+    names.iter().copied().find(|name| {
+        !seen.insert(*name)
+            || *name == "time"
+            || *name == "zarr.json"
+            || name.is_empty()
+            || name.starts_with('.')
+            || name.starts_with("__")
+            || name.contains(['/', '\\'])
+    })
+}
+
+/// [`ZarrWriter`] options.
+#[derive(Debug, Clone)]
+pub struct ZarrOptions {
+    format: ZarrFormat,
+    time_chunk: usize,
+    space_chunk: usize,
+}
+
+impl Default for ZarrOptions {
+    fn default() -> Self {
+        Self {
+            format: ZarrFormat::default(),
+            time_chunk: 1,
+            space_chunk: usize::MAX,
+        }
+    }
+}
+
+impl ZarrOptions {
+    pub fn new(format: ZarrFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+
+    /// Number of records per chunk along the time dimension (default 1).
+    pub fn time_chunk(mut self, records: usize) -> Self {
+        assert!(records > 0, "time chunk must be positive");
+        self.time_chunk = records;
+        self
+    }
+
+    /// Maximum chunk length along each grid dimension (default, a whole grid per chunk).
+    pub fn space_chunk(mut self, len: usize) -> Self {
+        assert!(len > 0, "space chunk must be positive");
+        self.space_chunk = len;
+        self
+    }
+
+    /// Open the store at `path` for `names` of an initialized `model`, see [`ZarrWriter`].
+    ///
+    /// Returns Err([`ZarrError::Name`]) before creating anything if a variable is selected
+    /// twice, is named `time`, or is not a single path segment, e.g. `a/b` or `..`.
+    pub fn open<M: Bmi>(
+        &self,
+        path: impl AsRef<Path>,
+        model: &M,
+        names: &[&str],
+    ) -> BmiResult<ZarrWriter> {
+        ZarrWriter::open(path.as_ref(), model, names, self)
+    }
+}
+
+/// Writes selected [`Bmi`] variables to a local, uncompressed
+/// [Zarr](https://zarr.dev) v2 or v3 store, one record per time step.
+///
+/// Each variable is an array with a leading `time` dimension followed by its grid dimensions
+/// (named as in [`NetcdfWriter`]) and carries `units`, `location`, `grid`, and `grid_type`
+/// attributes. A `time` array holds [`get_current_time`] with [`get_time_units`] as `units`.
+/// Chunks span [`ZarrOptions::time_chunk`] records and up to [`ZarrOptions::space_chunk`]
+/// elements along each grid dimension.
+///
+/// Opening an existing store appends to it, e.g. when a simulation is restarted:
+/// variables must match the existing arrays, existing chunk shapes are kept, and records
+/// must be later than the last stored time. Records are buffered until a time chunk is full;
+/// call [`flush`] or [`finish`] to write a partial chunk.
+///
+/// Example:
+/// ```no_run
+/// use bmi_rs::Bmi;
+/// use bmi_rs::zarr::{ZarrFormat, ZarrOptions};
+/// # fn run(mut model: impl Bmi) -> bmi_rs::BmiResult<()> {
+///
+/// let options = ZarrOptions::new(ZarrFormat::V3).time_chunk(24);
+/// let mut writer = options.open("out.zarr", &model, &["discharge"])?;
+/// while model.get_current_time() < model.get_end_time() {
+///     model.update()?;
+///     writer.record(&model)?;
+/// }
+/// writer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`NetcdfWriter`]: crate::netcdf::NetcdfWriter
+/// [`get_current_time`]: Bmi::get_current_time
+/// [`get_time_units`]: Bmi::get_time_units
+/// [`flush`]: ZarrWriter::flush
+/// [`finish`]: ZarrWriter::finish
+#[derive(Debug)]
+pub struct ZarrWriter {
+    root: PathBuf,
+    format: ZarrFormat,
+    time: Array,
+    arrays: Vec<Array>,
+    records: usize,
+    last_time: Option<f64>,
+}
+
+impl ZarrWriter {
+    fn open<M: Bmi>(
+        root: &Path,
+        model: &M,
+        names: &[&str],
+        options: &ZarrOptions,
+    ) -> BmiResult<Self> {
+        if let Some(name) = invalid_name(names) {
+            return Err(Box::new(ZarrError::Name(name.to_string())));
+        }
+        let format = options.format;
+        let mut time_attrs = Vec::new();
+        if !model.get_time_units().is_empty() {
+            time_attrs.push(("units".to_string(), model.get_time_units().to_string()));
+        }
+        let time = Array::new(
+            "time",
+            ValueType::F64,
+            vec!["time".to_string()],
+            Vec::new(),
+            vec![options.time_chunk],
+            time_attrs,
+        );
+
+        let mut grids = Vec::with_capacity(names.len());
+        for name in names {
+            grids.push(model.get_var_grid(name)?);
+        }
+        let suffix = grids.iter().any(|grid| *grid != grids[0]);
+        let mut arrays = Vec::with_capacity(names.len());
+        for (name, grid) in names.iter().zip(grids) {
+            let location = model.get_var_location(name)?;
+            let len = model.get_value_ptr(name)?.len();
+            let (spatial_dims, shape): (Vec<String>, Vec<usize>) =
+                spatial(model, grid, location, len, suffix)
+                    .dims
+                    .into_iter()
+                    .unzip();
+            let mut dims = vec!["time".to_string()];
+            dims.extend(spatial_dims);
+            let mut chunks = vec![options.time_chunk];
+            chunks.extend(shape.iter().map(|len| (*len).clamp(1, options.space_chunk)));
+            let mut attrs = vec![
+                ("units".to_string(), model.get_var_units(name)?.to_string()),
+                ("location".to_string(), location.to_string()),
+                ("grid".to_string(), grid.to_string()),
+            ];
+            if let Ok(grid_type) = model.get_grid_type(grid) {
+                attrs.push(("grid_type".to_string(), grid_type.to_string()));
+            }
+            let value_type = model.get_var_type(name)?;
+            arrays.push(Array::new(name, value_type, dims, shape, chunks, attrs));
+        }
+
+        let mut writer = Self {
+            root: root.to_path_buf(),
+            format,
+            time,
+            arrays,
+            records: 0,
+            last_time: None,
+        };
+        writer.resume()?;
+        writer.write_metadata()?;
+        Ok(writer)
+    }
+
+    /// Read existing array metadata and the last, partially filled time chunk.
+    fn resume(&mut self) -> BmiResult<()> {
+        let (root, format) = (&self.root, self.format);
+        let Some(records) = self.time.read_metadata(root, format)? else {
+            return Ok(());
+        };
+        let ct = self.time.chunks[0];
+        for array in self.arrays.iter_mut() {
+            if array.read_metadata(root, format)? != Some(records) || array.chunks[0] != ct {
+                return Err(Box::new(ZarrError::Mismatch(array.name.clone())));
+            }
+        }
+        self.records = records;
+        if records == 0 {
+            return Ok(());
+        }
+        let last = records - 1;
+        self.time.read_chunks(root, format, last / ct)?;
+        let at = (last % ct) * 8;
+        self.last_time = Some(f64::from_le_bytes(
+            self.time.buffer[at..at + 8].try_into().unwrap(),
+        ));
+        if records.is_multiple_of(ct) {
+            self.time.clear();
+            return Ok(());
+        }
+        for array in self.arrays.iter_mut() {
+            array.read_chunks(root, format, last / ct)?;
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self) -> BmiResult<()> {
+        fs::create_dir_all(&self.root)?;
+        match self.format {
+            ZarrFormat::V2 => fs::write(self.root.join(".zgroup"), "{\"zarr_format\":2}")?,
+            ZarrFormat::V3 => fs::write(
+                self.root.join("zarr.json"),
+                "{\"zarr_format\":3,\"node_type\":\"group\",\"attributes\":{}}",
+            )?,
+        }
+        self.time
+            .write_metadata(&self.root, self.format, self.records)?;
+        for array in self.arrays.iter() {
+            array.write_metadata(&self.root, self.format, self.records)?;
+        }
+        Ok(())
+    }
+
+    /// Buffer the current time and values of the selected variables of `model` as a record,
+    /// writing chunks when a time chunk is full.
+    pub fn record<M: Bmi>(&mut self, model: &M) -> BmiResult<()> {
+        let time = model.get_current_time();
+        if let Some(last) = self.last_time
+            && time <= last
+        {
+            return Err(Box::new(ZarrError::Time { last, found: time }));
+        }
+        let mut rows = Vec::with_capacity(self.arrays.len());
+        for array in self.arrays.iter() {
+            let values = model.get_value_ptr(&array.name)?;
+            if values.value_type() != array.value_type || values.len() != array.len() {
+                return Err(Box::new(ZarrError::Changed(array.name.clone())));
+            }
+            rows.push(le_bytes(&values));
+        }
+        let slot = self.records % self.time.chunks[0];
+        self.time.buffer[slot * 8..(slot + 1) * 8].copy_from_slice(&time.to_le_bytes());
+        for (array, row) in self.arrays.iter_mut().zip(rows) {
+            array.buffer[slot * row.len()..(slot + 1) * row.len()].copy_from_slice(&row);
+        }
+        self.records += 1;
+        self.last_time = Some(time);
+        if self.records.is_multiple_of(self.time.chunks[0]) {
+            self.write_chunks()?;
+            self.write_metadata()?;
+            self.time.clear();
+            self.arrays.iter_mut().for_each(Array::clear);
+        }
+        Ok(())
+    }
+
+    fn write_chunks(&mut self) -> BmiResult<()> {
+        let t = (self.records - 1) / self.time.chunks[0];
+        self.time.write_chunks(&self.root, self.format, t)?;
+        for array in self.arrays.iter_mut() {
+            array.write_chunks(&self.root, self.format, t)?;
+        }
+        Ok(())
+    }
+
+    /// Write the current, partial time chunk, if any, and update array metadata.
+    pub fn flush(&mut self) -> BmiResult<()> {
+        if !self.records.is_multiple_of(self.time.chunks[0]) {
+            self.write_chunks()?;
+        }
+        self.write_metadata()
+    }
+
+    /// Number of records in the store, including buffered records.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Flush and close the store.
+    pub fn finish(mut self) -> BmiResult<()> {
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forcing::synthetic::{Signal, SyntheticForcing};
+    use crate::grid::Grid;
+    use crate::testing::tempdir;
+
+    fn model() -> SyntheticForcing {
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![1., 1.],
+            origin: vec![0., 0.],
+        };
+        let ramp = Signal::Ramp {
+            value: 0.,
+            start: 0.,
+            slope: 1.,
+        };
+        let mut model = SyntheticForcing::new(0., 100., 1.)
+            .time_units("s")
+            .grid(grid)
+            .variable("ramp", "mm", ramp);
+        model.initialize("").unwrap();
+        model
+    }
+
+    fn f64s(bytes: &[u8]) -> Vec<f64> {
+        bytes
+            .chunks(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    fn metadata(path: &Path) -> Json {
+        Json::parse(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_copy_block() {
+        // 3x3 array of bytes, 2x2 chunk at (2, 2) with a single element in bounds
+        let mut array: Vec<u8> = (0..9).collect();
+        let mut chunk = vec![0xff; 4];
+        copy_block(&mut array, &[3, 3], &mut chunk, &[2, 2], &[2, 2], 1, true);
+        assert_eq!(chunk, [8, 0xff, 0xff, 0xff]);
+        copy_block(&mut array, &[3, 3], &mut chunk, &[2, 2], &[0, 0], 1, true);
+        assert_eq!(chunk, [0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn test_v3_append() {
+        let root = tempdir("zarr-v3");
+        let options = ZarrOptions::new(ZarrFormat::V3)
+            .time_chunk(2)
+            .space_chunk(2);
+        let mut model = model();
+        let mut writer = options.open(&root, &model, &["ramp"]).unwrap();
+        for _ in 0..3 {
+            writer.record(&model).unwrap();
+            model.update().unwrap();
+        }
+        writer.finish().unwrap();
+
+        let json = metadata(&root.join("ramp/zarr.json"));
+        let get = |path: &[&str]| json.get(path).unwrap();
+        assert_eq!(get(&["shape"]).as_usizes().unwrap(), [3, 2, 3]);
+        let chunk_shape = get(&["chunk_grid", "configuration", "chunk_shape"]);
+        assert_eq!(chunk_shape.as_usizes().unwrap(), [2, 2, 2]);
+        assert_eq!(get(&["attributes", "units"]).as_str(), Some("mm"));
+        let grid_type = get(&["attributes", "grid_type"]).as_str();
+        assert_eq!(grid_type, Some("uniform_rectilinear"));
+        // chunk (time 0, y 0, x 1) holds x = 2 and padding
+        let chunk = f64s(&fs::read(root.join("ramp/c/0/0/1")).unwrap());
+        assert_eq!(chunk[0], 0.);
+        assert!(chunk[1].is_nan());
+        assert_eq!(chunk[4], 1.);
+
+        // restart, appending to the partial time chunk
+        let mut writer = options.open(&root, &model, &["ramp"]).unwrap();
+        assert_eq!(writer.records(), 3);
+        let err = writer.record(&model_at(1.)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ZarrError>(),
+            Some(ZarrError::Time { .. })
+        ));
+        writer.record(&model).unwrap();
+        writer.finish().unwrap();
+
+        let time = f64s(&fs::read(root.join("time/c/1")).unwrap());
+        assert_eq!(time, [2., 3.]);
+        let json = metadata(&root.join("time/zarr.json"));
+        assert_eq!(json.get(&["shape"]).unwrap().as_usizes().unwrap(), [4]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    fn model_at(time: f64) -> SyntheticForcing {
+        let mut model = model();
+        model.update_until(time).unwrap();
+        model
+    }
+
+    #[test]
+    fn test_v2_metadata() {
+        let root = tempdir("zarr-v2");
+        let model = model();
+        let options = ZarrOptions::new(ZarrFormat::V2);
+        let mut writer = options.open(&root, &model, &["ramp"]).unwrap();
+        writer.record(&model).unwrap();
+        writer.finish().unwrap();
+
+        let zarray = metadata(&root.join("ramp/.zarray"));
+        assert_eq!(zarray.get(&["dtype"]).unwrap().as_str(), Some("<f8"));
+        let chunks = zarray.get(&["chunks"]).unwrap().as_usizes().unwrap();
+        assert_eq!(chunks, [1, 2, 3]);
+        let zattrs = fs::read_to_string(root.join("ramp/.zattrs")).unwrap();
+        assert!(zattrs.contains("\"_ARRAY_DIMENSIONS\":[\"time\",\"y\",\"x\"]"));
+        assert_eq!(fs::read(root.join("ramp/0/0/0")).unwrap().len(), 6 * 8);
+        assert!(root.join(".zgroup").exists());
+
+        // existing array is on a different grid
+        let mut scalar =
+            SyntheticForcing::new(0., 1., 1.).variable("ramp", "mm", Signal::Constant(1.));
+        scalar.initialize("").unwrap();
+        let err = options.open(&root, &scalar, &["ramp"]).unwrap_err();
+        assert!(err.is::<ZarrError>());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_invalid() {
+        let root = tempdir("zarr-invalid");
+        let model = model();
+        let options = ZarrOptions::default();
+        for names in [&["ramp", "ramp"][..], &["time"], &["a/b"], &[".."], &[""]] {
+            let err = options.open(&root, &model, names).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ZarrError>(),
+                Some(ZarrError::Name(_))
+            ));
+        }
+        assert!(!root.join("zarr.json").exists());
+
+        // an empty grid has a single chunk along each grid dimension and no chunk files
+        let grid = Grid::UniformRectilinear {
+            shape: vec![0, 3],
+            spacing: vec![1., 1.],
+            origin: vec![0., 0.],
+        };
+        let mut empty = SyntheticForcing::new(0., 1., 1.).grid(grid).variable(
+            "empty",
+            "mm",
+            Signal::Constant(1.),
+        );
+        empty.initialize("").unwrap();
+        let mut writer = options.open(&root, &empty, &["empty"]).unwrap();
+        writer.record(&empty).unwrap();
+        writer.finish().unwrap();
+        let json = metadata(&root.join("empty/zarr.json"));
+        let chunk_shape = json.get(&["chunk_grid", "configuration", "chunk_shape"]);
+        assert_eq!(chunk_shape.unwrap().as_usizes().unwrap(), [1, 1, 3]);
+        assert!(!root.join("empty/c").exists());
+
+        // an existing array without a time dimension
+        let metadata = fs::read_to_string(root.join("time/zarr.json")).unwrap();
+        fs::write(
+            root.join("time/zarr.json"),
+            metadata.replace("\"shape\":[1]", "\"shape\":[]"),
+        )
+        .unwrap();
+        let err = options.open(&root, &empty, &["empty"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ZarrError>(),
+            Some(ZarrError::Metadata(_))
+        ));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,663 @@
+use bmi_rs::errors::{BmiInvalidValue, BmiNotImplementedError, BmiUnknownVariable};
+use bmi_rs::names::Names;
+use bmi_rs::{Bmi, BmiResult, GridType, Location, RefValues, ValueType, Values};
+use libloading::Library;
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::fmt;
+use std::path::Path;
+use std::slice;
+
+/// Signature of a bmi-c model registration function, e.g. `register_bmi_simple`.
+pub type RegisterFn = unsafe extern "C" fn(*mut ffi::Bmi) -> *mut ffi::Bmi;
+
+/// A bmi-c function returned `BMI_FAILURE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BmiFailure(pub &'static str);
+
+impl fmt::Display for BmiFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` returned BMI_FAILURE", self.0)
+    }
+}
+
+impl Error for BmiFailure {}
+
+/// Call bmi-c function `$func` on `$self_.handle`.
+/// Evaluates to Err([`BmiNotImplementedError`]) if the function pointer is null and
+/// Err([`BmiFailure`]) if it does not return `BMI_SUCCESS`.
+macro_rules! c_call {
+    ($self_:expr, $func:ident($($arg:expr),*)) => {{
+        let handle = $self_.handle;
+        match unsafe { (*handle).$func } {
+            None => Err(Box::new(BmiNotImplementedError) as Box<dyn Error>),
+            Some(func) => match unsafe { func(handle, $($arg),*) } {
+                ffi::BMI_SUCCESS => Ok(()),
+                _ => Err(Box::new(BmiFailure(stringify!($func))) as Box<dyn Error>),
+            },
+        }
+    }};
+}
+
+macro_rules! c_grid_count {
+    ($self_:expr, $func:ident, $grid:expr) => {{
+        let mut value: c_int = 0;
+        c_call!($self_, $func($grid, &mut value))
+            .ok()
+            .map(|_| value as u32)
+    }};
+}
+
+macro_rules! c_grid_array {
+    ($self_:expr, $func:ident, $grid:expr, $t:ty, $len:expr) => {{
+        match $len {
+            Some(len) => {
+                let mut value: Vec<$t> = vec![Default::default(); len as usize];
+                c_call!($self_, $func($grid, value.as_mut_ptr() as *mut _))
+                    .ok()
+                    .map(|_| value)
+            }
+            None => None,
+        }
+    }};
+}
+
+macro_rules! values_from_raw {
+    ($value_type:expr, $ptr:expr, $len:expr; $($name:ident; $t:ty),*$(,)?) => {
+        match $value_type {
+            $(ValueType::$name => RefValues::$name(slice::from_raw_parts($ptr as *const $t, $len)),)*
+        }
+    };
+}
+
+macro_rules! zeroed_values {
+    ($value_type:expr, $len:expr; $($name:ident; $t:ty),*$(,)?) => {
+        match $value_type {
+            $(ValueType::$name => Values::$name(vec![0 as $t; $len]),)*
+        }
+    };
+}
+
+macro_rules! values_ptr {
+    ($values:expr; $($name:ident),*$(,)?) => {
+        match $values {
+            $(Values::$name(v) => v.as_mut_ptr() as *mut c_void,)*
+        }
+    };
+    (ref $values:expr; $($name:ident),*$(,)?) => {
+        match $values {
+            $(RefValues::$name(v) => v.as_ptr() as *mut c_void,)*
+        }
+    };
+}
+
+#[derive(Debug)]
+struct Var {
+    grid: i32,
+    value_type: ValueType,
+    units: String,
+    itemsize: u32,
+    nbytes: u32,
+    location: Location,
+    /// the model supports `get_value_ptr` for this variable
+    by_ptr: bool,
+}
+
+impl Var {
+    fn len(&self) -> usize {
+        (self.nbytes / self.itemsize.max(1)) as usize
+    }
+}
+
+#[derive(Debug, Default)]
+struct Grid {
+    grid_type: Option<GridType>,
+    rank: Option<u32>,
+    size: Option<u32>,
+    shape: Option<Vec<u32>>,
+    spacing: Option<Vec<f64>>,
+    origin: Option<Vec<f64>>,
+    x: Option<Vec<f64>>,
+    y: Option<Vec<f64>>,
+    z: Option<Vec<f64>>,
+    node_count: Option<u32>,
+    edge_count: Option<u32>,
+    face_count: Option<u32>,
+    edge_nodes: Option<Vec<u32>>,
+    face_edges: Option<Vec<u32>>,
+    face_nodes: Option<Vec<u32>>,
+    nodes_per_face: Option<Vec<u32>>,
+}
+
+fn cached<T: ?Sized>(value: Option<&T>) -> BmiResult<&T> {
+    match value {
+        Some(value) => Ok(value),
+        None => BmiNotImplementedError.into(),
+    }
+}
+
+fn c_name(name: &str) -> BmiResult<CString> {
+    match CString::new(name) {
+        Ok(name) => Ok(name),
+        Err(_) => BmiInvalidValue.into(),
+    }
+}
+
+fn from_buffer(buffer: &[u8]) -> String {
+    CStr::from_bytes_until_nul(buffer)
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// [`Bmi`] implementation backed by a model exposed over the
+/// [bmi-c interface](https://github.com/csdms/bmi-c), e.g. a model in a shared library.
+///
+/// Variable, time units, and grid metadata are read once in [`initialize`].
+/// [`get_value_ptr`] borrows the model's memory if the model supports bmi-c `get_value_ptr`,
+/// otherwise it returns a copy taken via bmi-c `get_value` after each call that can change
+/// the model's state.
+///
+/// No methods may be called after [`finalize`].
+///
+/// [`initialize`]: Bmi::initialize
+/// [`get_value_ptr`]: Bmi::get_value_ptr
+/// [`finalize`]: Bmi::finalize
+pub struct CModel {
+    handle: *mut ffi::Bmi,
+    component_name: String,
+    time_units: String,
+    input_names: Names,
+    output_names: Names,
+    vars: HashMap<String, Var>,
+    grids: HashMap<i32, Grid>,
+    values: HashMap<String, Values>,
+    // NOTE: must be dropped after `handle` is no longer used.
+    _library: Option<Library>,
+}
+
+impl CModel {
+    /// Load the shared library at `path` and register a model using its `symbol` registration
+    /// function.
+    pub fn load(path: impl AsRef<Path>, symbol: &str) -> BmiResult<CModel> {
+        let library = unsafe { Library::new(path.as_ref()) }?;
+        let register = *unsafe { library.get::<RegisterFn>(symbol.as_bytes()) }?;
+        let mut model = unsafe { CModel::register(register) };
+        model._library = Some(library);
+        Ok(model)
+    }
+
+    /// Register a model using `register`.
+    ///
+    /// # Safety
+    /// `register` must populate the provided `ffi::Bmi` with valid bmi-c function pointers.
+    pub unsafe fn register(register: RegisterFn) -> CModel {
+        // Safety: all zeros is a null `data` pointer and `None` function pointers.
+        let handle = Box::into_raw(Box::new(unsafe { std::mem::zeroed::<ffi::Bmi>() }));
+        unsafe { register(handle) };
+        let mut model = CModel {
+            handle,
+            component_name: String::new(),
+            time_units: String::new(),
+            input_names: Names::default(),
+            output_names: Names::default(),
+            vars: HashMap::new(),
+            grids: HashMap::new(),
+            values: HashMap::new(),
+            _library: None,
+        };
+        model.component_name = model.read_str(ffi::BMI_MAX_COMPONENT_NAME, |h, buf| unsafe {
+            (*h).get_component_name.map(|f| f(h, buf))
+        });
+        model
+    }
+
+    fn read_str(
+        &self,
+        max: u32,
+        f: impl FnOnce(*mut ffi::Bmi, *mut c_char) -> Option<c_int>,
+    ) -> String {
+        let mut buffer = vec![0u8; max as usize];
+        match f(self.handle, buffer.as_mut_ptr() as *mut c_char) {
+            Some(ffi::BMI_SUCCESS) => from_buffer(&buffer),
+            _ => String::new(),
+        }
+    }
+
+    fn read_names(&self, input: bool) -> BmiResult<Names> {
+        let mut count: c_int = 0;
+        match input {
+            true => c_call!(self, get_input_item_count(&mut count))?,
+            false => c_call!(self, get_output_item_count(&mut count))?,
+        }
+        let mut buffers = vec![vec![0u8; ffi::BMI_MAX_VAR_NAME as usize]; count.max(0) as usize];
+        let mut ptrs: Vec<*mut c_char> = buffers
+            .iter_mut()
+            .map(|b| b.as_mut_ptr() as *mut c_char)
+            .collect();
+        match input {
+            true => c_call!(self, get_input_var_names(ptrs.as_mut_ptr()))?,
+            false => c_call!(self, get_output_var_names(ptrs.as_mut_ptr()))?,
+        }
+        Ok(Names::new(buffers.iter().map(|b| from_buffer(b))))
+    }
+
+    fn read_var(&self, name: &str) -> BmiResult<Var> {
+        let c_name = c_name(name)?;
+        let mut grid: c_int = 0;
+        c_call!(self, get_var_grid(c_name.as_ptr(), &mut grid))?;
+        let value_type = self
+            .read_str(ffi::BMI_MAX_TYPE_NAME, |h, buf| unsafe {
+                (*h).get_var_type.map(|f| f(h, c_name.as_ptr(), buf))
+            })
+            .parse()?;
+        let units = self.read_str(ffi::BMI_MAX_UNITS_NAME, |h, buf| unsafe {
+            (*h).get_var_units.map(|f| f(h, c_name.as_ptr(), buf))
+        });
+        let mut itemsize: c_int = 0;
+        if c_call!(self, get_var_itemsize(c_name.as_ptr(), &mut itemsize)).is_err() {
+            itemsize = ValueType::bytes(&value_type) as c_int;
+        }
+        let mut nbytes: c_int = 0;
+        c_call!(self, get_var_nbytes(c_name.as_ptr(), &mut nbytes))?;
+        // models commonly report "none", treat anything unknown as a node
+        let location = self
+            .read_str(ffi::BMI_MAX_VAR_NAME, |h, buf| unsafe {
+                (*h).get_var_location.map(|f| f(h, c_name.as_ptr(), buf))
+            })
+            .parse()
+            .unwrap_or(Location::Node);
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let by_ptr =
+            c_call!(self, get_value_ptr(c_name.as_ptr(), &mut ptr)).is_ok() && !ptr.is_null();
+        Ok(Var {
+            grid,
+            value_type,
+            units,
+            itemsize: itemsize as u32,
+            nbytes: nbytes as u32,
+            location,
+            by_ptr,
+        })
+    }
+
+    fn read_grid(&self, grid: i32) -> Grid {
+        let mut g = Grid {
+            grid_type: self
+                .read_str(ffi::BMI_MAX_TYPE_NAME, |h, buf| unsafe {
+                    (*h).get_grid_type.map(|f| f(h, grid, buf))
+                })
+                .parse()
+                .ok(),
+            rank: c_grid_count!(self, get_grid_rank, grid),
+            size: c_grid_count!(self, get_grid_size, grid),
+            node_count: c_grid_count!(self, get_grid_node_count, grid),
+            edge_count: c_grid_count!(self, get_grid_edge_count, grid),
+            face_count: c_grid_count!(self, get_grid_face_count, grid),
+            ..Default::default()
+        };
+        let rank = g.rank.filter(|rank| *rank > 0);
+        g.shape = c_grid_array!(self, get_grid_shape, grid, c_int, rank)
+            .map(|shape| shape.into_iter().map(|v| v as u32).collect());
+        g.spacing = c_grid_array!(self, get_grid_spacing, grid, f64, rank);
+        g.origin = c_grid_array!(self, get_grid_origin, grid, f64, rank);
+
+        let points = g.node_count.or(g.size);
+        let dim = |i: usize| -> Option<u32> {
+            match (g.grid_type, &g.shape) {
+                (Some(GridType::Rectilinear), Some(shape)) if shape.len() > i => {
+                    Some(shape[shape.len() - 1 - i])
+                }
+                (Some(GridType::Rectilinear), _) => None,
+                _ => points,
+            }
+        };
+        let (x_len, y_len, z_len) = (dim(0), dim(1), dim(2));
+        g.x = c_grid_array!(self, get_grid_x, grid, f64, x_len);
+        g.y = c_grid_array!(self, get_grid_y, grid, f64, y_len);
+        g.z = c_grid_array!(self, get_grid_z, grid, f64, z_len);
+
+        let as_u32 = |v: Vec<c_int>| v.into_iter().map(|v| v as u32).collect::<Vec<u32>>();
+        g.edge_nodes = c_grid_array!(
+            self,
+            get_grid_edge_nodes,
+            grid,
+            c_int,
+            g.edge_count.map(|n| n * 2)
+        )
+        .map(as_u32);
+        g.nodes_per_face =
+            c_grid_array!(self, get_grid_nodes_per_face, grid, c_int, g.face_count).map(as_u32);
+        let connectivity = g.nodes_per_face.as_ref().map(|n| n.iter().sum::<u32>());
+        g.face_edges =
+            c_grid_array!(self, get_grid_face_edges, grid, c_int, connectivity).map(as_u32);
+        g.face_nodes =
+            c_grid_array!(self, get_grid_face_nodes, grid, c_int, connectivity).map(as_u32);
+        g
+    }
+
+    /// Copy the values of variables not accessible via bmi-c `get_value_ptr`.
+    fn refresh(&mut self) -> BmiResult<()> {
+        for (name, var) in self.vars.iter() {
+            if var.by_ptr {
+                continue;
+            }
+            let c_name = c_name(name)?;
+            let values = self.values.entry(name.clone()).or_insert_with(|| {
+                zeroed_values!(var.value_type, var.len();
+                    I16;i16, U16;u16, I32;i32, U32;u32, I64;i64, U64;u64, F32;f32, F64;f64,
+                )
+            });
+            let dest = values_ptr!(values; I16, U16, I32, U32, I64, U64, F32, F64);
+            c_call!(self, get_value(c_name.as_ptr(), dest))?;
+        }
+        Ok(())
+    }
+
+    fn var(&self, name: &str) -> BmiResult<&Var> {
+        match self.vars.get(name) {
+            Some(var) => Ok(var),
+            None => BmiUnknownVariable.into(),
+        }
+    }
+
+    fn grid(&self, grid: i32) -> BmiResult<&Grid> {
+        cached(self.grids.get(&grid))
+    }
+
+    fn time(&self, f: impl FnOnce(*mut ffi::Bmi, *mut f64) -> Option<c_int>) -> f64 {
+        let mut time = f64::NAN;
+        match f(self.handle, &mut time) {
+            Some(ffi::BMI_SUCCESS) => time,
+            _ => f64::NAN,
+        }
+    }
+}
+
+impl Drop for CModel {
+    fn drop(&mut self) {
+        // Safety: `handle` was created by `Box::into_raw` in `register`.
+        let _ = unsafe { Box::from_raw(self.handle) };
+    }
+}
+
+impl Bmi for CModel {
+    fn initialize(&mut self, config_file: &str) -> BmiResult<()> {
+        let config_file = c_name(config_file)?;
+        c_call!(self, initialize(config_file.as_ptr()))?;
+
+        let name = self.read_str(ffi::BMI_MAX_COMPONENT_NAME, |h, buf| unsafe {
+            (*h).get_component_name.map(|f| f(h, buf))
+        });
+        if !name.is_empty() {
+            self.component_name = name;
+        }
+        self.time_units = self.read_str(ffi::BMI_MAX_UNITS_NAME, |h, buf| unsafe {
+            (*h).get_time_units.map(|f| f(h, buf))
+        });
+        self.input_names = self.read_names(true)?;
+        self.output_names = self.read_names(false)?;
+
+        self.vars.clear();
+        self.grids.clear();
+        self.values.clear();
+        let names = self.input_names.clone();
+        let names = names.as_slice().iter().chain(self.output_names.as_slice());
+        let names: Vec<String> = names.map(|name| name.to_string()).collect();
+        for name in names {
+            let var = self.read_var(&name)?;
+            if !self.grids.contains_key(&var.grid) {
+                self.grids.insert(var.grid, self.read_grid(var.grid));
+            }
+            self.vars.insert(name, var);
+        }
+        self.refresh()
+    }
+
+    fn update(&mut self) -> BmiResult<()> {
+        c_call!(self, update())?;
+        self.refresh()
+    }
+
+    fn update_until(&mut self, then: f64) -> BmiResult<()> {
+        c_call!(self, update_until(then))?;
+        self.refresh()
+    }
+
+    fn finalize(&mut self) -> BmiResult<()> {
+        c_call!(self, finalize())
+    }
+
+    fn get_component_name(&self) -> &str {
+        &self.component_name
+    }
+
+    fn get_input_var_names(&self) -> &[&str] {
+        self.input_names.as_slice()
+    }
+
+    fn get_output_var_names(&self) -> &[&str] {
+        self.output_names.as_slice()
+    }
+
+    fn get_var_grid(&self, name: &str) -> BmiResult<i32> {
+        Ok(self.var(name)?.grid)
+    }
+
+    fn get_var_type(&self, name: &str) -> BmiResult<ValueType> {
+        Ok(self.var(name)?.value_type)
+    }
+
+    fn get_var_units(&self, name: &str) -> BmiResult<&str> {
+        Ok(&self.var(name)?.units)
+    }
+
+    fn get_var_itemsize(&self, name: &str) -> BmiResult<u32> {
+        Ok(self.var(name)?.itemsize)
+    }
+
+    fn get_var_nbytes(&self, name: &str) -> BmiResult<u32> {
+        Ok(self.var(name)?.nbytes)
+    }
+
+    fn get_var_location(&self, name: &str) -> BmiResult<Location> {
+        Ok(self.var(name)?.location)
+    }
+
+    fn get_current_time(&self) -> f64 {
+        self.time(|h, t| unsafe { (*h).get_current_time.map(|f| f(h, t)) })
+    }
+
+    fn get_start_time(&self) -> f64 {
+        self.time(|h, t| unsafe { (*h).get_start_time.map(|f| f(h, t)) })
+    }
+
+    fn get_end_time(&self) -> f64 {
+        self.time(|h, t| unsafe { (*h).get_end_time.map(|f| f(h, t)) })
+    }
+
+    fn get_time_units(&self) -> &str {
+        &self.time_units
+    }
+
+    fn get_time_step(&self) -> f64 {
+        self.time(|h, t| unsafe { (*h).get_time_step.map(|f| f(h, t)) })
+    }
+
+    fn get_value_ptr(&self, name: &str) -> BmiResult<RefValues<'_>> {
+        let var = self.var(name)?;
+        if !var.by_ptr {
+            return Ok(cached(self.values.get(name))?.into());
+        }
+        let c_name = c_name(name)?;
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        c_call!(self, get_value_ptr(c_name.as_ptr(), &mut ptr))?;
+        if ptr.is_null() {
+            return Err(Box::new(BmiFailure("get_value_ptr")));
+        }
+        // Safety: the model owns `len` items of `value_type` at `ptr`. They are only modified
+        // by calls that require `&mut self`.
+        Ok(unsafe {
+            values_from_raw!(var.value_type, ptr, var.len();
+                I16;i16, U16;u16, I32;i32, U32;u32, I64;i64, U64;u64, F32;f32, F64;f64,
+            )
+        })
+    }
+
+    fn set_value(&mut self, name: &str, src: RefValues) -> BmiResult<()> {
+        let var = self.var(name)?;
+        if src.len() != var.len() || src.value_type() != var.value_type {
+            return BmiInvalidValue.into();
+        }
+        let c_name = c_name(name)?;
+        let src = values_ptr!(ref src; I16, U16, I32, U32, I64, U64, F32, F64);
+        c_call!(self, set_value(c_name.as_ptr(), src))?;
+        self.refresh()
+    }
+
+    fn set_value_at_indices(&mut self, name: &str, inds: &[u32], src: RefValues) -> BmiResult<()> {
+        let var = self.var(name)?;
+        if src.len() != inds.len() || src.value_type() != var.value_type {
+            return BmiInvalidValue.into();
+        }
+        let c_name = c_name(name)?;
+        let mut inds: Vec<c_int> = inds.iter().map(|i| *i as c_int).collect();
+        let src = values_ptr!(ref src; I16, U16, I32, U32, I64, U64, F32, F64);
+        c_call!(
+            self,
+            set_value_at_indices(c_name.as_ptr(), inds.as_mut_ptr(), inds.len() as c_int, src)
+        )?;
+        self.refresh()
+    }
+
+    fn get_grid_type(&self, grid: i32) -> BmiResult<GridType> {
+        cached(self.grid(grid)?.grid_type.as_ref()).copied()
+    }
+
+    fn get_grid_rank(&self, grid: i32) -> BmiResult<u32> {
+        cached(self.grid(grid)?.rank.as_ref()).copied()
+    }
+
+    fn get_grid_size(&self, grid: i32) -> BmiResult<u32> {
+        cached(self.grid(grid)?.size.as_ref()).copied()
+    }
+
+    fn get_grid_shape(&self, grid: i32) -> BmiResult<&[u32]> {
+        cached(self.grid(grid)?.shape.as_deref())
+    }
+
+    fn get_grid_spacing(&self, grid: i32) -> BmiResult<&[f64]> {
+        cached(self.grid(grid)?.spacing.as_deref())
+    }
+
+    fn get_grid_origin(&self, grid: i32) -> BmiResult<&[f64]> {
+        cached(self.grid(grid)?.origin.as_deref())
+    }
+
+    fn get_grid_x(&self, grid: i32) -> BmiResult<&[f64]> {
+        cached(self.grid(grid)?.x.as_deref())
+    }
+
+    fn get_grid_y(&self, grid: i32) -> BmiResult<&[f64]> {
+        cached(self.grid(grid)?.y.as_deref())
+    }
+
+    fn get_grid_z(&self, grid: i32) -> BmiResult<&[f64]> {
+        cached(self.grid(grid)?.z.as_deref())
+    }
+
+    fn get_grid_node_count(&self, grid: i32) -> BmiResult<u32> {
+        cached(self.grid(grid)?.node_count.as_ref()).copied()
+    }
+
+    fn get_grid_edge_count(&self, grid: i32) -> BmiResult<u32> {
+        cached(self.grid(grid)?.edge_count.as_ref()).copied()
+    }
+
+    fn get_grid_face_count(&self, grid: i32) -> BmiResult<u32> {
+        cached(self.grid(grid)?.face_count.as_ref()).copied()
+    }
+
+    fn get_grid_edge_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
+        cached(self.grid(grid)?.edge_nodes.as_deref())
+    }
+
+    fn get_grid_face_edges(&self, grid: i32) -> BmiResult<&[u32]> {
+        cached(self.grid(grid)?.face_edges.as_deref())
+    }
+
+    fn get_grid_face_nodes(&self, grid: i32) -> BmiResult<&[u32]> {
+        cached(self.grid(grid)?.face_nodes.as_deref())
+    }
+
+    fn get_grid_nodes_per_face(&self, grid: i32) -> BmiResult<&[u32]> {
+        cached(self.grid(grid)?.nodes_per_face.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+    use bmi_rs::grid::Grid;
+
+    unsafe extern "C" fn register_synthetic(handle: *mut ffi::Bmi) -> *mut ffi::Bmi {
+        let grid = Grid::UniformRectilinear {
+            shape: vec![2, 3],
+            spacing: vec![10., 20.],
+            origin: vec![0., 5.],
+        };
+        let model = SyntheticForcing::new(0., 10., 2.)
+            .time_units("h")
+            .grid(grid)
+            .variable(
+                "ramp",
+                "mm",
+                Signal::Ramp {
+                    value: 0.,
+                    start: 0.,
+                    slope: 1.,
+                },
+            );
+        bmi_rs::register_model(handle, model);
+        handle
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut model = unsafe { CModel::register(register_synthetic) };
+        assert_eq!(model.get_component_name(), "synthetic forcing");
+        model.initialize("").unwrap();
+
+        assert_eq!(model.get_output_var_names(), ["ramp"]);
+        assert!(model.get_input_var_names().is_empty());
+        assert_eq!(model.get_var_units("ramp").unwrap(), "mm");
+        assert_eq!(model.get_var_type("ramp").unwrap().bytes(), 8);
+        assert_eq!(model.get_var_nbytes("ramp").unwrap(), 48);
+        assert_eq!(model.get_time_units(), "h");
+        assert_eq!(model.get_time_step(), 2.);
+        assert_eq!(model.get_end_time(), 10.);
+
+        let grid = model.get_var_grid("ramp").unwrap();
+        assert!(matches!(
+            model.get_grid_type(grid).unwrap(),
+            GridType::UniformRectilinear
+        ));
+        assert_eq!(model.get_grid_shape(grid).unwrap(), [2, 3]);
+        assert_eq!(model.get_grid_spacing(grid).unwrap(), [10., 20.]);
+        assert_eq!(model.get_grid_origin(grid).unwrap(), [0., 5.]);
+        assert!(model.get_grid_face_nodes(grid).is_err());
+
+        model.update().unwrap();
+        assert_eq!(model.get_current_time(), 2.);
+        assert_eq!(model.get_value_ptr("ramp").unwrap().to_f64_vec(), [2.; 6]);
+        model.update_until(6.).unwrap();
+        let values = model.get_value_at_indices("ramp", &[0, 5]).unwrap();
+        assert_eq!(values.to_f64_vec(), [6., 6.]);
+
+        assert!(model.get_value_ptr("foo").is_err());
+        let err = model.set_value("ramp", RefValues::F64(&[1.])).unwrap_err();
+        assert!(err.is::<BmiInvalidValue>());
+        model.finalize().unwrap();
+    }
+}
@@ -0,0 +1,299 @@
+//! Drive a [`Bmi`] model from start to end time and write selected output variables to CSV.
+//!
+//! The `bmi-run` binary runs models exposed over the
+//! [bmi-c interface](https://github.com/csdms/bmi-c) from a shared library using [`CModel`].
+//! Statically linked Rust models can be run by calling [`run`] directly:
+//! ```no_run
+//! use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+//! use bmi_run::{RunConfig, run};
+//!
+//! let mut model =
+//!     SyntheticForcing::new(0., 10., 1.).variable("precip", "mm", Signal::Constant(1.));
+//! let mut config = RunConfig::new("config.txt");
+//! config.outputs = vec!["precip".to_string()];
+//! let report = run(&mut model, &config, std::io::stdout()).unwrap();
+//! eprintln!("{report}");
+//! ```
+use bmi_rs::Bmi;
+use bmi_rs::clock::STEP_TOLERANCE;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// [`Bmi`] implementation over a bmi-c model.
+pub mod c_model;
+
+pub use crate::c_model::CModel;
+
+/// [`run`] options.
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    /// Passed to [`Bmi::initialize`].
+    pub config_file: String,
+    /// Time to start writing output. The model is spun up to this time without writing output.
+    /// Defaults to the model's start time.
+    pub start: Option<f64>,
+    /// Time to stop. Defaults to the model's end time.
+    pub end: Option<f64>,
+    /// Output variables to write. Arrays are written as one column per element.
+    pub outputs: Vec<String>,
+    /// Write output every `interval` time steps.
+    pub interval: u64,
+}
+
+impl RunConfig {
+    pub fn new(config_file: &str) -> Self {
+        Self {
+            config_file: config_file.to_string(),
+            start: None,
+            end: None,
+            outputs: Vec::new(),
+            interval: 1,
+        }
+    }
+}
+
+/// Phase of a [`run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Initialize,
+    Update,
+    Output,
+    Finalize,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phase = match self {
+            Phase::Initialize => "initialize",
+            Phase::Update => "update",
+            Phase::Output => "output",
+            Phase::Finalize => "finalize",
+        };
+        write!(f, "{phase}")
+    }
+}
+
+/// Error raised during a [`run`] and the [`Phase`] it was raised in.
+#[derive(Debug)]
+pub struct RunError {
+    pub phase: Phase,
+    pub source: Box<dyn Error>,
+}
+
+impl RunError {
+    fn new(phase: Phase, source: impl Into<Box<dyn Error>>) -> Self {
+        Self {
+            phase,
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.phase, self.source)
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Number of time steps taken and wall time spent in each [`Phase`] of a [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub steps: u64,
+    pub initialize: Duration,
+    pub update: Duration,
+    pub output: Duration,
+    pub finalize: Duration,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "steps:      {}", self.steps)?;
+        writeln!(f, "initialize: {:?}", self.initialize)?;
+        writeln!(f, "update:     {:?}", self.update)?;
+        writeln!(f, "output:     {:?}", self.output)?;
+        write!(f, "finalize:   {:?}", self.finalize)
+    }
+}
+
+fn timed<T>(total: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let now = Instant::now();
+    let value = f();
+    *total += now.elapsed();
+    value
+}
+
+fn write_header<M: Bmi>(
+    model: &M,
+    outputs: &[String],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    write!(out, "time")?;
+    for name in outputs {
+        match model.get_value_ptr(name)?.len() {
+            1 => write!(out, ",{name}")?,
+            len => (0..len).try_for_each(|i| write!(out, ",{name}[{i}]"))?,
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+fn write_row<M: Bmi>(
+    model: &M,
+    outputs: &[String],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    write!(out, "{}", model.get_current_time())?;
+    for name in outputs {
+        for value in model.get_value_ptr(name)?.to_f64_vec() {
+            write!(out, ",{value}")?;
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Initialize `model` with `config.config_file`, spin up to `config.start`, update until
+/// `config.end` writing `config.outputs` to `out` as CSV, then finalize.
+///
+/// A row is written at the start time, every `config.interval` time steps after, and at the
+/// end time.
+/// `finalize` is called even if updating or writing output fails.
+pub fn run<M: Bmi>(
+    model: &mut M,
+    config: &RunConfig,
+    mut out: impl Write,
+) -> Result<Report, RunError> {
+    let mut report = Report::default();
+    timed(&mut report.initialize, || {
+        model.initialize(&config.config_file)
+    })
+    .map_err(|e| RunError::new(Phase::Initialize, e))?;
+
+    let result = step(model, config, &mut out, &mut report);
+    let finalized = timed(&mut report.finalize, || model.finalize());
+    result?;
+    finalized.map_err(|e| RunError::new(Phase::Finalize, e))?;
+    Ok(report)
+}
+
+fn step<M: Bmi>(
+    model: &mut M,
+    config: &RunConfig,
+    out: &mut impl Write,
+    report: &mut Report,
+) -> Result<(), RunError> {
+    for name in config.outputs.iter() {
+        if !model.get_output_var_names().contains(&name.as_str()) {
+            return Err(RunError::new(
+                Phase::Output,
+                format!("unknown output variable `{name}`"),
+            ));
+        }
+    }
+    let end = config.end.unwrap_or_else(|| model.get_end_time());
+    if !end.is_finite() || end == f64::MAX {
+        return Err(RunError::new(
+            Phase::Update,
+            "model has no end time, an end time is required",
+        ));
+    }
+    if let Some(start) = config.start
+        && start > model.get_current_time()
+    {
+        timed(&mut report.update, || model.update_until(start))
+            .map_err(|e| RunError::new(Phase::Update, e))?;
+    }
+
+    timed(&mut report.output, || {
+        write_header(model, &config.outputs, out)?;
+        write_row(model, &config.outputs, out)
+    })
+    .map_err(|e| RunError::new(Phase::Output, e))?;
+
+    let interval = config.interval.max(1);
+    let tolerance = model.get_time_step().abs() * STEP_TOLERANCE;
+    while model.get_current_time() < end - tolerance {
+        let current = model.get_current_time();
+        timed(&mut report.update, || model.update())
+            .map_err(|e| RunError::new(Phase::Update, e))?;
+        if model.get_current_time() <= current {
+            return Err(RunError::new(
+                Phase::Update,
+                format!("model time did not advance past {current}"),
+            ));
+        }
+        report.steps += 1;
+        if report.steps.is_multiple_of(interval) {
+            timed(&mut report.output, || {
+                write_row(model, &config.outputs, out)
+            })
+            .map_err(|e| RunError::new(Phase::Output, e))?;
+        }
+    }
+    if !report.steps.is_multiple_of(interval) {
+        timed(&mut report.output, || {
+            write_row(model, &config.outputs, out)
+        })
+        .map_err(|e| RunError::new(Phase::Output, e))?;
+    }
+    timed(&mut report.output, || out.flush()).map_err(|e| RunError::new(Phase::Output, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bmi_rs::forcing::synthetic::{Signal, SyntheticForcing};
+
+    fn model() -> SyntheticForcing {
+        let ramp = Signal::Ramp {
+            value: 0.,
+            start: 0.,
+            slope: 1.,
+        };
+        SyntheticForcing::new(0., 10., 2.).variable("ramp", "mm", ramp)
+    }
+
+    #[test]
+    fn test_run() {
+        let mut config = RunConfig::new("");
+        config.start = Some(2.);
+        config.end = Some(8.);
+        config.interval = 2;
+        config.outputs = vec!["ramp".into()];
+
+        let mut out = Vec::new();
+        let report = run(&mut model(), &config, &mut out).unwrap();
+        assert_eq!(report.steps, 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "time,ramp\n2,2\n6,6\n8,8\n"
+        );
+
+        // the end time row is not repeated
+        config.end = Some(10.);
+        let mut out = Vec::new();
+        let report = run(&mut model(), &config, &mut out).unwrap();
+        assert_eq!(report.steps, 4);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "time,ramp\n2,2\n6,6\n10,10\n"
+        );
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let mut config = RunConfig::new("");
+        config.outputs = vec!["foo".into()];
+        let err = run(&mut model(), &config, std::io::sink()).unwrap_err();
+        assert_eq!(err.phase, Phase::Output);
+    }
+}
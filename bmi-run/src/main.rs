@@ -0,0 +1,115 @@
+use bmi_run::{CModel, RunConfig, run};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Run a bmi-c model from a shared library and write output variables as CSV.
+
+Usage: bmi-run --lib <path> --register <symbol> [options] <config file>
+
+Options:
+  --lib <path>         shared library containing the model
+  --register <symbol>  model registration function, e.g. register_bmi_simple
+  --start <time>       time to start writing output [default: model start time]
+  --end <time>         time to stop [default: model end time]
+  --output <names>     comma separated output variables to write
+  --interval <steps>   write output every <steps> time steps [default: 1]
+  --out <path>         output CSV file [default: stdout]
+  -h, --help           print this message
+";
+
+struct Args {
+    lib: String,
+    register: String,
+    out: Option<String>,
+    config: RunConfig,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, Box<dyn Error>> {
+    let mut lib = None;
+    let mut register = None;
+    let mut out = None;
+    let mut config = RunConfig::new("");
+    let mut config_file = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for `{arg}`"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--lib" => lib = Some(value()?),
+            "--register" => register = Some(value()?),
+            "--start" => config.start = Some(value()?.parse()?),
+            "--end" => config.end = Some(value()?.parse()?),
+            "--output" => {
+                config.outputs = value()?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "--interval" => config.interval = value()?.parse()?,
+            "--out" => out = Some(value()?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`").into()),
+            _ if config_file.is_none() => config_file = Some(arg),
+            _ => return Err(format!("unexpected argument `{arg}`").into()),
+        }
+    }
+    config.config_file = config_file.ok_or("missing config file")?;
+    Ok(Some(Args {
+        lib: lib.ok_or("missing `--lib`")?,
+        register: register.ok_or("missing `--register`")?,
+        out,
+        config,
+    }))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut model = match CModel::load(&args.lib, &args.register) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!(
+                "error: failed to load `{}` from {}: {e}",
+                args.register, args.lib
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let out: Box<dyn Write> = match &args.out {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("error: failed to create {path}: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+
+    match run(&mut model, &args.config, out) {
+        Ok(report) => {
+            eprintln!("{report}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}